@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -12,9 +13,15 @@ use essential_core::{account_service, err_on};
 use essential_data::repo::TypedRepository;
 use essential_data::{account_account, account_entry, account_transaction};
 use essential_test::database_configuration::{insert_euro_currency, setup_schema};
+use fugaso_math::config::{config_hash, find_variant, find_variant_by_hash, select_variant, ConfigVariant};
+use fugaso_math::configurable::{ConfigurableSlotMath, GameDefinition};
+use fugaso_math::config_validator::ConfigValidator;
+use fugaso_math_ed6::config::{bonanza_1000, thunder_express};
 use fugaso_math_ed6::math::ThunderExpressMath;
+use fugaso_math_ed7::config::mega_thunder;
 use fugaso_math_ed7::math::MegaThunderMath;
-use log::info;
+use lazy_static::lazy_static;
+use log::{error, info};
 
 use moka::future::Cache;
 use salvo::conn::TcpListener;
@@ -34,7 +41,8 @@ use fugaso_core::proxy::{
 };
 use fugaso_core::tournament::{TournamentConfig, TournamentGainService, TournamentHolder};
 use fugaso_data::repo::{
-    GameRepository, LaunchInfoRepository, PercentRepository, PromoAccountRepository, PromoStatsRepository, PromoTranRepository, RoundRepository, TournamentGainRepository,
+    FairSeedRepository, GameRepository, LaunchInfoRepository, PercentRepository, PromoAccountRepository, PromoStatsRepository, PromoTranRepository, RoundRepository,
+    TournamentGainRepository,
 };
 use fugaso_data::sequence_generator::{DemoIdGenerator, FugasoIdGenerator, IdGenerator, IdGeneratorFactory};
 use fugaso_data::{common_round, fugaso_action, fugaso_game, fugaso_round, launch_info, promo_account, promo_stats, promo_transaction, tournament_gain};
@@ -52,12 +60,18 @@ use fugaso_test::database_configuration::setup_schema_fugaso_game;
 #[cfg(feature = "redis")]
 use {essential_data::repo::DeferredRepository, mobc_redis::mobc::Pool, mobc_redis::RedisConnectionManager};
 
+lazy_static! {
+    /// config hashes of the definitions of `fugaso_config::GAMES` by their math classes
+    static ref GAME_HASHES: HashMap<&'static str, String> = fugaso_config::GAMES.iter().map(|(c, json)| (*c, config_hash(json, None))).collect();
+}
+
 pub struct ServerConfig<D: IDispacthercontext + Send + Sync> {
     pub p: ApplicationConfig,
     #[cfg(feature = "redis")]
     pub red_pool: Pool<RedisConnectionManager>,
 
     pub round_repo: Arc<RoundRepository>,
+    pub fair_repo: Arc<FairSeedRepository>,
     pub gain_repo: Arc<TournamentGainRepository>,
     pub promo_acc_repo: Arc<PromoAccountRepository>,
     pub promo_stats_repo: Arc<PromoStatsRepository>,
@@ -92,6 +106,7 @@ impl<D: IDispacthercontext + Send + Sync> Clone for ServerConfig<D> {
             admin_config: self.admin_config.clone(),
             p: self.p.clone(),
             round_repo: self.round_repo.clone(),
+            fair_repo: self.fair_repo.clone(),
             gain_repo: self.gain_repo.clone(),
             promo_acc_repo: self.promo_acc_repo.clone(),
             promo_stats_repo: self.promo_stats_repo.clone(),
@@ -166,6 +181,9 @@ impl<D: IDispacthercontext + Send + Sync + 'static> ServerConfig<D> {
         let round_repo = Arc::new(RoundRepository {
             conn: Arc::clone(&base.pool),
         });
+        let fair_repo = Arc::new(FairSeedRepository {
+            conn: Arc::clone(&base.pool),
+        });
         let percent_repo = Arc::new(PercentRepository {
             conn: Arc::clone(&base.pool),
         });
@@ -229,6 +247,7 @@ impl<D: IDispacthercontext + Send + Sync + 'static> ServerConfig<D> {
             #[cfg(feature = "redis")]
             red_pool,
             round_repo,
+            fair_repo,
             percent_repo,
             promo_acc_repo,
             promo_stats_repo,
@@ -266,6 +285,7 @@ impl<D: IDispacthercontext + Send + Sync + 'static> ServerConfig<D> {
             Arc::clone(&self.p.user_settings_repo),
             Arc::clone(&self.table_id_gen),
             Arc::clone(&self.round_repo),
+            Arc::clone(&self.fair_repo),
             Arc::clone(&self.bet_configurator),
             Arc::clone(&self.admin_config),
             math,
@@ -296,7 +316,13 @@ impl<D: IDispacthercontext + Send + Sync + 'static> ServerConfig<D> {
     }
 
     #[cfg(not(any(feature = "server_116_202_218_41_playtech", feature = "server_159_69_70_159_playtech")))]
-    pub async fn create_slot_dispatcher<M: SlotMath + Send + Sync>(&self, math: M, game: fugaso_game::Model) -> Result<SlotDispatcher<M, Self, Self, AdminStateLoader>, ServerError>
+    pub async fn create_slot_dispatcher<M: SlotMath + Send + Sync>(
+        &self,
+        math: M,
+        game: fugaso_game::Model,
+        variant: &str,
+        config_hash: String,
+    ) -> Result<SlotDispatcher<M, Self, Self, AdminStateLoader>, ServerError>
     where
         <M as SlotMath>::Special: Serialize + Sync + Send + 'static,
         <M as SlotMath>::Restore: Serialize + Sync + Send + 'static,
@@ -313,7 +339,8 @@ impl<D: IDispacthercontext + Send + Sync + 'static> ServerConfig<D> {
                 AdminStateLoader {
                     round_repo: Arc::clone(&self.round_repo),
                 },
-            ),
+            )
+            .with_variant(variant, config_hash),
         ))
     }
 
@@ -322,6 +349,8 @@ impl<D: IDispacthercontext + Send + Sync + 'static> ServerConfig<D> {
         math: M,
         game: fugaso_game::Model,
         _replay: bool,
+        variant: &str,
+        config_hash: String,
     ) -> Result<Box<dyn Dispatcher + Sync + Send>, ServerError>
     where
         <M as SlotMath>::Special: Serialize + Sync + Send + 'static,
@@ -331,18 +360,39 @@ impl<D: IDispacthercontext + Send + Sync + 'static> ServerConfig<D> {
         <M as SlotMath>::PlayFSM: Sync + Send + 'static,
         <M as SlotMath>::Calculator: Sync + Send + 'static,
     {
-        Ok(Box::new(self.create_slot_dispatcher(math, game).await?))
+        Ok(Box::new(self.create_slot_dispatcher(math, game, variant, config_hash).await?))
+    }
+
+    /// percent of the operator which selects the variant of a new round, a replayed round keeps its variant
+    async fn operator_percent(&self, game: &fugaso_game::Model, operator_id: Option<i64>, round: Option<&fugaso_round::Model>) -> Result<Option<i32>, ServerError> {
+        match (operator_id, round) {
+            (Some(id), None) => Ok(self.percent_repo.find_recursive_percent(id, game.id).await.map_err(|e| err_on!(e))?.map(|p| p.percent)),
+            _ => Ok(None),
+        }
     }
 
-    pub async fn create_dispatcher(&self, game_name: &str, replay: bool) -> Result<Box<dyn Dispatcher + Sync + Send>, ServerError> {
+    pub async fn create_dispatcher(
+        &self,
+        game_name: &str,
+        replay: bool,
+        operator_id: Option<i64>,
+        round: Option<&fugaso_round::Model>,
+    ) -> Result<Box<dyn Dispatcher + Sync + Send>, ServerError> {
         let g = self.game_service.get_game(game_name).await.map_err(|e| err_on!(e))?.ok_or_else(|| err_on!(GAME_MATH_ERROR))?;
 
-        let (config, reels_cfg) = (None, None);
         if g.math_class == stringify!(ThunderExpressMath) {
-            self.create_game_dispatcher(ThunderExpressMath::new(config, reels_cfg)?, g, replay).await
+            let percent = self.operator_percent(&g, operator_id, round).await?;
+            let (v, hash) = resolve_variant(&thunder_express::VARIANTS, thunder_express::variant_hash, percent, round)?;
+            self.create_game_dispatcher(ThunderExpressMath::new(v.config(), v.reels())?, g, replay, v.id, hash).await
         } else if g.math_class == stringify!(MegaThunderMath) {
-            self.create_game_dispatcher(MegaThunderMath::new(config, reels_cfg)?, g, replay).await
-        }  else {
+            let percent = self.operator_percent(&g, operator_id, round).await?;
+            let (v, hash) = resolve_variant(&mega_thunder::VARIANTS, mega_thunder::variant_hash, percent, round)?;
+            self.create_game_dispatcher(MegaThunderMath::new(v.config(), v.reels())?, g, replay, v.id, hash).await
+        } else if let Some(json) = fugaso_config::find_game(&g.math_class) {
+            let variant = g.math_class.clone();
+            let hash = GAME_HASHES.get(variant.as_str()).cloned().unwrap_or_else(|| config_hash(json, None));
+            self.create_game_dispatcher(ConfigurableSlotMath::new(json)?, g, replay, &variant, hash).await
+        } else {
             Err(err_on!("game is not supported!"))
         }
     }
@@ -486,8 +536,64 @@ impl ApplicationSettings {
 
 const CONFIG_DEFAULT: &str = include_str!("resources/application.json");
 
+/// every variant of the served games is checked before the server starts, so a broken config fails the startup instead of a login
+/// the variant of a replayed round is found by its config hash or by its id for the rounds without a hash,
+/// otherwise the variant is selected by the `percent` of the operator
+pub fn resolve_variant(
+    variants: &'static [ConfigVariant],
+    hash: fn(&ConfigVariant) -> String,
+    percent: Option<i32>,
+    round: Option<&fugaso_round::Model>,
+) -> Result<(&'static ConfigVariant, String), ServerError> {
+    let selected = match round {
+        Some(r) => match (&r.config_hash, &r.variant) {
+            (Some(h), _) => find_variant_by_hash(variants, hash, h).ok_or_else(|| err_on!(format!("config {h} of round {} is absent!", r.id)))?,
+            (None, Some(id)) => find_variant(variants, id).ok_or_else(|| err_on!(format!("config variant {id} is absent!")))?,
+            (None, None) => select_variant(variants, None).ok_or_else(|| err_on!(GAME_MATH_ERROR))?,
+        },
+        None => {
+            let selected = select_variant(variants, percent).ok_or_else(|| err_on!(GAME_MATH_ERROR))?;
+            info!("config variant {} - percent: {percent:?}", selected.id);
+            selected
+        }
+    };
+    Ok((selected, hash(selected)))
+}
+
+pub fn validate_configs() -> Result<(), ServerError> {
+    let definitions = fugaso_config::GAMES.iter().map(|(class, json)| {
+        let mut v = ConfigValidator::new(class);
+        if let Some(definition) = v.parse::<GameDefinition>("definition", json) {
+            v.merge(definition.validate());
+        }
+        v
+    });
+    let validators = thunder_express::VARIANTS
+        .iter()
+        .map(thunder_express::validate)
+        .chain(bonanza_1000::VARIANTS.iter().map(bonanza_1000::validate))
+        .chain(mega_thunder::VARIANTS.iter().map(mega_thunder::validate))
+        .chain(definitions)
+        .collect::<Vec<_>>();
+    let mut invalid = vec![];
+    for v in validators {
+        if v.is_valid() {
+            info!("config {} is valid", v.name);
+        } else {
+            error!("{v}");
+            invalid.push(v.name);
+        }
+    }
+    if invalid.is_empty() {
+        Ok(())
+    } else {
+        Err(err_on!(format!("configs {invalid:?} are invalid!")))
+    }
+}
+
 pub async fn run_server() {
     let app_settings: ApplicationSettings = ApplicationSettings::new().expect("error read application settings!");
+    validate_configs().expect("error validate configs!");
 
     let mut opt = ConnectOptions::new("sqlite::memory:".to_owned());
     opt.max_connections(2).min_connections(2).connect_timeout(Duration::from_secs(8)).idle_timeout(Duration::from_secs(8)).max_lifetime(Duration::from_secs(8)).sqlx_logging(true);
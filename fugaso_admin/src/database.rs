@@ -20,6 +20,15 @@ pub async fn insert_games<C: ConnectionTrait>(db: &C) -> Result<InsertResult<fug
             origin: "LOCAL".to_string(),
             promo: true,
             ..Default::default()
+        },
+        fugaso_game::Model {
+            id: 46,
+            display_name: Some("Lucky Lines".to_string()),
+            game_name: Some("luckylines".to_string()),
+            math_class: "LuckyLinesMath".to_string(),
+            origin: "LOCAL".to_string(),
+            promo: true,
+            ..Default::default()
         }
     ];
     fugaso_game::Entity::insert_many(games.into_iter().map(|m| m.into_active_model())).exec(db).await
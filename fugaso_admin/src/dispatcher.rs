@@ -8,7 +8,10 @@ use essential_core::err_on;
 use essential_core::error::ServerError;
 use essential_data::repo::JackpotRepository;
 use fugaso_core::admin::{InitArg, SlotAdmin, StateLoader, TypedRepoFactory};
-use fugaso_core::protocol::{AdminError, ErrorData, HistoryData, HistoryRequest, IResponse, LoginRequest, PlayerError, PlayerRequest, Response, TournamentData};
+use fugaso_core::protocol::{
+    AdminError, BonusRequest, ErrorData, FairSeedRequest, FairVerifyRequest, GambleRequest, HistoryData, HistoryRequest, IResponse, LoginRequest, PlayerError, PlayerRequest,
+    Response, TournamentData,
+};
 use fugaso_core::proxy::{is_rollback_code, AuthData, JackpotProxyFactory, PromoServiceFactory, PromoValue, RetryServiceFactory, SlotProxy};
 use fugaso_core::tournament::{TournamentPlace, TournamentWinData};
 use fugaso_data::fugaso_round::{self, RoundStatus};
@@ -617,11 +620,26 @@ where
         parent.on_tournament_info().await
     }
 
+    async fn on_fair_seed(&mut self, r: FairSeedRequest) -> Result<ResponseStacked, PlayerError> {
+        let parent = self.parent_mut();
+        parent.on_fair_seed(r).await
+    }
+
+    async fn on_fair_verify(&self, r: FairVerifyRequest) -> Result<ResponseStacked, PlayerError> {
+        let parent = self.parent();
+        parent.on_fair_verify(r).await
+    }
+
     async fn on_spin(&mut self, r: <<Self as SlotBaseDispatcher>::M as SlotMath>::Input) -> Result<ResponseStacked, PlayerError> {
         let parent = self.parent_mut();
         parent.on_spin(r).await
     }
 
+    async fn on_buy_bonus(&mut self, r: <<Self as SlotBaseDispatcher>::M as SlotMath>::Input) -> Result<ResponseStacked, PlayerError> {
+        let parent = self.parent_mut();
+        parent.on_buy_bonus(r).await
+    }
+
     async fn on_respin(&mut self) -> Result<ResponseStacked, PlayerError> {
         let parent = self.parent_mut();
         parent.on_respin().await
@@ -632,6 +650,26 @@ where
         parent.on_free_spin().await
     }
 
+    async fn on_drop(&mut self) -> Result<ResponseStacked, PlayerError> {
+        let parent = self.parent_mut();
+        parent.on_drop().await
+    }
+
+    async fn on_bonus(&mut self, r: BonusRequest) -> Result<ResponseStacked, PlayerError> {
+        let parent = self.parent_mut();
+        parent.on_bonus(r).await
+    }
+
+    async fn on_gamble(&mut self, r: GambleRequest) -> Result<ResponseStacked, PlayerError> {
+        let parent = self.parent_mut();
+        parent.on_gamble(r).await
+    }
+
+    async fn on_half_collect(&mut self) -> Result<ResponseStacked, PlayerError> {
+        let parent = self.parent_mut();
+        parent.on_half_collect().await
+    }
+
     async fn on_collect(&mut self, game_session_id: Option<String>) -> Result<ResponseStacked, PlayerError> {
         let parent = self.parent_mut();
         parent.on_collect(game_session_id).await
@@ -681,6 +719,57 @@ where
             tournament_gains: VecDeque::new(),
         }
     }
+
+    /// wagers the stake of the started round, the stake is the price of the buy for a bought bonus
+    async fn wager_round(&mut self, started: (Response<M::Special, M::Restore>, Round, Action, PromoValue)) -> Result<ResponseStacked, PlayerError> {
+        let (mut spin_response, round, action, promo) = started;
+        let (balance, amount) = match self.proxy.wager(&action, &round, &promo).await {
+            Ok((balance, amount)) => (balance, amount),
+            Err(e) => {
+                if e.rc == err_code::OUT_OF_MONEY_CODE.0 {
+                    self.admin.on_error(action.id, round.id, &e, RoundStatus::DECLINE).await?
+                } else if is_rollback_code(e.rc) {
+                    self.admin.on_error(action.id, round.id, &e, RoundStatus::ROLLBACK).await?
+                } else {
+                    self.admin.on_error(action.id, round.id, &e, RoundStatus::REMOTE_ERROR).await?
+                }
+                return Err(e.into());
+            }
+        };
+        let (jackpot_response, jackpots) = self.proxy.check_jackpots(amount, round.id).await?;
+        let tournament_win = if jackpots == 0 {
+            match self.tournament_gains.pop_front() {
+                None => None,
+                Some(w) => self.proxy.tournament_win(w, round.common_id).await.map(|t| Response::TournamentWin::<M::Special, M::Restore>(t.into())),
+            }
+        } else {
+            None
+        };
+        if self.admin.is_end() {
+            let (close_response, r, a) = self.admin.close_round().await?;
+            spin_response = close_response;
+            let balance_result = self.proxy.result(&a, &r, GameStatus::completed, promo, None).await.map_err(|e| {
+                PlayerError::Admin(AdminError {
+                    round_id: r.id,
+                    action_id: a.id,
+                    error: e,
+                })
+            })?;
+            self.admin.round_result(balance_result).await?;
+        } else {
+            self.admin.round_result(balance).await?;
+        }
+        self.next_id = Uuid::new_v4();
+        let mut packets = vec![spin_response, jackpot_response];
+        if let Some(r) = tournament_win {
+            packets.push(r)
+        }
+        Ok(ResponseStacked {
+            id: Some(self.next_id),
+            answer: Box::new(packets),
+            cache: true,
+        })
+    }
 }
 
 #[async_trait]
@@ -731,6 +820,7 @@ where
                 game: self.proxy.game().clone(),
                 demo: self.proxy.is_demo(),
                 country,
+                operator_id: self.proxy.operator_id,
                 bet_settings: self.proxy.bet_settings.clone(),
                 step_settings: self.proxy.step_settings.clone(),
                 currency: self.proxy.currency()?,
@@ -769,6 +859,9 @@ where
         let mut packets = self.proxy.join().await?;
         let join = self.admin.join(balance).await?;
         packets.push(join);
+        if let Some(fair) = self.admin.fair_seed() {
+            packets.push(fair);
+        }
         self.next_id = Uuid::new_v4();
         Ok(ResponseStacked {
             id: Some(self.next_id),
@@ -841,30 +934,36 @@ where
         })
     }
 
+    async fn on_fair_seed(&mut self, r: FairSeedRequest) -> Result<ResponseStacked, PlayerError> {
+        let response = self.admin.rotate_fair(r.client_seed).await?;
+        Ok(ResponseStacked {
+            id: Some(self.next_id),
+            answer: Box::new(vec![response]),
+            cache: false,
+        })
+    }
+
+    async fn on_fair_verify(&self, r: FairVerifyRequest) -> Result<ResponseStacked, PlayerError> {
+        let response = self.admin.verify_fair(r.round_id).await?;
+        Ok(ResponseStacked {
+            id: Some(self.next_id),
+            answer: Box::new(vec![response]),
+            cache: false,
+        })
+    }
+
     async fn on_spin(&mut self, r: M::Input) -> Result<ResponseStacked, PlayerError> {
-        let (mut spin_response, round, action, promo) = self.admin.spin(self.proxy.balance(), r).await?;
-        let (balance, amount) = match self.proxy.wager(&action, &round, &promo).await {
-            Ok((balance, amount)) => (balance, amount),
-            Err(e) => {
-                if e.rc == err_code::OUT_OF_MONEY_CODE.0 {
-                    self.admin.on_error(action.id, round.id, &e, RoundStatus::DECLINE).await?
-                } else if is_rollback_code(e.rc) {
-                    self.admin.on_error(action.id, round.id, &e, RoundStatus::ROLLBACK).await?
-                } else {
-                    self.admin.on_error(action.id, round.id, &e, RoundStatus::REMOTE_ERROR).await?
-                }
-                return Err(e.into());
-            }
-        };
-        let (jackpot_response, jackpots) = self.proxy.check_jackpots(amount, round.id).await?;
-        let tournament_win = if jackpots == 0 {
-            match self.tournament_gains.pop_front() {
-                None => None,
-                Some(w) => self.proxy.tournament_win(w, round.common_id).await.map(|t| Response::TournamentWin::<M::Special, M::Restore>(t.into())),
-            }
-        } else {
-            None
-        };
+        let started = self.admin.spin(self.proxy.balance(), r).await?;
+        self.wager_round(started).await
+    }
+
+    async fn on_buy_bonus(&mut self, r: M::Input) -> Result<ResponseStacked, PlayerError> {
+        let started = self.admin.buy_bonus(self.proxy.balance(), r).await?;
+        self.wager_round(started).await
+    }
+
+    async fn on_respin(&mut self) -> Result<ResponseStacked, PlayerError> {
+        let (mut spin_response, _round, _action, promo) = self.admin.respin(self.proxy.balance()).await?;
         if self.admin.is_end() {
             let (close_response, r, a) = self.admin.close_round().await?;
             spin_response = close_response;
@@ -876,23 +975,39 @@ where
                 })
             })?;
             self.admin.round_result(balance_result).await?;
-        } else {
-            self.admin.round_result(balance).await?;
         }
         self.next_id = Uuid::new_v4();
-        let mut packets = vec![spin_response, jackpot_response];
-        if let Some(r) = tournament_win {
-            packets.push(r)
+        Ok(ResponseStacked {
+            id: Some(self.next_id),
+            answer: Box::new(vec![spin_response]),
+            cache: true,
+        })
+    }
+
+    async fn on_free_spin(&mut self) -> Result<ResponseStacked, PlayerError> {
+        let (mut spin_response, _round, _action, promo) = self.admin.free_spin(self.proxy.balance()).await?;
+        if self.admin.is_end() {
+            let (close_response, r, a) = self.admin.close_round().await?;
+            spin_response = close_response;
+            let balance_result = self.proxy.result(&a, &r, GameStatus::completed, promo, None).await.map_err(|e| {
+                PlayerError::Admin(AdminError {
+                    round_id: r.id,
+                    action_id: a.id,
+                    error: e,
+                })
+            })?;
+            self.admin.round_result(balance_result).await?;
         }
+        self.next_id = Uuid::new_v4();
         Ok(ResponseStacked {
             id: Some(self.next_id),
-            answer: Box::new(packets),
+            answer: Box::new(vec![spin_response]),
             cache: true,
         })
     }
 
-    async fn on_respin(&mut self) -> Result<ResponseStacked, PlayerError> {
-        let (mut spin_response, _round, _action, promo) = self.admin.respin(self.proxy.balance()).await?;
+    async fn on_drop(&mut self) -> Result<ResponseStacked, PlayerError> {
+        let (mut spin_response, _round, _action, promo) = self.admin.drop(self.proxy.balance()).await?;
         if self.admin.is_end() {
             let (close_response, r, a) = self.admin.close_round().await?;
             spin_response = close_response;
@@ -913,8 +1028,30 @@ where
         })
     }
 
-    async fn on_free_spin(&mut self) -> Result<ResponseStacked, PlayerError> {
-        let (mut spin_response, _round, _action, promo) = self.admin.free_spin(self.proxy.balance()).await?;
+    async fn on_bonus(&mut self, r: BonusRequest) -> Result<ResponseStacked, PlayerError> {
+        let (mut spin_response, _round, _action, promo) = self.admin.bonus(self.proxy.balance(), r.pick).await?;
+        if self.admin.is_end() {
+            let (close_response, r, a) = self.admin.close_round().await?;
+            spin_response = close_response;
+            let balance_result = self.proxy.result(&a, &r, GameStatus::completed, promo, None).await.map_err(|e| {
+                PlayerError::Admin(AdminError {
+                    round_id: r.id,
+                    action_id: a.id,
+                    error: e,
+                })
+            })?;
+            self.admin.round_result(balance_result).await?;
+        }
+        self.next_id = Uuid::new_v4();
+        Ok(ResponseStacked {
+            id: Some(self.next_id),
+            answer: Box::new(vec![spin_response]),
+            cache: true,
+        })
+    }
+
+    async fn on_gamble(&mut self, r: GambleRequest) -> Result<ResponseStacked, PlayerError> {
+        let (mut spin_response, _round, _action, promo) = self.admin.gamble(self.proxy.balance(), r.choice).await?;
         if self.admin.is_end() {
             let (close_response, r, a) = self.admin.close_round().await?;
             spin_response = close_response;
@@ -935,6 +1072,16 @@ where
         })
     }
 
+    async fn on_half_collect(&mut self) -> Result<ResponseStacked, PlayerError> {
+        let response = self.admin.half_collect(self.proxy.balance()).await?;
+        self.next_id = Uuid::new_v4();
+        Ok(ResponseStacked {
+            id: Some(self.next_id),
+            answer: Box::new(vec![response]),
+            cache: true,
+        })
+    }
+
     async fn on_collect(&mut self, game_session_id: Option<String>) -> Result<ResponseStacked, PlayerError> {
         let (collect_response, round, action, status, promo) = self.admin.collect(self.proxy.balance()).await?;
         let balance_result = self.proxy.result(&action, &round, status, promo, game_session_id).await.map_err(|e| {
@@ -1016,11 +1163,18 @@ where
         }
         let r = match request {
             PlayerRequest::BetSpin(r) => self.on_spin(r).await,
+            PlayerRequest::BuyBonus(r) => self.on_buy_bonus(r).await,
             PlayerRequest::ReSpin => self.on_respin().await,
             PlayerRequest::FreeSpin => self.on_free_spin().await,
+            PlayerRequest::Drop => self.on_drop().await,
+            PlayerRequest::Bonus(r) => self.on_bonus(r).await,
+            PlayerRequest::Gamble(r) => self.on_gamble(r).await,
+            PlayerRequest::HalfCollect => self.on_half_collect().await,
             PlayerRequest::Collect => self.on_collect(None).await,
             PlayerRequest::TournamentInfo => self.on_tournament_info().await,
             PlayerRequest::History(r) => self.on_history(r).await,
+            PlayerRequest::FairSeed(r) => self.on_fair_seed(r).await,
+            PlayerRequest::FairVerify(r) => self.on_fair_verify(r).await,
             PlayerRequest::Login(_) => Err(PlayerError::Account(acc_err(err_code::NOT_LOGGED_ON, line!(), file!()))),
         };
         match r {
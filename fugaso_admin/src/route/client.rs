@@ -184,7 +184,7 @@ pub async fn handle_on<D: IDispacthercontext + Send + Sync + 'static>(
         };
 
         let dispatcher = cfg
-            .create_dispatcher(&player_req.session.game_name, round_id.is_some())
+            .create_dispatcher(&player_req.session.game_name, round_id.is_some(), player_req.session.operator_id, round_actions.as_ref().map(|r| &r.0))
             .await
             .map_err(|e| {
                 error!("{e}");
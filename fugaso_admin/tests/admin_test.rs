@@ -0,0 +1,518 @@
+mod integration;
+mod session;
+
+use fugaso_admin::config::resolve_variant;
+use fugaso_admin::dispatcher::SlotBaseDispatcher;
+use fugaso_core::protocol::{BonusRequest, FairSeedRequest, FairVerifyRequest, GambleRequest};
+use fugaso_data::fugaso_action::ActionKind;
+use fugaso_data::fugaso_round;
+use fugaso_math::config::config_hash;
+use fugaso_math::configurable::ConfigurableSlotMath;
+use fugaso_math::fsm::FsmDefinition;
+use fugaso_math::gamble::GambleChoice;
+use fugaso_math::math::{Request, Step};
+use fugaso_math_ed6::config::thunder_express;
+use fugaso_math_ed6::math::ThunderExpressMath;
+use fugaso_math_ed6::rand::ThunderExpressRandom;
+use fugaso_test::database_configuration::{setup_schema_fugaso_action, setup_schema_fugaso_fair_seed, setup_schema_fugaso_round};
+use integration::create_connection;
+use sea_orm::IntoActiveModel;
+use serde_json::Value;
+use session::{create_session_cfg, game_data, join_session, packets, SessionConfig, SessionDispatcher, OPERATOR_ID};
+
+const GAME_NAME: &str = "luckylines";
+const MATH_CLASS: &str = "LuckyLinesMath";
+
+fn lucky_lines() -> Value {
+    serde_json::from_str(fugaso_config::find_game(MATH_CLASS).expect("error find game!")).expect("error parse game!")
+}
+
+fn create_request() -> Request {
+    Request {
+        bet: 1,
+        line: 10,
+        denom: 1,
+        bet_counter: 1,
+        reels: 5,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_acts() {
+    let cfg = create_session_cfg(create_connection().await, fugaso_config::BETS).await;
+    // the fsm of the game lets the drop go from the bet, the admin does not declare it
+    let mut fsm = FsmDefinition::default();
+    fsm.transitions.get_mut(&ActionKind::BET).expect("error bet state!").insert(ActionKind::DROP, ActionKind::DROP);
+    let mut definition = lucky_lines();
+    definition["fsm"] = serde_json::to_value(&fsm).expect("error fsm!");
+    let json = definition.to_string();
+    let math = ConfigurableSlotMath::new(&json).expect("error math!");
+    let (mut dispatcher, _) = join_session(&cfg, math, GAME_NAME, MATH_CLASS, config_hash(&json, None), None, None).await;
+    let balance = dispatcher.proxy.balance();
+
+    assert!(dispatcher.on_drop().await.is_err());
+    assert!(dispatcher.on_respin().await.is_err());
+    assert!(dispatcher.on_free_spin().await.is_err());
+    assert!(dispatcher
+        .on_bonus(BonusRequest {
+            pick: 0
+        })
+        .await
+        .is_err());
+    assert!(dispatcher
+        .on_gamble(GambleRequest {
+            choice: GambleChoice::Red
+        })
+        .await
+        .is_err());
+    assert!(dispatcher.on_half_collect().await.is_err());
+    assert!(dispatcher.on_collect(None).await.is_err());
+    assert_eq!(dispatcher.proxy.balance(), balance);
+
+    // the rejected acts leave the fsm on the bet
+    let spin = packets(dispatcher.on_spin(create_request()).await.expect("error spin!"));
+    assert_eq!(game_data(&spin)["subType"], "SPIN");
+}
+
+/// every grid wins on the top row, the cascades go on until the cap of the round
+const CASCADE_JSON: &str = r#"{
+    "cols": 3,
+    "rows": 3,
+    "reels": [["AAAA", "AAAA", "AAAA"]],
+    "evaluator": "LINES",
+    "lines": ["^^^"],
+    "wins": {"A": {"3": 2}},
+    "maxWin": 10,
+    "cascade": [1, 2, 3]
+}"#;
+
+fn create_cascade_request() -> Request {
+    Request {
+        bet: 1,
+        line: 1,
+        denom: 1,
+        bet_counter: 1,
+        reels: 3,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_drop() {
+    let cfg = create_session_cfg(create_connection().await, fugaso_config::BETS).await;
+    let math = ConfigurableSlotMath::new(CASCADE_JSON).expect("error math!");
+    let (mut dispatcher, _) = join_session(&cfg, math, GAME_NAME, MATH_CLASS, config_hash(CASCADE_JSON, None), None, None).await;
+    let balance = dispatcher.proxy.balance();
+
+    let spin = packets(dispatcher.on_spin(create_cascade_request()).await.expect("error spin!"));
+    assert_eq!(game_data(&spin)["nextAct"], "DROP");
+    assert_eq!(dispatcher.proxy.balance(), balance - 1);
+    // the cascades go before the next round and the collect
+    assert!(dispatcher.on_spin(create_cascade_request()).await.is_err());
+    assert!(dispatcher.on_collect(None).await.is_err());
+
+    let drop = packets(dispatcher.on_drop().await.expect("error drop!"));
+    let data = game_data(&drop);
+    assert_eq!((data["subType"].as_str(), data["nextAct"].as_str()), (Some("DROP"), Some("DROP")));
+    assert_eq!(data["result"]["total"], 6);
+    let drop = packets(dispatcher.on_drop().await.expect("error drop!"));
+    let data = game_data(&drop);
+    assert_eq!((data["subType"].as_str(), data["nextAct"].as_str()), (Some("DROP"), Some("COLLECT")));
+    assert_eq!(data["result"]["total"], 10);
+    assert!(dispatcher.on_drop().await.is_err());
+    assert_eq!(dispatcher.proxy.balance(), balance - 1);
+
+    let collect = packets(dispatcher.on_collect(None).await.expect("error collect!"));
+    assert_eq!(game_data(&collect)["subType"], "COLLECT");
+    assert_eq!(dispatcher.proxy.balance(), balance - 1 + 10);
+}
+
+#[tokio::test]
+async fn test_drop_restore() {
+    let cfg = create_session_cfg(create_connection().await, fugaso_config::BETS).await;
+    let math = ConfigurableSlotMath::new(CASCADE_JSON).expect("error math!");
+    let (mut dispatcher, _) = join_session(&cfg, math, GAME_NAME, MATH_CLASS, config_hash(CASCADE_JSON, None), None, None).await;
+    let balance = dispatcher.proxy.balance();
+    let (_, round, spin, _) = dispatcher.admin.spin(balance, create_cascade_request()).await.expect("error spin!");
+    let (_, _, drop, _) = dispatcher.admin.drop(balance).await.expect("error drop!");
+    assert_eq!(drop.act_descr, Some(ActionKind::DROP));
+    assert_eq!(drop.next_act, Some(ActionKind::DROP));
+
+    // the next session goes on with the second cascade and its multiplier
+    let math = ConfigurableSlotMath::new(CASCADE_JSON).expect("error math!");
+    let (mut dispatcher, joined) = join_session(&cfg, math, GAME_NAME, MATH_CLASS, config_hash(CASCADE_JSON, None), None, Some((round, vec![spin, drop]))).await;
+    assert_eq!(game_data(&joined)["nextAct"], "DROP");
+    let drop = packets(dispatcher.on_drop().await.expect("error drop!"));
+    let data = game_data(&drop);
+    assert_eq!(data["result"]["wons"][0]["multi"], 3);
+    assert_eq!(data["result"]["total"], 10);
+    assert_eq!(data["nextAct"], "COLLECT");
+}
+
+/// every grid starts the pick bonus of two picks, the spin pays 1 and a pick pays 5
+const BONUS_JSON: &str = r#"{
+    "cols": 3,
+    "rows": 3,
+    "reels": [["KKKK", "KKKK", "KKKK"]],
+    "evaluator": "LINES",
+    "lines": ["^^^"],
+    "wins": {"K": {"3": 1}},
+    "bonus": {"symbol": "K", "trigger": 3, "prizes": [{"kind": "WIN", "value": 5}, {"kind": "WIN", "value": 5}, {"kind": "WIN", "value": 5}], "picks": 2}
+}"#;
+
+#[tokio::test]
+async fn test_bonus() {
+    let cfg = create_session_cfg(create_connection().await, fugaso_config::BETS).await;
+    let math = ConfigurableSlotMath::new(BONUS_JSON).expect("error math!");
+    let (mut dispatcher, _) = join_session(&cfg, math, GAME_NAME, MATH_CLASS, config_hash(BONUS_JSON, None), None, None).await;
+    let balance = dispatcher.proxy.balance();
+
+    let spin = packets(dispatcher.on_spin(create_cascade_request()).await.expect("error spin!"));
+    assert_eq!(game_data(&spin)["nextAct"], "BONUS");
+    assert_eq!(dispatcher.proxy.balance(), balance - 1);
+    // the picks go before the next round and the collect
+    assert!(dispatcher.on_spin(create_cascade_request()).await.is_err());
+    assert!(dispatcher.on_collect(None).await.is_err());
+
+    let bonus = packets(
+        dispatcher
+            .on_bonus(BonusRequest {
+                pick: 0,
+            })
+            .await
+            .expect("error bonus!"),
+    );
+    let data = game_data(&bonus);
+    assert_eq!((data["subType"].as_str(), data["nextAct"].as_str()), (Some("BONUS"), Some("BONUS")));
+    assert_eq!(data["result"]["total"], 6);
+    assert_eq!(data["bonus"]["left"], 1);
+    // a cell is picked once
+    assert!(dispatcher
+        .on_bonus(BonusRequest {
+            pick: 0
+        })
+        .await
+        .is_err());
+
+    let bonus = packets(
+        dispatcher
+            .on_bonus(BonusRequest {
+                pick: 1,
+            })
+            .await
+            .expect("error bonus!"),
+    );
+    let data = game_data(&bonus);
+    assert_eq!((data["subType"].as_str(), data["nextAct"].as_str()), (Some("BONUS"), Some("COLLECT")));
+    assert_eq!(data["result"]["total"], 11);
+    assert_eq!(data["bonus"]["finished"], true);
+    assert!(dispatcher
+        .on_bonus(BonusRequest {
+            pick: 2
+        })
+        .await
+        .is_err());
+    assert_eq!(dispatcher.proxy.balance(), balance - 1);
+
+    let collect = packets(dispatcher.on_collect(None).await.expect("error collect!"));
+    assert_eq!(game_data(&collect)["subType"], "COLLECT");
+    assert_eq!(dispatcher.proxy.balance(), balance - 1 + 11);
+}
+
+#[tokio::test]
+async fn test_bonus_restore() {
+    let cfg = create_session_cfg(create_connection().await, fugaso_config::BETS).await;
+    let math = ConfigurableSlotMath::new(BONUS_JSON).expect("error math!");
+    let (mut dispatcher, _) = join_session(&cfg, math, GAME_NAME, MATH_CLASS, config_hash(BONUS_JSON, None), None, None).await;
+    let balance = dispatcher.proxy.balance();
+    let (_, round, spin, _) = dispatcher.admin.spin(balance, create_cascade_request()).await.expect("error spin!");
+    let (_, _, bonus, _) = dispatcher.admin.bonus(balance, 0).await.expect("error bonus!");
+    assert_eq!(bonus.act_descr, Some(ActionKind::BONUS));
+    assert_eq!(bonus.next_act, Some(ActionKind::BONUS));
+    assert!(bonus.choice.is_some());
+
+    // the next session goes on with the board of the choice
+    let math = ConfigurableSlotMath::new(BONUS_JSON).expect("error math!");
+    let (mut dispatcher, joined) = join_session(&cfg, math, GAME_NAME, MATH_CLASS, config_hash(BONUS_JSON, None), None, Some((round, vec![spin, bonus]))).await;
+    assert_eq!(game_data(&joined)["nextAct"], "BONUS");
+    assert!(dispatcher
+        .on_bonus(BonusRequest {
+            pick: 0
+        })
+        .await
+        .is_err());
+    let bonus = packets(
+        dispatcher
+            .on_bonus(BonusRequest {
+                pick: 2,
+            })
+            .await
+            .expect("error bonus!"),
+    );
+    let data = game_data(&bonus);
+    assert_eq!(data["result"]["total"], 11);
+    assert_eq!(data["nextAct"], "COLLECT");
+}
+
+/// every grid pays 2 on the top row and the win is gambled before the collect
+const GAMBLE_JSON: &str = r#"{
+    "cols": 3,
+    "rows": 3,
+    "reels": [["AAAA", "AAAA", "AAAA"]],
+    "evaluator": "LINES",
+    "lines": ["^^^"],
+    "wins": {"A": {"3": 2}},
+    "gamble": true
+}"#;
+
+#[tokio::test]
+async fn test_gamble() {
+    let cfg = create_session_cfg(create_connection().await, fugaso_config::BETS).await;
+    let math = ConfigurableSlotMath::new(GAMBLE_JSON).expect("error math!");
+    let (mut dispatcher, _) = join_session(&cfg, math, GAME_NAME, MATH_CLASS, config_hash(GAMBLE_JSON, None), None, None).await;
+    let balance = dispatcher.proxy.balance();
+
+    let spin = packets(dispatcher.on_spin(create_cascade_request()).await.expect("error spin!"));
+    assert_eq!(game_data(&spin)["nextAct"], "COLLECT");
+    assert_eq!(dispatcher.proxy.balance(), balance - 1);
+
+    // the half collect banks 1 and leaves 1 for the gamble
+    let half = packets(dispatcher.on_half_collect().await.expect("error half collect!"));
+    let data = game_data(&half);
+    assert_eq!((data["subType"].as_str(), data["nextAct"].as_str()), (Some("GAMBLE"), Some("COLLECT")));
+    assert_eq!(data["result"]["total"], 2);
+    assert_eq!(data["gamble"]["banked"], 1);
+    assert!(dispatcher.on_half_collect().await.is_err());
+
+    let gamble = packets(
+        dispatcher
+            .on_gamble(GambleRequest {
+                choice: GambleChoice::Red,
+            })
+            .await
+            .expect("error gamble!"),
+    );
+    let data = game_data(&gamble);
+    assert_eq!(data["subType"], "GAMBLE");
+    let total = if data["gamble"]["history"][0]["color"] == "RED" {
+        assert_eq!(data["nextAct"], "COLLECT");
+        3
+    } else {
+        assert_eq!(data["nextAct"], "GAMBLE_END");
+        1
+    };
+    assert_eq!(data["result"]["total"], total);
+    assert_eq!(dispatcher.proxy.balance(), balance - 1);
+
+    let collect = packets(dispatcher.on_collect(None).await.expect("error collect!"));
+    assert_eq!(game_data(&collect)["subType"], "COLLECT");
+    assert_eq!(dispatcher.proxy.balance(), balance - 1 + total);
+    // the next round starts without the gamble
+    let spin = packets(dispatcher.on_spin(create_cascade_request()).await.expect("error spin!"));
+    assert_eq!(game_data(&spin)["result"]["total"], 2);
+}
+
+/// the bets of the games with `value` as the entry `key` of the bets of `clazz`
+fn bets_with(clazz: &str, key: &str, value: Value) -> String {
+    let mut bets: Value = serde_json::from_str(fugaso_config::BETS).expect("error parse bets!");
+    let entry = bets.as_array_mut().expect("error bets list!").iter_mut().find(|b| b["clazz"] == clazz).expect("error find bets!");
+    entry[key] = value;
+    bets.to_string()
+}
+
+/// the bets of the games with `buy` as the bonus buy of the thunder express
+fn bets_with_buy(buy: Value) -> String {
+    bets_with("ThunderExpressMath", "buy", buy)
+}
+
+async fn join_thunder_express(cfg: &SessionConfig, country: Option<&str>) -> SessionDispatcher<ThunderExpressMath<ThunderExpressRandom>> {
+    let math = ThunderExpressMath::new(None, None).expect("error math!");
+    let variant = &thunder_express::VARIANTS[0];
+    let (dispatcher, _) = join_session(cfg, math, "thunderexpress", variant.id, thunder_express::variant_hash(variant), country, None).await;
+    dispatcher
+}
+
+fn create_buy_request() -> Request {
+    let settings = thunder_express::CFG.settings();
+    Request {
+        bet: 2,
+        line: settings.lines[0],
+        denom: 10,
+        bet_counter: 1,
+        reels: settings.reels[0],
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_buy_bonus() {
+    let bets = bets_with_buy(serde_json::json!({"multiplier": 100, "countryExclusion": ["GB"]}));
+    let cfg = create_session_cfg(create_connection().await, &bets).await;
+    let mut dispatcher = join_thunder_express(&cfg, Some("FR")).await;
+    let balance = dispatcher.proxy.balance();
+
+    let buy = packets(dispatcher.on_buy_bonus(create_buy_request()).await.expect("error buy!"));
+    let data = game_data(&buy);
+    // the price is the playing bet 2 x 10 by the multiplier
+    let price = 2 * 10 * 100;
+    assert_eq!(data["balance"].as_i64(), Some(balance - price));
+    let win = if data["nextAct"] == "RESPIN" {
+        0
+    } else {
+        data["result"]["total"].as_i64().expect("error total!")
+    };
+    assert_eq!(dispatcher.proxy.balance(), balance - price + win);
+}
+
+#[tokio::test]
+async fn test_buy_bonus_disabled() {
+    let excluded = [
+        (serde_json::json!({"multiplier": 0}), Some("FR")),
+        (serde_json::json!({"multiplier": 100, "countryExclusion": ["GB"]}), Some("GB")),
+        // the unknown country can not be checked against the exclusions
+        (serde_json::json!({"multiplier": 100, "countryExclusion": ["GB"]}), None),
+        (serde_json::json!({"multiplier": 100, "operatorExclusion": [OPERATOR_ID]}), Some("FR")),
+    ];
+    for (buy, country) in excluded {
+        let cfg = create_session_cfg(create_connection().await, &bets_with_buy(buy.clone())).await;
+        let mut dispatcher = join_thunder_express(&cfg, country).await;
+        let balance = dispatcher.proxy.balance();
+        assert!(dispatcher.on_buy_bonus(create_buy_request()).await.is_err(), "{buy} {country:?}");
+        assert_eq!(dispatcher.proxy.balance(), balance);
+        // the rejected buy leaves the round to the spin
+        let spin = packets(dispatcher.on_spin(create_buy_request()).await.expect("error spin!"));
+        assert_eq!(game_data(&spin)["subType"], "SPIN");
+    }
+}
+
+#[tokio::test]
+async fn test_variant_by_percent() {
+    let cfg = create_session_cfg(create_connection().await, fugaso_config::BETS).await;
+    let (variant, hash) = resolve_variant(&thunder_express::VARIANTS, thunder_express::variant_hash, Some(94), None).expect("error variant!");
+    assert_eq!(variant.id, "thunder_express_94");
+    assert_ne!(hash, thunder_express::variant_hash(&thunder_express::VARIANTS[0]));
+
+    // the round is played by the config of the variant and keeps its id and hash
+    let math = ThunderExpressMath::new(variant.config(), variant.reels()).expect("error math!");
+    let (mut dispatcher, _) = join_session(&cfg, math, "thunderexpress", variant.id, hash.clone(), None, None).await;
+    let balance = dispatcher.proxy.balance();
+    let (_, round, _, _) = dispatcher.admin.spin(balance, create_buy_request()).await.expect("error spin!");
+    assert_eq!((round.variant.as_deref(), round.config_hash.as_ref()), (Some("thunder_express_94"), Some(&hash)));
+
+    // the replay finds the variant of the round by its hash whatever the percent of the operator is now
+    let (replayed, _) = resolve_variant(&thunder_express::VARIANTS, thunder_express::variant_hash, Some(92), Some(&round)).expect("error variant!");
+    assert_eq!(replayed.id, "thunder_express_94");
+    // a percent without a certified variant falls back to the default one
+    let (fallback, _) = resolve_variant(&thunder_express::VARIANTS, thunder_express::variant_hash, Some(90), None).expect("error variant!");
+    assert_eq!(fallback.id, thunder_express::VARIANTS[0].id);
+}
+
+/// no grid pays, so every spin closes its round
+const NO_WIN_JSON: &str = r#"{
+    "cols": 3,
+    "rows": 3,
+    "reels": [["AAAA", "BBBB", "AAAA"]],
+    "evaluator": "LINES",
+    "lines": ["^^^"],
+    "wins": {"A": {"3": 2}}
+}"#;
+
+async fn join_fair(cfg: &SessionConfig) -> SessionDispatcher<ConfigurableSlotMath> {
+    let math = ConfigurableSlotMath::new(NO_WIN_JSON).expect("error math!");
+    let (dispatcher, joined) = join_session(cfg, math, GAME_NAME, MATH_CLASS, config_hash(NO_WIN_JSON, None), None, None).await;
+    assert!(joined.iter().any(|p| p["kind"] == "FAIR_SEED"));
+    dispatcher
+}
+
+/// a closed round of the session stored as the real store keeps it, the rounds of the sessions are stored for one player
+async fn play_fair(cfg: &SessionConfig, dispatcher: &mut SessionDispatcher<ConfigurableSlotMath>, user_id: Option<i64>) -> fugaso_round::Model {
+    let balance = dispatcher.proxy.balance();
+    let (_, _, spin, _) = dispatcher.admin.spin(balance, create_cascade_request()).await.expect("error spin!");
+    assert!(dispatcher.admin.is_end());
+    let (_, round, _) = dispatcher.admin.close_round().await.expect("error close!");
+    let round = fugaso_round::Model {
+        user_id: user_id.or(round.user_id),
+        ..round
+    };
+    assert!(round.seed_id.is_some());
+    cfg.round_repo.insert(round.clone().into_active_model(), spin.into_active_model()).await.expect("error store round!");
+    round
+}
+
+async fn verify_fair(dispatcher: &SessionDispatcher<ConfigurableSlotMath>, round: &fugaso_round::Model) -> Option<Value> {
+    let request = FairVerifyRequest {
+        round_id: round.common_id.expect("error common id!"),
+    };
+    dispatcher.on_fair_verify(request).await.ok().map(|r| packets(r).remove(0))
+}
+
+#[tokio::test]
+async fn test_fair_sessions() {
+    let pool = create_connection().await;
+    setup_schema_fugaso_round(&pool).await;
+    setup_schema_fugaso_action(&pool).await;
+    setup_schema_fugaso_fair_seed(&pool).await;
+    let cfg = create_session_cfg(pool, &bets_with(MATH_CLASS, "fair", serde_json::json!({"operator": [OPERATOR_ID]}))).await;
+
+    // the player plays on two devices, each session has its own server seed
+    let mut first = join_fair(&cfg).await;
+    let mut second = join_fair(&cfg).await;
+    let own = play_fair(&cfg, &mut first, None).await;
+    let other = play_fair(&cfg, &mut second, own.user_id).await;
+    assert_ne!(own.seed_id, other.seed_id);
+
+    // the seed of the other session is not revealed while it plays it
+    assert!(verify_fair(&first, &other).await.is_none());
+    assert!(verify_fair(&first, &own).await.is_none());
+
+    // the rotation retires the seed of the other session only
+    let rotated = packets(
+        second
+            .on_fair_seed(FairSeedRequest {
+                client_seed: None,
+            })
+            .await
+            .expect("error rotate!"),
+    );
+    let server_seed = rotated[0]["reveal"]["serverSeed"].as_str().expect("error reveal!").to_string();
+    let verified = verify_fair(&first, &other).await.expect("error verify!");
+    assert_eq!(verified["kind"], "FAIR_VERIFY");
+    assert_eq!((verified["seeds"]["seedId"].as_i64(), verified["seeds"]["serverSeed"].as_str()), (other.seed_id, Some(server_seed.as_str())));
+    assert_eq!(verified["seeds"]["serverHash"].as_str(), other.fair_hash.as_deref());
+    assert!(verify_fair(&first, &own).await.is_none());
+
+    // the next seed of the other session is retired by the end of the session
+    let next = play_fair(&cfg, &mut second, own.user_id).await;
+    assert!(verify_fair(&first, &next).await.is_none());
+    second.disconnect(None, None).await;
+    assert!(verify_fair(&first, &next).await.is_some());
+    assert!(verify_fair(&first, &own).await.is_none());
+}
+
+#[tokio::test]
+async fn test_fair_step() {
+    let bets = |waiver: Vec<i64>| bets_with(MATH_CLASS, "fair", serde_json::json!({"operator": [OPERATOR_ID], "stepWaiver": waiver}));
+    let limited = Step {
+        win: 1_000,
+        take: 90,
+    };
+    let cfg = create_session_cfg(create_connection().await, &bets(vec![])).await;
+    assert!(cfg.bet_configurator.is_fair(MATH_CLASS, "EUR", Some(OPERATOR_ID), &Step::default()));
+    // the fair rounds are not played again for the allowed win, so the step which limits it turns the mode off
+    assert!(!cfg.bet_configurator.is_fair(MATH_CLASS, "EUR", Some(OPERATOR_ID), &limited));
+    assert!(!cfg.bet_configurator.is_fair(
+        MATH_CLASS,
+        "EUR",
+        Some(OPERATOR_ID),
+        &Step {
+            take: 100,
+            ..limited.clone()
+        }
+    ));
+
+    // the operator of the waiver takes the fair rounds without the win control
+    let cfg = create_session_cfg(create_connection().await, &bets(vec![OPERATOR_ID])).await;
+    assert!(cfg.bet_configurator.is_fair(MATH_CLASS, "EUR", Some(OPERATOR_ID), &limited));
+    assert!(!cfg.bet_configurator.is_fair(MATH_CLASS, "EUR", Some(OPERATOR_ID + 1), &limited));
+}
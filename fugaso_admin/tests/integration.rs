@@ -53,6 +53,7 @@ pub async fn create_connection() -> DbConn {
     pool
 }
 
+#[allow(unused)]
 pub fn assert_answer(expected: &Value, actual: &Value, path: String, excludes: &Vec<&str>) {
     match expected {
         Value::String(s) => {
@@ -0,0 +1,87 @@
+use essential_core::account_service::ProxyAlias;
+use fugaso_admin::config::{ServerArg, ServerConfig};
+use fugaso_admin::dispatcher::{DispatcherContext, ResponseStacked, SlotBaseDispatcher, SlotDispatcher};
+use fugaso_core::admin::{BetConfigurator, SuccessStateLoader};
+use fugaso_core::proxy::AuthData;
+use fugaso_data::{fugaso_action, fugaso_round};
+use fugaso_math::math::SlotMath;
+use sea_orm::DbConn;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+pub type SessionConfig = ServerConfig<DispatcherContext>;
+pub type SessionDispatcher<M> = SlotDispatcher<M, SessionConfig, SessionConfig, SuccessStateLoader>;
+
+#[allow(unused)]
+pub const OPERATOR_ID: i64 = 1700;
+
+/// config of the server on `pool` with the bets of the games taken from `bets` instead of `fugaso_config::BETS`
+#[allow(unused)]
+pub async fn create_session_cfg(pool: DbConn, bets: &str) -> SessionConfig {
+    let cfg = ServerConfig::new(ServerArg {
+        pool,
+        ..Default::default()
+    })
+    .await
+    .expect("error create config");
+    let bet_configurator = BetConfigurator::new(Arc::clone(&cfg.p.currency_repo), Arc::clone(&cfg.p.exchange_repo), bets).await.expect("error bets config");
+    ServerConfig {
+        bet_configurator: Arc::new(bet_configurator),
+        ..cfg
+    }
+}
+
+/// a demo player of `OPERATOR_ID` logged in the game and joined from `country` with the unfinished round of `round_actions`,
+/// the packets of the join are returned with the dispatcher
+#[allow(unused)]
+pub async fn join_session<M: SlotMath + Send + Sync>(
+    cfg: &SessionConfig,
+    math: M,
+    game_name: &str,
+    variant: &str,
+    config_hash: String,
+    country: Option<&str>,
+    round_actions: Option<(fugaso_round::Model, Vec<fugaso_action::Model>)>,
+) -> (SessionDispatcher<M>, Vec<Value>)
+where
+    <M as SlotMath>::Special: Serialize + Sync + Send + 'static,
+    <M as SlotMath>::Restore: Serialize + Sync + Send + 'static,
+    <M as SlotMath>::Input: Serialize + Sync + Send + 'static,
+    <M as SlotMath>::V: Sync + Send + 'static,
+    <M as SlotMath>::PlayFSM: Sync + Send + 'static,
+    <M as SlotMath>::Calculator: Sync + Send + 'static,
+{
+    let game = cfg.game_service.get_game(game_name).await.expect("error load game!").expect("error find game!");
+    let mut dispatcher = cfg.create_slot_dispatcher(math, game, variant, config_hash).await.expect("error dispatcher load!");
+    let (user_id, _) = SlotBaseDispatcher::login(
+        &mut dispatcher,
+        AuthData {
+            user_name: Some("toker".to_string()),
+            session_id: Some("1750".to_string()),
+            operator_id: Some(OPERATOR_ID),
+            game_name: game_name.to_string(),
+            mode: ProxyAlias::Demo,
+            password: Some("000000".to_string()),
+        },
+        None,
+        None,
+        None,
+    )
+    .await
+    .expect("error login!");
+    let joined = SlotBaseDispatcher::join(&mut dispatcher, user_id, country.map(|c| c.to_string()), round_actions).await.expect("error join!");
+    (dispatcher, packets(joined))
+}
+
+/// packets of the answer as they go to the client
+#[allow(unused)]
+pub fn packets(response: ResponseStacked) -> Vec<Value> {
+    serde_json::from_slice(&response.answer.render().expect("error render!")).expect("error parse packets!")
+}
+
+/// the game data of the packets, the packets of a play start with it
+#[allow(unused)]
+pub fn game_data(packets: &[Value]) -> &Value {
+    packets.iter().find(|p| p["kind"] == "GAME_DATA").expect("game data is absent!")
+}
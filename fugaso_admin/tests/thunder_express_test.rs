@@ -40,12 +40,13 @@ async fn create_server_cfg() -> TestConfig {
     let game = cfg.game_service.get_game(GAME_NAME).await.expect("error load game!").expect("error find game!");
 
     let mut rand = MockThunderExpressRand::new();
+    rand.expect_reseed().return_const(());
     rand.expect_rand_cols_group().return_const(Ok((vec![0; gconf::CFG.reels[0].len()], (0..gconf::CFG.reels[0].len()).map(|_| vec!['H'; gconf::ROWS]).collect())));
 
     rand.expect_rand_mults().return_const(Ok((0..gconf::CFG.reels[0].len()).map(|_| vec![0; gconf::ROWS]).collect()));
 
     let math = ThunderExpressMath::configured(rand).expect("math load error!");
-    let dispatcher = cfg.create_slot_dispatcher(math, game).await.expect("error dispatcher load!");
+    let dispatcher = cfg.create_slot_dispatcher(math, game, gconf::VARIANTS[0].id, gconf::variant_hash(&gconf::VARIANTS[0])).await.expect("error dispatcher load!");
     let dispatch_sync_ctx = DispatcherSyncContext::new(dispatcher).await;
     ServerConfig::custom(
         ServerArg {
@@ -153,6 +154,7 @@ async fn assert_series(
             let grid = spin_data.result.grid.clone();
 
             let mut rand = MockThunderExpressRand::new();
+            rand.expect_reseed().return_const(());
             rand.expect_rand_cols_group().return_const(Ok((stops.clone(), grid.clone())));
             rand.expect_rand_cols().return_const((stops.clone(), grid.clone()));
 
@@ -1 +1,8 @@
-pub const BETS: &str = include_str!("resources/bets.json");
\ No newline at end of file
+pub const BETS: &str = include_str!("resources/bets.json");
+
+/// definitions of the games made of data by the math class of the game
+pub const GAMES: [(&str, &str); 1] = [("LuckyLinesMath", include_str!("resources/games/lucky_lines.json"))];
+
+pub fn find_game(math_class: &str) -> Option<&'static str> {
+    GAMES.iter().find(|(c, _)| *c == math_class).map(|(_, json)| *json)
+}
@@ -1,4 +1,4 @@
-use crate::protocol::{HistoryRequest, Response, RoundStory, TournamentUserWin};
+use crate::protocol::{FairSeedData, FairVerifyData, HistoryRequest, Response, RoundStory, TournamentUserWin};
 use crate::proxy::{BetSettings, DemoPromoService, GameService, PromoInfo, PromoService, PromoServiceFactory, PromoValue};
 use chrono::Local;
 use essential_core::account_service::{err_code, AccountError, GameStatus};
@@ -13,13 +13,16 @@ use essential_data::{currency, exchange_rate, user_settings};
 use fugaso_data::fugaso_action::ActionKind;
 use fugaso_data::fugaso_round::{RoundDetail, RoundStatus};
 use fugaso_data::model::ActiveClone;
-use fugaso_data::repo::{PercentRepository, RoundRepository};
+use fugaso_data::repo::{FairSeedRepository, PercentRepository, RoundRepository};
 use fugaso_data::sequence_generator::{FugasoIdGenerator, IdGenerator, IdGeneratorFactory};
 use fugaso_data::{common_round, fugaso_action, fugaso_game, fugaso_percent, fugaso_round, promo_account, promo_stats, promo_transaction};
 use fugaso_data::{common_round::Model as CommonRound, fugaso_action::Model as Action, fugaso_round::Model as Round};
-use fugaso_math::fsm::FSM;
+use fugaso_math::fair::{FairRound, FairSeeds};
+use fugaso_math::fsm::{ADMIN_CLIENT_ACTS, ADMIN_SERVER_ACTS, FSM};
+use fugaso_math::gamble::{GambleChoice, GambleLimit};
 use fugaso_math::math::{self, BetCalculator, GameInitArg, GamePlayInput, IRequest, JoinArg, MathSettings, ProxyMath, ReplayMath, SlotMath, SpinArg, Step};
-use fugaso_math::protocol::{GameData, GameResult, Promo, SpinData};
+use fugaso_math::protocol::{id, GameData, GameResult, Promo, SpinData};
+use fugaso_math::seed::{seeded_count, stored_seed, RoundSeeder, Seed};
 use fugaso_math::validator::{SimpleValidator, Validator};
 use log::{debug, error, warn};
 use num_traits::ToPrimitive;
@@ -64,6 +67,9 @@ pub trait TypedRepoFactory<
 #[serde(rename_all = "camelCase")]
 pub struct AdminConfig {
     pub history_limit: u64,
+    /// gambles of a round, 0 without a limit
+    #[serde(default)]
+    pub gamble_steps: usize,
 }
 
 pub struct SlotAdmin<M: SlotMath, F: IdGeneratorFactory + PromoServiceFactory + TypedRepoFactory, S: StateLoader> {
@@ -84,6 +90,8 @@ pub struct SlotAdmin<M: SlotMath, F: IdGeneratorFactory + PromoServiceFactory +
     user_settings_repo: Arc<UserSettingsRepository>,
     table_id_gen: Arc<dyn IdGenerator + Send + Sync>,
     round_repo: Arc<RoundRepository>,
+    /// server seeds of the provably fair mode, they are stored apart from the rounds until they are revealed
+    fair_repo: Arc<FairSeedRepository>,
     config: Arc<AdminConfig>,
     state_loader: S,
 
@@ -100,6 +108,18 @@ pub struct SlotAdmin<M: SlotMath, F: IdGeneratorFactory + PromoServiceFactory +
     input: RoundInput<M::Input>,
     round: fugaso_round::Model,
     result: Arc<GameData<M::Special, M::Restore>>,
+    variant: Option<String>,
+    config_hash: Option<String>,
+    gamble_limit: GambleLimit,
+    /// bonus buy of the game, none when the buy is not allowed for the player
+    buy: Option<BuyConfig>,
+    seeder: RoundSeeder,
+    /// index of the next seeded action of the round
+    seed_index: u64,
+    /// seed of the current round, none for a round stored without it
+    round_seed: Option<Seed>,
+    /// seeds of the provably fair mode, the rounds are seeded by them instead of the seeder
+    fair: Option<FairSeeds>,
 }
 
 #[derive(Debug)]
@@ -122,6 +142,7 @@ pub struct InitArg {
     pub game: fugaso_game::Model,
     pub demo: bool,
     pub country: Option<String>,
+    pub operator_id: Option<i64>,
     pub bet_settings: BetSettings,
     pub step_settings: StepSettings,
     pub currency: (i64, String),
@@ -186,6 +207,7 @@ where
         user_settings_repo: Arc<UserSettingsRepository>,
         table_id_gen: Arc<FugasoIdGenerator>,
         round_repo: Arc<RoundRepository>,
+        fair_repo: Arc<FairSeedRepository>,
         configurator: Arc<BetConfigurator>,
         config: Arc<AdminConfig>,
         math: M,
@@ -201,6 +223,7 @@ where
             percent_repo,
             user_settings_repo,
             round_repo,
+            fair_repo,
             config,
             user_id: 0,
             game: (0, None),
@@ -226,7 +249,41 @@ where
                 ..Default::default()
             })),
             state_loader,
+            variant: None,
+            config_hash: None,
+            gamble_limit: GambleLimit::default(),
+            buy: None,
+            seeder: RoundSeeder::default(),
+            seed_index: 0,
+            round_seed: None,
+            fair: None,
+        }
+    }
+
+    /// id and content hash of the config variant of the math, they are stored on every round
+    pub fn with_variant(mut self, variant: &str, config_hash: String) -> Self {
+        self.variant = Some(variant.to_string());
+        self.config_hash = Some(config_hash);
+        self
+    }
+
+    /// the client actions of the admin are declared in `ADMIN_CLIENT_ACTS` with their states, an action the configured fsm allows
+    /// beyond them is rejected before the fsm moves
+    fn client_act(&mut self, action: ActionKind) -> Result<ActionKind, ServerError> {
+        let state = self.fsm.current();
+        if !ADMIN_CLIENT_ACTS.contains(&(state.clone(), action.clone())) {
+            return Err(err_on!(format!("client action {action:?} of the state {state:?} is not declared!")));
         }
+        self.fsm.client_act(action)
+    }
+
+    /// the server actions of the admin are declared in `ADMIN_SERVER_ACTS` with their states
+    fn server_act(&mut self, action: ActionKind) -> Result<ActionKind, ServerError> {
+        let state = self.fsm.current();
+        if !ADMIN_SERVER_ACTS.contains(&(state.clone(), action.clone())) {
+            return Err(err_on!(format!("server action {action:?} of the state {state:?} is not declared!")));
+        }
+        self.fsm.server_act(action)
     }
 
     pub async fn init(&mut self, arg: InitArg) -> Result<(), ServerError> {
@@ -238,6 +295,15 @@ where
         let math_settings = self.math.settings();
         let default_percent = self.get_default_percent(&math_settings, &arg.game, &arg.currency, arg.country.clone(), &arg.bet_settings).await?;
         self.step = arg.step_settings.convert(default_percent.2);
+        self.gamble_limit = GambleLimit::from_max_win(arg.bet_settings.max_win, default_percent.2, self.config.gamble_steps);
+        self.buy = self.configurator.find_buy(&arg.game.math_class, arg.country.as_deref(), arg.operator_id);
+        self.fair = if self.configurator.is_fair(&arg.game.math_class, &arg.currency.1, arg.operator_id, &self.step) {
+            Some(FairSeeds::new(None)?)
+        } else {
+            None
+        };
+        self.store_fair().await?;
+        self.math.set_fair(self.fair.is_some());
         debug!("step: {:?}", self.step);
         let round: Option<(fugaso_round::Model, Vec<fugaso_action::Model>)>;
         let mut percent: fugaso_percent::Model;
@@ -323,6 +389,12 @@ where
             promo_value: promo,
         };
         self.round = r.0;
+        self.seed_index = seeded_count(&r.1);
+        let fair_seed = match self.round.seed_id {
+            Some(id) => self.fair_repo.find(id).await.map_err(|e| err_on!(e))?,
+            None => None,
+        };
+        self.round_seed = stored_seed(&self.round, fair_seed.as_ref())?;
         if self.round.timestamp_close.is_none() && self.round.status == Some(RoundStatus::SUCCESS) {
             self.validator.correct(&mut self.input.request);
         }
@@ -343,7 +415,7 @@ where
         if let Some(a) = r.1.iter().max_by(|a, b| a.id.cmp(&b.id)) {
             if let Some(k) = &a.next_act {
                 match k {
-                    ActionKind::RESPIN | ActionKind::DROP | ActionKind::FREE_SPIN => self.fsm.init(k.clone()),
+                    ActionKind::RESPIN | ActionKind::DROP | ActionKind::BONUS | ActionKind::FREE_SPIN => self.fsm.init(k.clone()),
                     _ => {}
                 };
             }
@@ -391,8 +463,8 @@ where
         let request_settings = self.configurator.filter(settings, &source, currency).await?;
         Ok((
             fugaso_percent::Model {
-                free_percent: 100,
-                percent: 100,
+                free_percent: percent.free_percent,
+                percent: percent.percent,
                 poss_bets: Some(request_settings.bets_str()),
                 denomination: Some(request_settings.denom_str()),
                 bet_multiplier: 10_000,
@@ -445,17 +517,46 @@ where
     }
 
     pub async fn spin(&mut self, balance: i64, mut request: M::Input) -> Result<(Response<M::Special, M::Restore>, Round, Action, PromoValue), ServerError> {
-        self.fsm.client_act(ActionKind::BET)?;
-        self.fsm.client_act(ActionKind::SPIN)?;
+        self.client_act(ActionKind::BET)?;
+        self.client_act(ActionKind::SPIN)?;
         self.validator.correct(&mut request);
+        self.start_round(balance, request, None).await
+    }
 
+    /// the bonus is bought for the price of `calc_buy_bet` with the request of the player or for the total bet of the request of the buy
+    /// when the math prices the buy by its bet counter, the promo does not pay the buy
+    pub async fn buy_bonus(&mut self, balance: i64, mut request: M::Input) -> Result<(Response<M::Special, M::Restore>, Round, Action, PromoValue), ServerError> {
+        let multiplier = self.buy.as_ref().map(|b| b.multiplier).ok_or_else(|| err_on!("bonus buy is not allowed!"))?;
+        self.client_act(ActionKind::BET)?;
+        self.client_act(ActionKind::SPIN)?;
+        self.validator.correct(&mut request);
+        let buy_request = self.math.buy_request(&request)?;
+        let price = match multiplier {
+            Some(m) => self.bet_calculator.calc_buy_bet(&request, m),
+            None => self.bet_calculator.calc_total_bet(&buy_request),
+        };
+        if price <= self.bet_calculator.calc_total_bet(&request) {
+            return Err(err_on!("price of the bonus buy is not configured!"));
+        }
+        self.start_round(balance, buy_request, Some(price)).await
+    }
+
+    async fn start_round(&mut self, balance: i64, request: M::Input, buy_price: Option<i64>) -> Result<(Response<M::Special, M::Restore>, Round, Action, PromoValue), ServerError> {
         self.input = RoundInput {
             request,
             ..Default::default()
         };
 
-        let stake = self.bet_calculator.calc_total_bet(&self.input.request);
+        let stake = buy_price.unwrap_or_else(|| self.bet_calculator.calc_total_bet(&self.input.request));
         let now = Local::now();
+        let fair = self.fair.as_mut().map(|f| f.next_round());
+        let seed = match fair.as_ref() {
+            Some(f) => Seed::Fair {
+                key: f.key()?,
+                stream: 0,
+            },
+            None => Seed::Plain(self.seeder.next_seed()),
+        };
         let common_id = self.table_id_gen.gen_common_round().await.map_err(|e| err_on!(e))?;
         let common_round = CommonRound {
             id: common_id,
@@ -475,12 +576,25 @@ where
             win: Some(0),
             common_id: Some(common_round.id),
             status: Some(RoundStatus::SUCCESS),
+            variant: self.variant.clone(),
+            config_hash: self.config_hash.clone(),
+            bought: buy_price.map(|_| true),
+            seed: Some(seed.head() as i64),
+            fair_hash: fair.as_ref().map(|f| f.server_hash.clone()),
+            client_seed: fair.as_ref().map(|f| f.client_seed.clone()),
+            nonce: fair.as_ref().map(|f| f.nonce as i64),
+            seed_id: fair.map(|f| f.seed_id),
+            step_win: Some(self.step.win),
+            step_take: Some(self.step.take as i32),
             ..Default::default()
         };
         let external_id = Some(Uuid::new_v4().to_string());
         let mut amount = stake;
 
-        let promo_change = self.promo_service.decrement(common_id, external_id.clone(), amount).await?;
+        let promo_change = match buy_price {
+            Some(_) => (None, None, None, None, self.promo_service.promo_state()),
+            None => self.promo_service.decrement(common_id, external_id.clone(), amount).await?,
+        };
         if let Some(t) = promo_change.3 {
             self.load_promo_input(t)?;
             amount = 0;
@@ -495,39 +609,42 @@ where
         }
 
         let combo = self.combo_gen.find_combo(self.user_id).await;
-        let result = self.math.spin(
-            &self.input.request,
-            SpinArg {
-                balance,
-                round_id: common_id,
-                round_type: round.detail.clone(),
-                round_multiplier: round.multi,
-                next_act: self.fsm.current(),
-                promo: promo_change.4,
-                stake: amount,
-            },
-            &self.step,
-            combo,
-        )?;
+        let arg = SpinArg {
+            balance,
+            round_id: common_id,
+            round_type: round.detail.clone(),
+            round_multiplier: round.multi,
+            next_act: self.fsm.current(),
+            promo: promo_change.4,
+            stake: amount,
+        };
+        self.round_seed = Some(seed);
+        self.math.reseed(seed.action(0));
+        let result = if buy_price.is_some() {
+            self.math.buy_spin(&self.input.request, arg, &self.step, combo)?
+        } else {
+            self.math.spin(&self.input.request, arg, &self.step, combo)?
+        };
+        self.seed_index = 1;
 
         let free_left = result.free().map(|f| f.left).unwrap_or(0);
         if result.has_bonus() {
-            self.fsm.server_act(ActionKind::BONUS_START)?;
+            self.server_act(ActionKind::BONUS_START)?;
         } else if result.has_respin() {
-            self.fsm.server_act(ActionKind::RESPIN_START)?;
+            self.server_act(ActionKind::RESPIN_START)?;
         } else if result.has_drop() {
             if free_left > 0 {
-                self.fsm.server_act(ActionKind::FREESPIN_START)?;
+                self.server_act(ActionKind::FREESPIN_START)?;
             } else {
-                self.fsm.server_act(ActionKind::DROP_START)?;
+                self.server_act(ActionKind::DROP_START)?;
             }
         } else if free_left > 0 {
-            self.fsm.server_act(ActionKind::FREESPIN_START)?;
+            self.server_act(ActionKind::FREESPIN_START)?;
         } else if result.total() > 0 {
             if result.is_gamble_end(stake) {
-                self.fsm.server_act(ActionKind::GAMBLE_END)?;
+                self.server_act(ActionKind::GAMBLE_END)?;
             } else {
-                self.fsm.server_act(ActionKind::COLLECT_START)?;
+                self.server_act(ActionKind::COLLECT_START)?;
             }
         }
         let action = Action {
@@ -561,10 +678,11 @@ where
     }
 
     pub async fn respin(&mut self, balance: i64) -> Result<(Response<M::Special, M::Restore>, Round, Action, PromoValue), ServerError> {
-        self.fsm.client_act(ActionKind::RESPIN)?;
+        self.client_act(ActionKind::RESPIN)?;
         let combo = self.combo_gen.find_combo(self.user_id).await;
         let stake = self.bet_calculator.calc_total_bet(&self.input.request);
         let promo = self.promo_service.promo_state();
+        self.reseed();
         let result = self.math.respin(
             &self.input.request,
             SpinArg {
@@ -579,17 +697,18 @@ where
             &self.step,
             combo,
         )?;
+        self.seed_index += 1;
 
         let left = result.free().map(|f| f.left).unwrap_or(0);
         if result.has_respin() {
-            self.fsm.server_act(ActionKind::RESPIN_START)?;
+            self.server_act(ActionKind::RESPIN_START)?;
         } else if left > 0 {
-            self.fsm.server_act(ActionKind::FREESPIN_START)?;
+            self.server_act(ActionKind::FREESPIN_START)?;
         } else if result.total() > 0 {
             if result.is_gamble_end(stake) {
-                self.fsm.server_act(ActionKind::GAMBLE_END)?;
+                self.server_act(ActionKind::GAMBLE_END)?;
             } else {
-                self.fsm.server_act(ActionKind::COLLECT_START)?;
+                self.server_act(ActionKind::COLLECT_START)?;
             }
         }
         self.result = self.math.post_process(self.fsm.current(), result)?;
@@ -610,11 +729,210 @@ where
         Ok((Response::GameData(self.result.clone()), self.round.clone(), a, self.input.promo_value.clone()))
     }
 
+    pub async fn bonus(&mut self, balance: i64, pick: usize) -> Result<(Response<M::Special, M::Restore>, Round, Action, PromoValue), ServerError> {
+        self.client_act(ActionKind::BONUS)?;
+        let stake = self.bet_calculator.calc_total_bet(&self.input.request);
+        let promo = self.promo_service.promo_state();
+        self.reseed();
+        let result = self.math.bonus(
+            &self.input.request,
+            SpinArg {
+                balance,
+                round_id: self.round.common_id.ok_or_else(|| err_on!("common id is none!"))?,
+                round_type: self.round.detail.clone(),
+                round_multiplier: self.round.multi,
+                next_act: self.fsm.current(),
+                promo,
+                stake,
+            },
+            &self.step,
+            pick,
+        )?;
+        self.seed_index += 1;
+
+        let left = result.free().map(|f| f.left).unwrap_or(0);
+        if !result.has_bonus() {
+            self.server_act(ActionKind::CLOSE)?;
+            if left > 0 {
+                self.server_act(ActionKind::FREESPIN_START)?;
+            } else if result.total() > 0 {
+                if result.is_gamble_end(stake) {
+                    self.server_act(ActionKind::GAMBLE_END)?;
+                } else {
+                    self.server_act(ActionKind::COLLECT_START)?;
+                }
+            }
+        }
+        self.result = self.math.post_process(self.fsm.current(), result)?;
+
+        let now = Local::now();
+        let action = Action {
+            id: self.table_id_gen.gen_action().await.map_err(|e| err_on!(e))?,
+            amount: self.result.total(),
+            act_descr: Some(ActionKind::BONUS),
+            round_id: Some(self.round.id),
+            time_done: Some(now.naive_local()),
+            next_act: Some(self.fsm.current()),
+            external_id: Some(Uuid::new_v4().to_string()),
+            remote_code: Some(err_code::GENERAL_CODE),
+            ..self.result.create_action_default()?
+        };
+        let a = self.type_repo.store_c(Insert(action.into_active_model())).await.map_err(|e| err_on!(e))?;
+        Ok((Response::GameData(self.result.clone()), self.round.clone(), a, self.input.promo_value.clone()))
+    }
+
+    pub async fn gamble(&mut self, balance: i64, choice: GambleChoice) -> Result<(Response<M::Special, M::Restore>, Round, Action, PromoValue), ServerError> {
+        let is_free = self.fsm.current() == ActionKind::FREE_COLLECT;
+        self.client_act(ActionKind::GAMBLE_PLAY)?;
+        let stake = self.bet_calculator.calc_total_bet(&self.input.request);
+        let promo = self.promo_service.promo_state();
+        self.reseed();
+        let result = self.math.gamble(
+            &self.input.request,
+            SpinArg {
+                balance,
+                round_id: self.round.common_id.ok_or_else(|| err_on!("common id is none!"))?,
+                round_type: self.round.detail.clone(),
+                round_multiplier: self.round.multi,
+                next_act: self.fsm.current(),
+                promo,
+                stake,
+            },
+            choice,
+            &self.gamble_limit,
+        )?;
+        self.seed_index += 1;
+
+        let can_gamble = match &result {
+            GameData::Gamble(g) => self.gamble_limit.can_gamble(&g.gamble),
+            _ => false,
+        };
+        let left = result.free().map(|f| f.left).unwrap_or(0);
+        if result.total() > 0 {
+            match (is_free, can_gamble) {
+                (false, true) => self.server_act(ActionKind::COLLECT_START)?,
+                (false, false) => self.server_act(ActionKind::GAMBLE_END)?,
+                (true, true) => self.server_act(ActionKind::FREE_COLLECT_START)?,
+                (true, false) => self.server_act(ActionKind::GAMBLE_FREE_END)?,
+            };
+        } else if left > 0 {
+            self.server_act(ActionKind::FREESPIN_START)?;
+        }
+        self.result = self.math.post_process(self.fsm.current(), result)?;
+
+        let now = Local::now();
+        let action = Action {
+            id: self.table_id_gen.gen_action().await.map_err(|e| err_on!(e))?,
+            amount: self.result.total(),
+            act_descr: Some(ActionKind::GAMBLE_PLAY),
+            round_id: Some(self.round.id),
+            time_done: Some(now.naive_local()),
+            next_act: Some(self.fsm.current()),
+            external_id: Some(Uuid::new_v4().to_string()),
+            remote_code: Some(err_code::GENERAL_CODE),
+            ..self.result.create_action_default()?
+        };
+        let a = self.type_repo.store_c(Insert(action.into_active_model())).await.map_err(|e| err_on!(e))?;
+        Ok((Response::GameData(self.result.clone()), self.round.clone(), a, self.input.promo_value.clone()))
+    }
+
+    /// banks the half of the win and leaves the rest for the gamble, the banked half is paid with the collect
+    pub async fn half_collect(&mut self, balance: i64) -> Result<Response<M::Special, M::Restore>, ServerError> {
+        self.client_act(ActionKind::HALF_COLLECT)?;
+        let stake = self.bet_calculator.calc_total_bet(&self.input.request);
+        let promo = self.promo_service.promo_state();
+        self.reseed();
+        let result = self.math.half_collect(
+            &self.input.request,
+            SpinArg {
+                balance,
+                round_id: self.round.common_id.ok_or_else(|| err_on!("common id is none!"))?,
+                round_type: self.round.detail.clone(),
+                round_multiplier: self.round.multi,
+                next_act: self.fsm.current(),
+                promo,
+                stake,
+            },
+        )?;
+        self.seed_index += 1;
+        self.result = self.math.post_process(self.fsm.current(), result)?;
+
+        let now = Local::now();
+        let action = Action {
+            id: self.table_id_gen.gen_action().await.map_err(|e| err_on!(e))?,
+            amount: self.result.total(),
+            act_descr: Some(ActionKind::HALF_COLLECT),
+            round_id: Some(self.round.id),
+            time_done: Some(now.naive_local()),
+            next_act: Some(self.fsm.current()),
+            external_id: Some(Uuid::new_v4().to_string()),
+            remote_code: Some(err_code::GENERAL_CODE),
+            ..self.result.create_action_default()?
+        };
+        self.type_repo.store_c(Insert(action.into_active_model())).await.map_err(|e| err_on!(e))?;
+        Ok(Response::GameData(self.result.clone()))
+    }
+
+    pub async fn drop(&mut self, balance: i64) -> Result<(Response<M::Special, M::Restore>, Round, Action, PromoValue), ServerError> {
+        self.client_act(ActionKind::DROP)?;
+        let combo = self.combo_gen.find_combo(self.user_id).await;
+        let stake = self.bet_calculator.calc_total_bet(&self.input.request);
+        let promo = self.promo_service.promo_state();
+        self.reseed();
+        let result = self.math.drop(
+            &self.input.request,
+            SpinArg {
+                balance,
+                round_id: self.round.common_id.ok_or_else(|| err_on!("common id is none!"))?,
+                round_type: self.round.detail.clone(),
+                round_multiplier: self.round.multi,
+                next_act: self.fsm.current(),
+                promo,
+                stake,
+            },
+            &self.step,
+            combo,
+        )?;
+        self.seed_index += 1;
+
+        let left = result.free().map(|f| f.left).unwrap_or(0);
+        if result.has_drop() {
+            self.server_act(ActionKind::DROP_START)?;
+        } else if result.has_respin() {
+            self.server_act(ActionKind::RESPIN_START)?;
+        } else if left > 0 {
+            self.server_act(ActionKind::FREESPIN_START)?;
+        } else if result.total() > 0 {
+            if result.is_gamble_end(stake) {
+                self.server_act(ActionKind::GAMBLE_END)?;
+            } else {
+                self.server_act(ActionKind::COLLECT_START)?;
+            }
+        }
+        self.result = self.math.post_process(self.fsm.current(), result)?;
+
+        let now = Local::now();
+        let action = Action {
+            id: self.table_id_gen.gen_action().await.map_err(|e| err_on!(e))?,
+            amount: self.result.total(),
+            act_descr: Some(ActionKind::DROP),
+            round_id: Some(self.round.id),
+            time_done: Some(now.naive_local()),
+            next_act: Some(self.fsm.current()),
+            external_id: Some(Uuid::new_v4().to_string()),
+            remote_code: Some(err_code::GENERAL_CODE),
+            ..self.result.create_action_default()?
+        };
+        let a = self.type_repo.store_c(Insert(action.into_active_model())).await.map_err(|e| err_on!(e))?;
+        Ok((Response::GameData(self.result.clone()), self.round.clone(), a, self.input.promo_value.clone()))
+    }
+
     pub async fn free_spin(&mut self, balance: i64) -> Result<(Response<M::Special, M::Restore>, Round, Action, PromoValue), ServerError> {
-        self.fsm.client_act(ActionKind::FREE_SPIN)?;
+        self.client_act(ActionKind::FREE_SPIN)?;
         let combo = self.combo_gen.find_combo(self.user_id).await;
         let stake = self.bet_calculator.calc_total_bet(&self.input.request);
         let promo = self.promo_service.promo_state();
+        self.reseed();
         let result = self.math.free_spin(
             &self.input.request,
             SpinArg {
@@ -629,17 +947,18 @@ where
             &self.step,
             combo,
         )?;
+        self.seed_index += 1;
 
         let left = result.free().map(|f| f.left).unwrap_or(0);
         if result.has_respin() {
-            self.fsm.server_act(ActionKind::RESPIN_START)?;
+            self.server_act(ActionKind::RESPIN_START)?;
         } else if left > 0 {
-            self.fsm.server_act(ActionKind::FREESPIN_START)?;
+            self.server_act(ActionKind::FREESPIN_START)?;
         } else if result.total() > 0 {
             if result.is_gamble_end(stake) {
-                self.fsm.server_act(ActionKind::GAMBLE_END)?;
+                self.server_act(ActionKind::GAMBLE_END)?;
             } else {
-                self.fsm.server_act(ActionKind::COLLECT_START)?;
+                self.server_act(ActionKind::COLLECT_START)?;
             }
         }
         self.result = self.math.post_process(self.fsm.current(), result)?;
@@ -660,15 +979,83 @@ where
         Ok((Response::GameData(self.result.clone()), self.round.clone(), a, self.input.promo_value.clone()))
     }
 
+    /// commitment of the seeds of the provably fair mode, none when the mode is off
+    pub fn fair_seed(&self) -> Option<Response<M::Special, M::Restore>> {
+        self.fair.as_ref().map(|f| {
+            Response::FairSeed(FairSeedData {
+                id: id::FAIR_SEED,
+                commitment: f.commitment(),
+                reveal: None,
+            })
+        })
+    }
+
+    /// the current server seed of the provably fair mode is stored before a round is played with it
+    async fn store_fair(&self) -> Result<(), ServerError> {
+        if let Some(f) = self.fair.as_ref() {
+            self.fair_repo.insert(f.seed_model(self.user_id, self.game.0, Local::now().naive_local()).into_active_model()).await.map_err(|e| err_on!(e))?;
+        }
+        Ok(())
+    }
+
+    /// reveals the server seed of the finished rounds and commits to a new one, the seed of an unfinished round
+    /// would tell its next actions, so the seeds are rotated between the rounds only
+    pub async fn rotate_fair(&mut self, client_seed: Option<String>) -> Result<Response<M::Special, M::Restore>, ServerError> {
+        if self.fsm.current() != ActionKind::BET {
+            return Err(err_on!("round is not finished!"));
+        }
+        let fair = self.fair.as_mut().ok_or_else(|| err_on!("provably fair mode is off!"))?;
+        let reveal = fair.rotate(client_seed)?;
+        let commitment = fair.commitment();
+        self.store_fair().await?;
+        self.fair_repo.reveal(reveal.seed_id, Local::now().naive_local()).await.map_err(|e| err_on!(e))?;
+        Ok(Response::FairSeed(FairSeedData {
+            id: id::FAIR_SEED,
+            commitment,
+            reveal: Some(reveal),
+        }))
+    }
+
+    /// seeds of a finished round of the provably fair mode by the id of the history, the server seed is shown when it is retired
+    /// by the rotation or the end of the session which played it, whichever session of the player asks for it
+    pub async fn verify_fair(&self, round_id: i64) -> Result<Response<M::Special, M::Restore>, ServerError> {
+        let round =
+            self.round_repo.find_user_round(self.user_id, self.game.0, round_id).await.map_err(|e| err_on!(e))?.ok_or_else(|| err_on!(format!("round {round_id} is absent!")))?;
+        if round.timestamp_close.is_none() {
+            return Err(err_on!("round is not finished!"));
+        }
+        let seed_id = round.seed_id.ok_or_else(|| err_on!(format!("round {round_id} is not provably fair!")))?;
+        let seed = self
+            .fair_repo
+            .find(seed_id)
+            .await
+            .map_err(|e| err_on!(e))?
+            .filter(|s| s.revealed.is_some())
+            .ok_or_else(|| err_on!("server seed of the round is not revealed, the seeds must be rotated!"))?;
+        let seeds = FairRound::stored(&round, &seed).ok_or_else(|| err_on!(format!("server seed of round {round_id} is not its commitment!")))?;
+        Ok(Response::FairVerify(FairVerifyData {
+            id: id::FAIR_VERIFY,
+            round_id,
+            seeds,
+        }))
+    }
+
+    /// the next action of the round draws from its own stream of the seed of the round
+    fn reseed(&mut self) {
+        if let Some(seed) = self.round_seed {
+            self.math.reseed(seed.action(self.seed_index));
+        }
+    }
+
     pub async fn collect(&mut self, balance: i64) -> Result<(Response<M::Special, M::Restore>, Round, Action, GameStatus, PromoValue), ServerError> {
-        self.fsm.server_act(ActionKind::COLLECT)?;
+        self.server_act(ActionKind::COLLECT)?;
         let now = Local::now();
         let left = self.result.free().map(|f| f.left).unwrap_or(0);
         let time_close = if left > 0 {
-            self.fsm.server_act(ActionKind::FREESPIN_START)?;
+            self.server_act(ActionKind::FREESPIN_START)?;
             None
         } else if self.result.has_drop() {
-            self.fsm.server_act(ActionKind::DROP_START)?;
+            self.server_act(ActionKind::DROP_START)?;
             None
         } else {
             Some(Local::now().naive_local())
@@ -743,7 +1130,7 @@ where
     }
 
     pub async fn close_round(&mut self) -> Result<(Response<M::Special, M::Restore>, Round, Action), ServerError> {
-        self.fsm.server_act(ActionKind::CLOSE)?;
+        self.server_act(ActionKind::CLOSE)?;
         let result = self.math.close(self.fsm.current())?;
         self.result = Arc::new(result);
         let now = Local::now();
@@ -914,8 +1301,19 @@ where
         rounds
     }
 
+    /// the end of the session retires its server seed and the seed of the last round, the seed of an unfinished round is kept
+    /// secret for the session which finishes the round
     pub async fn close(&self) {
         self.type_repo.flush().await;
+        let open = self.fsm.current() != ActionKind::BET;
+        let mut retired = self.fair.as_ref().map(|f| f.seed_id()).into_iter().chain(self.round.seed_id).filter(|id| !open || Some(*id) != self.round.seed_id).collect::<Vec<_>>();
+        retired.dedup();
+        let now = Local::now().naive_local();
+        for id in retired {
+            if let Err(e) = self.fair_repo.reveal(id, now).await {
+                error!("{e}");
+            }
+        }
     }
 }
 
@@ -1069,6 +1467,8 @@ impl BetConfigurator {
                         settings,
                         exclusion,
                         country_exclusion,
+                        buy: v.buy,
+                        fair: v.fair,
                     },
                 )
             })
@@ -1089,6 +1489,25 @@ impl BetConfigurator {
         Ok(request_on)
     }
 
+    /// bonus buy of the game, none when the game has no buy or the buy is banned for the player
+    pub fn find_buy(&self, math_class: &str, country: Option<&str>, operator_id: Option<i64>) -> Option<BuyConfig> {
+        self.map_bets.get(math_class).and_then(|c| c.buy.as_ref()).filter(|b| b.allows(country, operator_id)).cloned()
+    }
+
+    /// the provably fair mode is on for the currency or the operator of the player, the fair rounds are not played again
+    /// for the allowed win, so the mode is off when the step of the player limits the win and the operator does not waive it
+    pub fn is_fair(&self, math_class: &str, currency: &str, operator_id: Option<i64>, step: &Step) -> bool {
+        let fair = self.map_bets.get(math_class).and_then(|c| c.fair.as_ref()).filter(|f| f.allows(currency, operator_id));
+        match fair {
+            Some(f) if step.is_limited() && !f.waives_step(operator_id) => {
+                warn!("provably fair mode of {math_class} is off for the step {step:?} of operator {operator_id:?}!");
+                false
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
     pub async fn find_bets<C: BetCalculator>(&self, game_settings: GameSettings<C>, currency: &(i64, String), country: Option<String>) -> Result<RequestSettings, ServerError> {
         let request_on = self.get_default_settings(&game_settings.math_class, currency, country)?;
         self.filter(game_settings, request_on, currency).await
@@ -1242,6 +1661,8 @@ pub struct GameConfig {
     pub settings: RequestSettings,
     pub exclusion: HashMap<String, RequestSettings>,
     pub country_exclusion: HashMap<String, RequestSettings>,
+    pub buy: Option<BuyConfig>,
+    pub fair: Option<FairConfig>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -1257,6 +1678,55 @@ pub struct BetConfig {
     pub currency_exclusion: Vec<CurrencyExclusion>,
     #[serde(default)]
     pub country_exclusion: Vec<CountryExclusion>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buy: Option<BuyConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fair: Option<FairConfig>,
+}
+
+/// provably fair mode of a game, it is on for the currencies and the operators of the lists, the operators of `step_waiver`
+/// take the fair rounds without the win control of their step
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FairConfig {
+    #[serde(default)]
+    pub currency: Vec<String>,
+    #[serde(default)]
+    pub operator: Vec<i64>,
+    #[serde(default)]
+    pub step_waiver: Vec<i64>,
+}
+
+impl FairConfig {
+    pub fn allows(&self, currency: &str, operator_id: Option<i64>) -> bool {
+        self.currency.iter().any(|c| c == currency) || operator_id.map(|o| self.operator.contains(&o)).unwrap_or(false)
+    }
+
+    pub fn waives_step(&self, operator_id: Option<i64>) -> bool {
+        operator_id.map(|o| self.step_waiver.contains(&o)).unwrap_or(false)
+    }
+}
+
+/// bonus buy of a game, `multiplier` is the price in the playing bets for the maths which keep the bet counter of the player,
+/// without it the price is the total bet of `SlotMath::buy_request`, the buy is banned for the countries and the operators of the lists
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuyConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multiplier: Option<i64>,
+    #[serde(default)]
+    pub country_exclusion: Vec<String>,
+    #[serde(default)]
+    pub operator_exclusion: Vec<i64>,
+}
+
+impl BuyConfig {
+    /// an unknown country or operator is rejected when the exclusions of it are configured
+    pub fn allows(&self, country: Option<&str>, operator_id: Option<i64>) -> bool {
+        self.multiplier.map(|m| m > 0).unwrap_or(true)
+            && country.map(|c| !self.country_exclusion.iter().any(|e| e.eq_ignore_ascii_case(c))).unwrap_or(self.country_exclusion.is_empty())
+            && operator_id.map(|o| !self.operator_exclusion.contains(&o)).unwrap_or(self.operator_exclusion.is_empty())
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -3,6 +3,8 @@ use std::fmt::Display;
 use std::marker::PhantomData;
 use essential_core::account_service::{AccountError, ErrorType, ProxyAlias};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use fugaso_math::fair::{FairCommitment, FairReveal, FairRound};
+use fugaso_math::gamble::GambleChoice;
 use fugaso_math::protocol::{DatabaseStore, FreeGame, Gain, GameData, GamePlayEvent, GameResult};
 use std::str::FromStr;
 use std::sync::Arc;
@@ -49,6 +51,10 @@ pub enum Response<S: DatabaseStore + Default + Serialize + Sync + Send, R: Defau
     History(HistoryData<S, R>),
     #[serde(rename = "TOURNAMENT_WIN")]
     TournamentWin(TournamentGainData),
+    #[serde(rename = "FAIR_SEED")]
+    FairSeed(FairSeedData),
+    #[serde(rename = "FAIR_VERIFY")]
+    FairVerify(FairVerifyData),
 }
 
 impl<S: DatabaseStore + Default + Serialize + Sync + Send, R: Default + Serialize> IResponse for Vec<Response<S, R>> {
@@ -81,6 +87,18 @@ impl<S: DatabaseStore + Default + Serialize + Sync + Send, R: Default + Serializ
                             d.fire_event(ActionKind::RESPIN, &sender);
                             result = Ok(d.next_act.clone())
                          }
+                        GameData::Drop(d) => {
+                            d.fire_event(ActionKind::DROP, &sender);
+                            result = Ok(d.next_act.clone())
+                        }
+                        GameData::Bonus(d) => {
+                            d.spin.fire_event(ActionKind::BONUS, &sender);
+                            result = Ok(d.spin.next_act.clone())
+                        }
+                        GameData::Gamble(d) => {
+                            d.spin.fire_event(ActionKind::GAMBLE_PLAY, &sender);
+                            result = Ok(d.spin.next_act.clone())
+                        }
                         GameData::Collect(d) => { 
                             d.fire_event(ActionKind::COLLECT, &sender);
                             result = Ok(d.next_act.clone()) 
@@ -122,6 +140,25 @@ impl From<TournamentTransfer> for TournamentGainData {
     }
 }
 
+/// seeds of the provably fair mode, the reveal is the server seed replaced by the rotation
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FairSeedData {
+    pub id: i32,
+    pub commitment: FairCommitment,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reveal: Option<FairReveal>,
+}
+
+/// revealed seeds of a round of the provably fair mode, the round id is the id of the history
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FairVerifyData {
+    pub id: i32,
+    pub round_id: i64,
+    pub seeds: FairRound,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HistoryData<S: DatabaseStore + Default, R: Default> {
@@ -149,6 +186,14 @@ pub struct RoundStory<S: DatabaseStore + Default, R: Default> {
     pub line: i32,
     pub denom: i32,
     pub bet_on: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fair_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_seed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed_id: Option<i64>,
 }
 
 impl<S: DatabaseStore + Default, R: Default> From<(fugaso_round::Model, Vec<fugaso_action::Model>)> for RoundStory<S, R> {
@@ -169,6 +214,10 @@ impl<S: DatabaseStore + Default, R: Default> From<(fugaso_round::Model, Vec<fuga
             line: value.0.line,
             denom: value.0.denom,
             bet_on: value.0.bet,
+            fair_hash: value.0.fair_hash,
+            client_seed: value.0.client_seed,
+            nonce: value.0.nonce,
+            seed_id: value.0.seed_id,
         }
     }
 }
@@ -395,6 +444,33 @@ pub struct HistoryRequest {
     pub limit: u64,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BonusRequest {
+    pub pick: usize,
+}
+
+/// rotation of the seeds of the provably fair mode, the current client seed is kept without the new one
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FairSeedRequest {
+    #[serde(default)]
+    pub client_seed: Option<String>,
+}
+
+/// seeds of a played round of the provably fair mode by the id of the history
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FairVerifyRequest {
+    pub round_id: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GambleRequest {
+    pub choice: GambleChoice,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "kind")]
 pub enum PlayerRequest<R> {
@@ -402,16 +478,30 @@ pub enum PlayerRequest<R> {
     Login(LoginRequest),
     #[serde(rename = "BET_SPIN")]
     BetSpin(R),
+    #[serde(rename = "BUY_BONUS")]
+    BuyBonus(R),
     #[serde(rename = "COLLECT")]
     Collect,
     #[serde(rename = "RESPIN")]
     ReSpin,
     #[serde(rename = "FREE_SPIN")]
     FreeSpin,
+    #[serde(rename = "DROP")]
+    Drop,
+    #[serde(rename = "BONUS")]
+    Bonus(BonusRequest),
+    #[serde(rename = "GAMBLE_PLAY")]
+    Gamble(GambleRequest),
+    #[serde(rename = "HALF_COLLECT")]
+    HalfCollect,
     #[serde(rename = "TOURNAMENT_INFO")]
     TournamentInfo,
     #[serde(rename = "HISTORY")]
     History(HistoryRequest),
+    #[serde(rename = "FAIR_SEED")]
+    FairSeed(FairSeedRequest),
+    #[serde(rename = "FAIR_VERIFY")]
+    FairVerify(FairVerifyRequest),
 }
 
 impl <R>PlayerRequest<R> {
@@ -419,11 +509,18 @@ impl <R>PlayerRequest<R> {
         match self {
             PlayerRequest::Login(_) => { "LOGIN" }
             PlayerRequest::BetSpin(_) => { "BET_SPIN" }
+            PlayerRequest::BuyBonus(_) => { "BUY_BONUS" }
             PlayerRequest::Collect => { "COLLECT" }
             PlayerRequest::ReSpin => { "RESPIN" }
             PlayerRequest::FreeSpin => { "FREE_SPIN" }
+            PlayerRequest::Drop => { "DROP" }
+            PlayerRequest::Bonus(_) => { "BONUS" }
+            PlayerRequest::Gamble(_) => { "GAMBLE_PLAY" }
+            PlayerRequest::HalfCollect => { "HALF_COLLECT" }
             PlayerRequest::TournamentInfo => { "TOURNAMENT_INFO" }
             PlayerRequest::History(_) => { "HISTORY" }
+            PlayerRequest::FairSeed(_) => { "FAIR_SEED" }
+            PlayerRequest::FairVerify(_) => { "FAIR_VERIFY" }
         }
     }
 }
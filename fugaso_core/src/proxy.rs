@@ -163,6 +163,7 @@ pub struct SlotProxy<M: SlotMath, F: JackpotProxyFactory + AccountServiceFactory
     pub config: ProxyConfig,
     pub phantom: PhantomData<M>,
     pub game_session_id: Option<String>,
+    pub operator_id: Option<i64>,
 }
 
 impl<M: SlotMath, F: JackpotProxyFactory + AccountServiceFactory + RetryServiceFactory> SlotProxy<M, F>
@@ -214,6 +215,7 @@ where
             retry_service: Box::new(DefaultRetryService),
             phantom: PhantomData,
             game_session_id: None,
+            operator_id: None,
         })
     }
     pub async fn login(
@@ -248,6 +250,7 @@ where
         };
 
         let jackpot_proxy = self.factory.create_jackpot_proxy(&self.game.math_class);
+        self.operator_id = auth.operator_id;
         self.service
             .config_account(AccountConfig {
                 operator_id: auth.operator_id,
@@ -0,0 +1,22 @@
+use sea_orm::entity::prelude::*;
+
+/// server seed of the provably fair mode, the rounds keep only its commitment and the seed is shown when `revealed` is set,
+/// after the session of the seed rotated it or ended
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "fugaso_fair_seed")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: i64,
+    pub user_id: i64,
+    pub game_id: i64,
+    pub server_seed: String,
+    pub server_hash: String,
+    pub timestamp_open: DateTime,
+    /// time of the retirement of the seed, none while a session can play it
+    pub revealed: Option<DateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
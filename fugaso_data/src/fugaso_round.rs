@@ -94,6 +94,23 @@ pub struct Model {
     pub bet_counter: i32,
     pub stake: Option<i64>,
     pub win: Option<i64>,
+    /// id of the math config variant which played the round
+    pub variant: Option<String>,
+    /// content hash of the math config and reels of the variant, see `fugaso_math::config::config_hash`
+    pub config_hash: Option<String>,
+    /// the round was started by a bonus buy
+    pub bought: Option<bool>,
+    /// seed of the round drawn from the master generator, the actions of the round draw from it, see `fugaso_math::seed`
+    pub seed: Option<i64>,
+    /// commitment of the server seed of the provably fair mode, the seed of the round is the head of `fugaso_math::fair::fair_key`
+    pub fair_hash: Option<String>,
+    pub client_seed: Option<String>,
+    pub nonce: Option<i64>,
+    /// id of the server seed of the provably fair mode, the seed itself is kept in `fugaso_fair_seed` until it is revealed
+    pub seed_id: Option<i64>,
+    /// step of the percent of the player which limited the win of the round, see `fugaso_math::math::Step`
+    pub step_win: Option<i64>,
+    pub step_take: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -187,6 +204,16 @@ impl ActiveClone<ActiveModel> for Model {
             bet_counter: Unchanged(self.bet_counter),
             stake: Unchanged(self.stake),
             win: Unchanged(self.win),
+            variant: Unchanged(self.variant.clone()),
+            config_hash: Unchanged(self.config_hash.clone()),
+            bought: Unchanged(self.bought),
+            seed: Unchanged(self.seed),
+            fair_hash: Unchanged(self.fair_hash.clone()),
+            client_seed: Unchanged(self.client_seed.clone()),
+            nonce: Unchanged(self.nonce),
+            seed_id: Unchanged(self.seed_id),
+            step_win: Unchanged(self.step_win),
+            step_take: Unchanged(self.step_take),
         }
     }
 
@@ -209,6 +236,16 @@ impl ActiveClone<ActiveModel> for Model {
             bet_counter: Unchanged(1),
             stake: Unchanged(None),
             win: Unchanged(None),
+            variant: Unchanged(None),
+            config_hash: Unchanged(None),
+            bought: Unchanged(None),
+            seed: Unchanged(None),
+            fair_hash: Unchanged(None),
+            client_seed: Unchanged(None),
+            nonce: Unchanged(None),
+            seed_id: Unchanged(None),
+            step_win: Unchanged(None),
+            step_take: Unchanged(None),
         }
     }
 }
@@ -250,6 +287,26 @@ pub struct StoreModel {
     pub stake: StoreValue<Option<i64>>,
     #[serde(skip_serializing_if = "StoreValue::is_none", default)]
     pub win: StoreValue<Option<i64>>,
+    #[serde(skip_serializing_if = "StoreValue::is_none", default)]
+    pub variant: StoreValue<Option<String>>,
+    #[serde(skip_serializing_if = "StoreValue::is_none", default)]
+    pub config_hash: StoreValue<Option<String>>,
+    #[serde(skip_serializing_if = "StoreValue::is_none", default)]
+    pub bought: StoreValue<Option<bool>>,
+    #[serde(skip_serializing_if = "StoreValue::is_none", default)]
+    pub seed: StoreValue<Option<i64>>,
+    #[serde(skip_serializing_if = "StoreValue::is_none", default)]
+    pub fair_hash: StoreValue<Option<String>>,
+    #[serde(skip_serializing_if = "StoreValue::is_none", default)]
+    pub client_seed: StoreValue<Option<String>>,
+    #[serde(skip_serializing_if = "StoreValue::is_none", default)]
+    pub nonce: StoreValue<Option<i64>>,
+    #[serde(skip_serializing_if = "StoreValue::is_none", default)]
+    pub seed_id: StoreValue<Option<i64>>,
+    #[serde(skip_serializing_if = "StoreValue::is_none", default)]
+    pub step_win: StoreValue<Option<i64>>,
+    #[serde(skip_serializing_if = "StoreValue::is_none", default)]
+    pub step_take: StoreValue<Option<i32>>,
 }
 
 #[cfg(feature = "redis")]
@@ -275,6 +332,16 @@ impl ActiveStore for ActiveModel {
             bet_counter: self.bet_counter.into(),
             stake: self.stake.into(),
             win: self.win.into(),
+            variant: self.variant.into(),
+            config_hash: self.config_hash.into(),
+            bought: self.bought.into(),
+            seed: self.seed.into(),
+            fair_hash: self.fair_hash.into(),
+            client_seed: self.client_seed.into(),
+            nonce: self.nonce.into(),
+            seed_id: self.seed_id.into(),
+            step_win: self.step_win.into(),
+            step_take: self.step_take.into(),
         }
     }
 }
\ No newline at end of file
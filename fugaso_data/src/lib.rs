@@ -3,6 +3,7 @@ pub mod fugaso_game;
 pub mod fugaso_percent;
 pub mod fugaso_action;
 pub mod fugaso_round;
+pub mod fugaso_fair_seed;
 pub mod promo_account;
 pub mod promo_transaction;
 pub mod promo_stats;
@@ -187,6 +188,16 @@ mod tests {
                 bet_counter: StoreValue::S(1),
                 stake: StoreValue::S(Some(6)),
                 win: StoreValue::S(Some(4)),
+                variant: StoreValue::S(None),
+                config_hash: StoreValue::S(None),
+                bought: StoreValue::S(None),
+                seed: StoreValue::S(None),
+                fair_hash: StoreValue::S(None),
+                client_seed: StoreValue::S(None),
+                nonce: StoreValue::S(None),
+                seed_id: StoreValue::S(None),
+                step_win: StoreValue::S(None),
+                step_take: StoreValue::S(None),
             }
         ));
         let json_rmp = rmp_serde::to_vec_named(&query).unwrap();
@@ -1,8 +1,9 @@
 use crate::fugaso_round::RoundStatus;
-use crate::{fugaso_action, fugaso_game, fugaso_percent, fugaso_round, launch_info, promo_account, promo_stats, promo_transaction, tournament_gain};
+use crate::{fugaso_action, fugaso_fair_seed, fugaso_game, fugaso_percent, fugaso_round, launch_info, promo_account, promo_stats, promo_transaction, tournament_gain};
 use fugaso_game::Model as Game;
+use sea_orm::prelude::DateTime;
 use sea_orm::prelude::Uuid;
-use sea_orm::sea_query::Query;
+use sea_orm::sea_query::{Expr, Query};
 use sea_orm::{ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbBackend, DbErr, FromQueryResult, Order, QueryFilter, Statement, TransactionTrait};
 use sea_orm::{EntityTrait, QueryOrder, QuerySelect};
 use std::collections::HashMap;
@@ -131,6 +132,14 @@ impl RoundRepository {
         }
     }
 
+    /// round of the user by the common id which the history shows
+    pub async fn find_user_round(&self, user_id: i64, game_id: i64, common_id: i64) -> Result<Option<fugaso_round::Model>, DbErr> {
+        fugaso_round::Entity::find()
+            .filter(Condition::all().add(fugaso_round::Column::UserId.eq(user_id)).add(fugaso_round::Column::GameId.eq(game_id)).add(fugaso_round::Column::CommonId.eq(common_id)))
+            .one(self.conn.as_ref())
+            .await
+    }
+
     pub async fn find_last_rounds(&self, user_id: i64, game_id: i64, limit: u64) -> Result<Vec<(fugaso_round::Model, Vec<fugaso_action::Model>)>, DbErr> {
         /*let round_with_actions: Vec<(fugaso_round::Model, Vec<fugaso_action::Model>)> = fugaso_round::Entity::find()
         .find_with_related(fugaso_action::Entity)
@@ -188,6 +197,31 @@ impl RoundRepository {
     }
 }
 
+#[derive(Debug)]
+pub struct FairSeedRepository {
+    pub conn: Arc<DatabaseConnection>,
+}
+
+impl FairSeedRepository {
+    pub async fn insert(&self, s: fugaso_fair_seed::ActiveModel) -> Result<fugaso_fair_seed::Model, DbErr> {
+        s.insert(self.conn.as_ref()).await
+    }
+
+    pub async fn find(&self, id: i64) -> Result<Option<fugaso_fair_seed::Model>, DbErr> {
+        fugaso_fair_seed::Entity::find_by_id(id).one(self.conn.as_ref()).await
+    }
+
+    /// the seed is retired, no session plays it any more, so it is shown with its rounds
+    pub async fn reveal(&self, id: i64, now: DateTime) -> Result<(), DbErr> {
+        fugaso_fair_seed::Entity::update_many()
+            .col_expr(fugaso_fair_seed::Column::Revealed, Expr::value(Some(now)))
+            .filter(Condition::all().add(fugaso_fair_seed::Column::Id.eq(id)).add(fugaso_fair_seed::Column::Revealed.is_null()))
+            .exec(self.conn.as_ref())
+            .await?;
+        Ok(())
+    }
+}
+
 pub struct TournamentGainRepository {
     pub conn: Arc<DatabaseConnection>,
 }
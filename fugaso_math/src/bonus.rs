@@ -0,0 +1,351 @@
+use crate::config_validator::{ConfigValidator, IssueKind};
+use crate::math::{IPlayResponse, SpinArg};
+use crate::protocol::{id, DatabaseStore, FreeGame, GameData, GameResult, Promo, SpinData};
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use crate::rng::RngSource;
+use fugaso_data::fugaso_action;
+use fugaso_data::fugaso_action::ActionKind;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
+pub enum Prize {
+    /// win of the factor of the stake
+    #[serde(rename = "WIN")]
+    Win(i64),
+    /// adds to the multiplier of the wins of the board
+    #[serde(rename = "MULTI")]
+    Multi(i32),
+    /// the board ends with the pick
+    #[serde(rename = "END")]
+    End,
+}
+
+/// prizes of a pick bonus declared by the math, they are shuffled over the board when the bonus starts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickConfig {
+    pub prizes: Vec<Prize>,
+    /// picks of a board, 0 for picking until the end prize or the last cell
+    pub picks: usize,
+}
+
+impl PickConfig {
+    pub fn deal<R: RngSource>(&self, rand: &mut R, stake: i64, start_total: i64) -> PickBoard {
+        let mut prizes = self.prizes.clone();
+        for i in (1..prizes.len()).rev() {
+            let j = rand.random(0, i + 1);
+            prizes.swap(i, j);
+        }
+        PickBoard {
+            prizes,
+            picks: vec![],
+            max_picks: self.picks,
+            stake,
+            start_total,
+        }
+    }
+}
+
+/// pick bonus of a game started by `trigger` symbols `symbol` anywhere on the grid
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickTrigger {
+    pub symbol: char,
+    pub trigger: usize,
+    #[serde(flatten)]
+    pub pick: PickConfig,
+}
+
+impl PickTrigger {
+    pub fn is_triggered(&self, grid: &Vec<Vec<char>>) -> bool {
+        grid.iter().flatten().filter(|s| **s == self.symbol).count() >= self.trigger
+    }
+
+    pub fn validate(&self) -> ConfigValidator {
+        let mut v = ConfigValidator::new(stringify!(PickTrigger));
+        if self.pick.prizes.is_empty() {
+            v.add(IssueKind::EmptyFeature, "prizes", "bonus without the prizes".to_string());
+        }
+        if self.trigger == 0 || self.pick.picks > self.pick.prizes.len() {
+            v.add(IssueKind::IllegalCount, "picks", format!("illegal picks {} or trigger {}", self.pick.picks, self.trigger));
+        }
+        if let Some(i) = self.pick.prizes.iter().position(|p| matches!(p, Prize::Win(f) if *f < 0) || matches!(p, Prize::Multi(m) if *m < 0)) {
+            v.add(IssueKind::IllegalMultiplier, &format!("prizes[{i}]"), format!("prize {:?}", self.pick.prizes[i]));
+        }
+        v
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pick {
+    pub index: usize,
+    pub prize: Prize,
+}
+
+/// state of the board for the client, the prizes which are not picked are shown only when the board is finished
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickState {
+    pub size: usize,
+    pub picks: Vec<Pick>,
+    pub left: usize,
+    pub multi: i32,
+    pub total: i64,
+    pub finished: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub board: Option<Vec<Prize>>,
+}
+
+/// board of a pick bonus, the whole board is stored in the `choice` of the actions of the bonus to restore the round
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickBoard {
+    pub prizes: Vec<Prize>,
+    pub picks: Vec<usize>,
+    pub max_picks: usize,
+    pub stake: i64,
+    /// total of the round before the bonus
+    pub start_total: i64,
+}
+
+impl PickBoard {
+    pub fn pick(&mut self, index: usize) -> Result<&Prize, ServerError> {
+        if self.is_finished() {
+            return Err(err_on!("bonus is finished!"));
+        }
+        if index >= self.prizes.len() || self.picks.contains(&index) {
+            return Err(err_on!(format!("illegal pick: {index}!")));
+        }
+        self.picks.push(index);
+        Ok(&self.prizes[index])
+    }
+
+    pub fn picked(&self) -> impl Iterator<Item = &Prize> {
+        self.picks.iter().map(|i| &self.prizes[*i])
+    }
+
+    pub fn left(&self) -> usize {
+        if self.picked().any(|p| *p == Prize::End) {
+            return 0;
+        }
+        let cells = self.prizes.len() - self.picks.len();
+        if self.max_picks > 0 {
+            std::cmp::min(cells, self.max_picks.saturating_sub(self.picks.len()))
+        } else {
+            cells
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.left() == 0
+    }
+
+    pub fn multi(&self) -> i32 {
+        1 + self
+            .picked()
+            .map(|p| match p {
+                Prize::Multi(m) => *m,
+                _ => 0,
+            })
+            .sum::<i32>()
+    }
+
+    /// wins of the picks by the multiplier of the board
+    pub fn total(&self) -> i64 {
+        let factor = self
+            .picked()
+            .map(|p| match p {
+                Prize::Win(f) => *f,
+                _ => 0,
+            })
+            .sum::<i64>();
+        factor * self.stake * self.multi() as i64
+    }
+
+    pub fn state(&self) -> PickState {
+        let finished = self.is_finished();
+        PickState {
+            size: self.prizes.len(),
+            picks: self
+                .picks
+                .iter()
+                .map(|i| Pick {
+                    index: *i,
+                    prize: self.prizes[*i].clone(),
+                })
+                .collect(),
+            left: self.left(),
+            multi: self.multi(),
+            total: self.total(),
+            finished,
+            board: if finished {
+                Some(self.prizes.clone())
+            } else {
+                None
+            },
+        }
+    }
+
+    pub fn to_choice(&self) -> Result<String, ServerError> {
+        serde_json::to_string(self).map_err(|e| err_on!(e))
+    }
+
+    pub fn from_choice(value: &str) -> Result<Self, ServerError> {
+        serde_json::from_str(value).map_err(|e| err_on!(e))
+    }
+
+    /// board of the last bonus action of the round when the bonus is not finished
+    pub fn restore(actions: &[fugaso_action::Model]) -> Result<Option<Self>, ServerError> {
+        match actions.iter().filter(|a| a.act_descr == Some(ActionKind::BONUS)).max_by_key(|a| a.id) {
+            Some(fugaso_action::Model {
+                choice: Some(c),
+                next_act: Some(ActionKind::BONUS),
+                ..
+            }) => Self::from_choice(c).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// result of the last pick on top of the result which started the bonus, the round total includes the wins of the board
+    pub fn play<S: DatabaseStore + Default + Clone, R: Default + Clone>(&self, prev: &SpinData<S, R>, arg: SpinArg) -> GameData<S, R> {
+        let total = self.start_total + self.total();
+        GameData::Bonus(BonusData {
+            spin: SpinData {
+                id: id::GAME_DATA,
+                balance: arg.balance,
+                result: GameResult {
+                    total,
+                    gains: vec![],
+                    ..prev.result.clone()
+                },
+                next_act: if self.is_finished() {
+                    ActionKind::CLOSE
+                } else {
+                    ActionKind::BONUS
+                },
+                round_id: arg.round_id,
+                round_type: arg.round_type,
+                round_multiplier: arg.round_multiplier,
+                promo: arg.promo,
+                ..prev.clone()
+            },
+            bonus: self.state(),
+            board: self.clone(),
+        })
+    }
+}
+
+/// result of a pick, `board` is hidden from the client and goes to the `choice` of the action
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BonusData<S: DatabaseStore + Default, R: Default> {
+    #[serde(flatten)]
+    pub spin: SpinData<S, R>,
+    pub bonus: PickState,
+    #[serde(skip)]
+    pub board: PickBoard,
+}
+
+impl<S: DatabaseStore + Default, R: Default> BonusData<S, R> {
+    pub fn new(spin: SpinData<S, R>, board: PickBoard) -> Self {
+        Self {
+            spin,
+            bonus: board.state(),
+            board,
+        }
+    }
+}
+
+impl<S: DatabaseStore + Default + 'static, R: Default + 'static> IPlayResponse for BonusData<S, R> {
+    fn create_action_default(&self) -> Result<fugaso_action::Model, ServerError> {
+        Ok(fugaso_action::Model {
+            choice: Some(self.board.to_choice()?),
+            ..self.spin.create_action_default()?
+        })
+    }
+
+    fn free(&self) -> Option<&FreeGame> {
+        self.spin.free()
+    }
+
+    fn has_bonus(&self) -> bool {
+        self.spin.has_bonus()
+    }
+
+    fn has_respin(&self) -> bool {
+        false
+    }
+
+    fn has_drop(&self) -> bool {
+        false
+    }
+
+    fn total(&self) -> i64 {
+        self.spin.total()
+    }
+
+    fn is_gamble_end(&self, total_bet: i64) -> bool {
+        self.spin.is_gamble_end(total_bet)
+    }
+
+    fn stops_on(&self) -> Vec<usize> {
+        self.spin.stops_on()
+    }
+
+    fn grid_on(&self) -> Vec<Vec<char>> {
+        self.spin.grid_on()
+    }
+
+    fn promo(&self) -> Promo {
+        self.spin.promo()
+    }
+
+    fn set_next_act(&mut self, kind: ActionKind) {
+        self.spin.set_next_act(kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bonus::{PickBoard, PickConfig, Prize};
+    use essential_rand::random::RandomGenerator;
+    use fugaso_data::fugaso_action::{self, ActionKind};
+
+    #[test]
+    pub fn test_pick_bonus() {
+        let config = PickConfig {
+            prizes: vec![Prize::Win(5), Prize::Win(10), Prize::Multi(1), Prize::End],
+            picks: 0,
+        };
+        let mut board = config.deal(&mut RandomGenerator::from_seed(7), 100, 40);
+        let mut prizes = board.prizes.clone();
+        prizes.sort_by_key(|p| format!("{p:?}"));
+        assert_eq!(prizes, vec![Prize::End, Prize::Multi(1), Prize::Win(10), Prize::Win(5)]);
+
+        let index = |b: &PickBoard, p: &Prize| b.prizes.iter().position(|v| v == p).unwrap();
+        let (win, multi, end) = (index(&board, &Prize::Win(10)), index(&board, &Prize::Multi(1)), index(&board, &Prize::End));
+        board.pick(win).unwrap();
+        board.pick(multi).unwrap();
+        assert!(board.pick(win).is_err());
+        let state = board.state();
+        assert_eq!((state.left, state.multi, state.total, state.finished, state.board), (2, 2, 2000, false, None));
+
+        // the whole board is in the choice of the action and the bonus is restored while it is not finished
+        let action = fugaso_action::Model {
+            id: 2,
+            act_descr: Some(ActionKind::BONUS),
+            next_act: Some(ActionKind::BONUS),
+            choice: Some(board.to_choice().unwrap()),
+            ..Default::default()
+        };
+        let mut restored = PickBoard::restore(&[action]).unwrap().unwrap();
+        assert_eq!(restored, board);
+        restored.pick(end).unwrap();
+        assert!(restored.is_finished());
+        assert_eq!(restored.state().board, Some(board.prizes.clone()));
+        assert!(restored.pick(index(&board, &Prize::Win(5))).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use crate::protocol::Gain;
+use crate::rand::{ReelRandom, Result};
+use std::collections::BTreeSet;
+
+/// cells of the gains as `(column, row)` taken from their points
+pub fn win_cells(gains: &[Gain]) -> BTreeSet<(usize, usize)> {
+    gains.iter().flat_map(|g| g.points.iter().map(|p| (p.x, p.y))).collect()
+}
+
+/// the cells fall out of the grid, the symbols above them drop down and the strips of the reels fill the columns from the top,
+/// so the stop of a column moves up by the count of its removed cells like the reel goes on spinning
+pub fn refill(reels: &Vec<Vec<char>>, stops: &Vec<usize>, grid: &Vec<Vec<char>>, cells: &BTreeSet<(usize, usize)>) -> (Vec<usize>, Vec<Vec<char>>) {
+    grid.iter()
+        .enumerate()
+        .map(|(c, column)| {
+            let reel = &reels[c];
+            let kept = column.iter().enumerate().filter(|(r, _)| !cells.contains(&(c, *r))).map(|(_, s)| *s).collect::<Vec<_>>();
+            let removed = column.len() - kept.len();
+            let stop = (stops[c] + reel.len() - removed % reel.len()) % reel.len();
+            let column_on = (0..removed).map(|r| reel[(stop + r) % reel.len()]).chain(kept).collect::<Vec<_>>();
+            (stop, column_on)
+        })
+        .unzip()
+}
+
+/// result of a cascade step, `removed` are the cells of the previous grid which fell out
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CascadeStep {
+    pub stops: Vec<usize>,
+    pub grid: Vec<Vec<char>>,
+    pub removed: BTreeSet<(usize, usize)>,
+    pub multiplier: i32,
+}
+
+/// repeats the drops while the grid has wins, the multipliers go by the number of the cascade and the last one stays for the rest
+#[derive(Debug, Clone)]
+pub struct CascadeEngine {
+    pub multipliers: Vec<i32>,
+}
+
+impl Default for CascadeEngine {
+    fn default() -> Self {
+        Self {
+            multipliers: vec![1],
+        }
+    }
+}
+
+impl CascadeEngine {
+    pub fn new(multipliers: Vec<i32>) -> Self {
+        Self {
+            multipliers,
+        }
+    }
+
+    /// multiplier of the wins of the cascade, 0 is the spin itself
+    pub fn multiplier(&self, cascade: usize) -> i32 {
+        self.multipliers.get(cascade).or(self.multipliers.last()).cloned().unwrap_or(1)
+    }
+
+    /// grid of the next cascade after the cells of `gains` fall out, none without the wins
+    pub fn next<R: ReelRandom>(&self, rand: &R, category: usize, cascade: usize, stops: &Vec<usize>, grid: &Vec<Vec<char>>, gains: &[Gain]) -> Result<Option<CascadeStep>> {
+        let removed = win_cells(gains);
+        if removed.is_empty() {
+            return Ok(None);
+        }
+        let (stops, grid) = rand.refill_cols(category, stops, grid, &removed)?;
+        Ok(Some(CascadeStep {
+            stops,
+            grid,
+            removed,
+            multiplier: self.multiplier(cascade + 1),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cascade::{refill, win_cells, CascadeEngine};
+    use crate::cluster::ClusterEvaluator;
+    use crate::rand::ReelRandom;
+    use std::collections::{BTreeSet, HashMap};
+
+    #[test]
+    pub fn test_cascade_refill() {
+        let reels = vec![vec!['A', 'B', 'C', 'D', 'E'], vec!['F', 'G', 'H']];
+        let grid = vec![vec!['C', 'D', 'E'], vec!['F', 'G', 'H']];
+        // the cell above falls down and the strip goes on from the stop before the window
+        let (stops, grid_on) = refill(&reels, &vec![2, 0], &grid, &BTreeSet::from([(0, 1)]));
+        assert_eq!(stops, vec![1, 0]);
+        assert_eq!(grid_on, vec![vec!['B', 'C', 'E'], vec!['F', 'G', 'H']]);
+        let (stops, grid_on) = refill(&reels, &vec![2, 0], &grid, &BTreeSet::from([(1, 0), (1, 1), (1, 2)]));
+        assert_eq!(stops, vec![2, 0]);
+        assert_eq!(grid_on, grid);
+
+        let wins = HashMap::from([('A', HashMap::from([(3, 1)]))]);
+        let gains = ClusterEvaluator::new(&wins).eval(&vec![vec!['A', 'A'], vec!['A', 'B']], 1);
+        assert_eq!(win_cells(&gains), BTreeSet::from([(0, 0), (0, 1), (1, 0)]));
+        let engine = CascadeEngine::new(vec![1, 2, 3]);
+        assert_eq!((engine.multiplier(0), engine.multiplier(2), engine.multiplier(5)), (1, 3, 3));
+
+        // a game without the cascades does not refill
+        struct Fixed;
+        impl ReelRandom for Fixed {
+            fn rand_cols(&mut self, _category: usize, _combos: Option<Vec<usize>>) -> (Vec<usize>, Vec<Vec<char>>) {
+                (vec![0, 0], vec![vec!['A', 'A'], vec!['A', 'B']])
+            }
+        }
+        assert!(engine.next(&Fixed, 0, 0, &vec![0, 0], &vec![vec!['A', 'A'], vec!['A', 'B']], &gains).is_err());
+        assert_eq!(engine.next(&Fixed, 0, 0, &vec![0, 0], &vec![vec!['A', 'A'], vec!['A', 'B']], &[]).ok(), Some(None));
+    }
+}
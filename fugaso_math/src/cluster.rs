@@ -0,0 +1,148 @@
+use crate::math::Request;
+use crate::protocol::{BasePoint, Gain};
+use std::collections::{BTreeSet, HashMap};
+
+/// orthogonal neighbours of a cell as `(column, row)`, the columns can have different counts of rows
+pub fn neighbours(grid: &Vec<Vec<char>>, column: usize, row: usize) -> Vec<(usize, usize)> {
+    let mut cells = vec![];
+    if row > 0 {
+        cells.push((column, row - 1));
+    }
+    if row + 1 < grid[column].len() {
+        cells.push((column, row + 1));
+    }
+    if column > 0 && row < grid[column - 1].len() {
+        cells.push((column - 1, row));
+    }
+    if column + 1 < grid.len() && row < grid[column + 1].len() {
+        cells.push((column + 1, row));
+    }
+    cells
+}
+
+/// cells connected to `start` through the cells accepted by `accept`, `start` itself is not checked
+pub fn connected(grid: &Vec<Vec<char>>, start: (usize, usize), accept: impl Fn(char) -> bool) -> BTreeSet<(usize, usize)> {
+    let mut cells = BTreeSet::from([start]);
+    let mut stack = vec![start];
+    while let Some((c, r)) = stack.pop() {
+        for n in neighbours(grid, c, r) {
+            if accept(grid[n.0][n.1]) && cells.insert(n) {
+                stack.push(n);
+            }
+        }
+    }
+    cells
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cluster {
+    pub symbol: char,
+    pub cells: BTreeSet<(usize, usize)>,
+}
+
+/// pays of the clusters of the connected symbols with `wins` of the cluster size, the factor of the greatest size
+/// not above the size of a cluster is paid, a wild joins the clusters of every symbol around it
+#[derive(Debug, Clone)]
+pub struct ClusterEvaluator<'a> {
+    pub wins: &'a HashMap<char, HashMap<usize, i32>>,
+    pub wilds: Vec<char>,
+}
+
+impl<'a> ClusterEvaluator<'a> {
+    pub fn new(wins: &'a HashMap<char, HashMap<usize, i32>>) -> Self {
+        Self {
+            wins,
+            wilds: vec![],
+        }
+    }
+
+    pub fn with_wilds(mut self, wilds: &[char]) -> Self {
+        self.wilds = wilds.to_vec();
+        self
+    }
+
+    pub fn is_wild(&self, c: char) -> bool {
+        self.wilds.contains(&c)
+    }
+
+    pub fn factor(&self, symbol: char, size: usize) -> i32 {
+        self.wins.get(&symbol).and_then(|m| m.iter().filter(|(s, _)| **s <= size).max_by_key(|(s, _)| **s).map(|(_, f)| *f)).unwrap_or(0)
+    }
+
+    /// clusters of every symbol which is not a wild, the wilds are counted in each cluster they touch
+    pub fn clusters(&self, grid: &Vec<Vec<char>>) -> Vec<Cluster> {
+        let mut clusters = vec![];
+        let mut visited = BTreeSet::new();
+        for (c, column) in grid.iter().enumerate() {
+            for (r, symbol) in column.iter().enumerate() {
+                if self.is_wild(*symbol) || visited.contains(&(c, r)) {
+                    continue;
+                }
+                let cells = connected(grid, (c, r), |s| s == *symbol || self.is_wild(s));
+                visited.extend(cells.iter().filter(|(c, r)| grid[*c][*r] == *symbol).cloned());
+                clusters.push(Cluster {
+                    symbol: *symbol,
+                    cells,
+                });
+            }
+        }
+        clusters
+    }
+
+    /// wins of the clusters in the order of their first cells, `line_num` is the number of the cluster
+    pub fn eval(&self, grid: &Vec<Vec<char>>, bet: i64) -> Vec<Gain> {
+        let columns = grid.len();
+        self.clusters(grid)
+            .into_iter()
+            .filter_map(|cluster| {
+                let count = cluster.cells.len();
+                let factor = self.factor(cluster.symbol, count);
+                if factor > 0 {
+                    Some(Gain {
+                        symbol: cluster.symbol,
+                        count,
+                        amount: factor as i64 * bet,
+                        multi: 1,
+                        columns: Some(columns),
+                        indexes: cluster.cells.iter().map(|(c, r)| r * columns + c).collect(),
+                        points: cluster.cells.iter().map(|(c, r)| BasePoint { x: *c, y: *r }).collect(),
+                        ..Default::default()
+                    })
+                } else {
+                    None
+                }
+            })
+            .enumerate()
+            .map(|(line_num, g)| Gain {
+                line_num,
+                ..g
+            })
+            .collect()
+    }
+
+    /// wins with the bet of `req.bet`
+    pub fn eval_request(&self, req: &Request, grid: &Vec<Vec<char>>, multiplier: i32) -> Vec<Gain> {
+        self.eval(grid, req.bet as i64 * multiplier as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cluster::{neighbours, ClusterEvaluator};
+    use crate::protocol::BasePoint;
+    use std::collections::HashMap;
+
+    #[test]
+    pub fn test_cluster_evaluator() {
+        let wins = HashMap::from([('A', HashMap::from([(4, 2), (6, 5)])), ('B', HashMap::from([(4, 3)]))]);
+        // the wild joins two groups of A and is counted in the cluster of B as well
+        let grid = vec![vec!['A', 'A', 'B'], vec!['A', 'W', 'B'], vec!['C', 'A', 'B'], vec!['C', 'A']];
+        assert_eq!(neighbours(&grid, 3, 1), vec![(3, 0), (2, 1)]);
+        let gains = ClusterEvaluator::new(&wins).with_wilds(&['W']).eval(&grid, 1);
+        assert_eq!(gains.len(), 2);
+        assert_eq!((gains[0].symbol, gains[0].count, gains[0].amount, gains[0].line_num), ('A', 6, 5, 0));
+        assert!(gains[0].points.contains(&BasePoint { x: 1, y: 1 }));
+        assert_eq!((gains[1].symbol, gains[1].count, gains[1].amount, gains[1].line_num), ('B', 4, 3, 1));
+        assert!(gains[1].points.contains(&BasePoint { x: 1, y: 1 }));
+    }
+}
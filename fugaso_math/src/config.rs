@@ -1,9 +1,72 @@
 use crate::protocol::{deserialize_lines, deserialize_vec_reels, serialize_vec_reels};
+use log::warn;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::{BTreeMap, HashMap};
 
 pub type ReelDist = Vec<Vec<BTreeMap<i32, Vec<char>>>>;
 
+/// lowercase hex of the plain SHA-256 of the content, `sha256sum` gives the same
+pub fn sha256_hex(content: &str) -> String {
+    Sha256::digest(content.as_bytes()).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// content hash of a config with its reels, it is stored on every round to find the config which played it,
+/// it is `sha256_hex` of the config, a newline and the reels
+pub fn config_hash(config: &str, reels: Option<&str>) -> String {
+    sha256_hex(&format!("{config}\n{}", reels.unwrap_or("")))
+}
+
+/// certified config of a game for the `percent` of `fugaso_percent`, the embedded config of the game is used without the json,
+/// retired variants are not selected any more but kept to replay their rounds
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigVariant {
+    pub id: &'static str,
+    pub percent: i32,
+    pub config: Option<&'static str>,
+    pub reels: Option<&'static str>,
+    pub active: bool,
+}
+
+impl ConfigVariant {
+    pub fn config(&self) -> Option<String> {
+        self.config.map(|c| c.to_string())
+    }
+
+    pub fn reels(&self) -> Option<String> {
+        self.reels.map(|r| r.to_string())
+    }
+
+    /// `config` and `reels` are the embedded jsons of the game
+    pub fn hash(&self, config: &str, reels: Option<&str>) -> String {
+        config_hash(self.config.unwrap_or(config), self.reels.or(reels))
+    }
+}
+
+/// variant of the percent, the first active variant is the default one when the percent is absent,
+/// a percent without a certified variant falls back to the default one with a warning
+pub fn select_variant(variants: &[ConfigVariant], percent: Option<i32>) -> Option<&ConfigVariant> {
+    let mut active = variants.iter().filter(|v| v.active);
+    if let Some(p) = percent {
+        if let Some(v) = active.clone().find(|v| v.percent == p) {
+            return Some(v);
+        }
+        let fallback = active.next();
+        warn!("percent {p} is not certified - fallback to variant {:?}!", fallback.map(|v| v.id));
+        return fallback;
+    }
+    active.next()
+}
+
+/// `variant_hash` is the hash function of the game which knows its embedded configs
+pub fn find_variant_by_hash<'a>(variants: &'a [ConfigVariant], variant_hash: fn(&ConfigVariant) -> String, hash: &str) -> Option<&'a ConfigVariant> {
+    variants.iter().find(|v| variant_hash(v) == hash)
+}
+
+pub fn find_variant<'a>(variants: &'a [ConfigVariant], id: &str) -> Option<&'a ConfigVariant> {
+    variants.iter().find(|v| v.id == id)
+}
+
 pub trait BaseConfig {
     fn reels(&self) -> &Vec<Vec<Vec<char>>>;
 }
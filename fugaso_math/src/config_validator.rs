@@ -0,0 +1,234 @@
+use crate::config::ReelDist;
+use crate::math::MathSettings;
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssueKind {
+    Parse,
+    UnusedSymbol,
+    LineRow,
+    LineLength,
+    BetCounters,
+    EmptyDist,
+    ZeroWeight,
+    DistCount,
+    ReelCategories,
+    JackpotCoin,
+    UnreachableState,
+    NoReturn,
+    MissingTransition,
+    EmptyFeature,
+    SpecialSymbol,
+    IllegalCount,
+    IllegalMultiplier,
+    MissingProgress,
+    MissingSymbol,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigIssue {
+    pub kind: IssueKind,
+    /// field of the config like `distCoin[3]` or `lines[7]`
+    pub path: String,
+    pub message: String,
+}
+
+impl Display for ConfigIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} {}: {}", self.kind, self.path, self.message)
+    }
+}
+
+/// collects every problem of a game config instead of failing on the first one
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigValidator {
+    pub name: String,
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ConfigValidator {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            issues: vec![],
+        }
+    }
+
+    pub fn add(&mut self, kind: IssueKind, path: &str, message: String) {
+        self.issues.push(ConfigIssue {
+            kind,
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), ServerError> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(err_on!(format!("{self}")))
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(&mut self, path: &str, json: &str) -> Option<T> {
+        match serde_json::from_str(json) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                self.add(IssueKind::Parse, path, e.to_string());
+                None
+            }
+        }
+    }
+
+    /// symbols of the paytable which can not appear on the grid, `overlays` are placed over the reels by the game
+    pub fn check_paytable(&mut self, wins: &HashMap<char, HashMap<usize, i32>>, reels: &Vec<Vec<Vec<char>>>, reels_cfg: Option<&ReelDist>, overlays: &[char], columns: usize) {
+        let mut symbols = reels.iter().flatten().flatten().cloned().collect::<BTreeSet<_>>();
+        if let Some(dist) = reels_cfg {
+            symbols.extend(dist.iter().flatten().flat_map(|c| c.values().flatten().cloned()));
+        }
+        symbols.extend(overlays.iter().cloned());
+        let mut unused = wins.keys().filter(|s| !symbols.contains(s)).collect::<Vec<_>>();
+        unused.sort();
+        for s in unused {
+            self.add(IssueKind::UnusedSymbol, "wins", format!("symbol {s} never appears on the reels"));
+        }
+        let mut pays = wins.iter().collect::<Vec<_>>();
+        pays.sort_by_key(|p| p.0);
+        for (s, m) in pays {
+            if let Some(count) = m.keys().find(|c| **c == 0 || **c > columns) {
+                self.add(IssueKind::LineLength, "wins", format!("symbol {s} pays for {count} symbols of {columns} columns"));
+            }
+        }
+    }
+
+    pub fn check_lines(&mut self, lines: &Vec<Vec<usize>>, rows: usize, columns: usize) {
+        if lines.is_empty() {
+            self.add(IssueKind::LineLength, "lines", "lines are empty".to_string());
+        }
+        for (i, line) in lines.iter().enumerate() {
+            let path = format!("lines[{i}]");
+            if line.len() != columns {
+                self.add(IssueKind::LineLength, &path, format!("line has {} columns of {columns}", line.len()));
+            }
+            if let Some(r) = line.iter().find(|r| **r >= rows) {
+                self.add(IssueKind::LineRow, &path, format!("row {r} is out of {rows} rows"));
+            }
+        }
+    }
+
+    /// the bet counters are positive and increasing
+    pub fn check_counter_values(&mut self, bet_counters: &[usize]) {
+        if bet_counters.is_empty() || bet_counters.contains(&0) {
+            self.add(IssueKind::BetCounters, "betCounters", format!("illegal bet counters {bet_counters:?}"));
+        }
+        if bet_counters.windows(2).any(|w| w[0] >= w[1]) {
+            self.add(IssueKind::BetCounters, "betCounters", format!("bet counters {bet_counters:?} are not increasing"));
+        }
+    }
+
+    /// `counters` are the distributions which are taken by the index of the bet counter with their sizes
+    pub fn check_bet_counters(&mut self, bet_counters: &[usize], settings: &MathSettings, counters: &[(&str, usize, usize)]) {
+        self.check_counter_values(bet_counters);
+        if settings.bet_counters != bet_counters {
+            self.add(IssueKind::BetCounters, "betCounters", format!("bet counters {bet_counters:?} do not match the math settings {:?}", settings.bet_counters));
+        }
+        for (name, size, per_counter) in counters.iter() {
+            if *size != bet_counters.len() * per_counter {
+                self.add(IssueKind::DistCount, name, format!("{size} distributions for {} bet counters", bet_counters.len()));
+            }
+        }
+    }
+
+    /// keys of the distributions are cumulative bounds, so they must increase from a positive one
+    pub fn check_dist<V>(&mut self, path: &str, dist: &BTreeMap<i32, V>) {
+        if dist.is_empty() {
+            self.add(IssueKind::EmptyDist, path, "distribution is empty".to_string());
+        } else if let Some(b) = dist.keys().find(|b| **b <= 0) {
+            self.add(IssueKind::ZeroWeight, path, format!("bound {b} gives a zero weight"));
+        }
+    }
+
+    pub fn check_dists<V>(&mut self, name: &str, dists: &[BTreeMap<i32, V>]) {
+        if dists.is_empty() {
+            self.add(IssueKind::EmptyDist, name, "distributions are empty".to_string());
+        }
+        for (i, d) in dists.iter().enumerate() {
+            self.check_dist(&format!("{name}[{i}]"), d);
+        }
+    }
+
+    /// chance `(hits, total)` of the tuple distributions
+    pub fn check_chance(&mut self, path: &str, chance: (i32, i32)) {
+        if chance.1 <= 0 || chance.0 < 0 || chance.0 > chance.1 {
+            self.add(IssueKind::ZeroWeight, path, format!("illegal chance {chance:?}"));
+        }
+    }
+
+    pub fn check_reel_dist(&mut self, dist: &ReelDist, categories: usize, columns: usize, rows: usize) {
+        if dist.len() != categories {
+            self.add(IssueKind::ReelCategories, "reels", format!("{} categories of {categories}", dist.len()));
+        }
+        for (c, category) in dist.iter().enumerate() {
+            if category.len() != columns {
+                self.add(IssueKind::ReelCategories, &format!("reels[{c}]"), format!("{} columns of {columns}", category.len()));
+            }
+            for (i, column) in category.iter().enumerate() {
+                let path = format!("reels[{c}][{i}]");
+                self.check_dist(&path, column);
+                if let Some(w) = column.values().find(|w| w.len() != rows) {
+                    self.add(IssueKind::ReelCategories, &path, format!("window {w:?} has not {rows} rows"));
+                }
+            }
+        }
+    }
+
+    /// `categories` is the least count of the categories which the game takes from the reels
+    pub fn check_reels(&mut self, reels: &Vec<Vec<Vec<char>>>, categories: usize) {
+        if reels.len() < categories {
+            self.add(IssueKind::ReelCategories, "reels", format!("{} categories of {categories}", reels.len()));
+        }
+        for (c, category) in reels.iter().enumerate() {
+            if category.is_empty() {
+                self.add(IssueKind::ReelCategories, &format!("reels[{c}]"), "category is empty".to_string());
+            }
+            if let Some(i) = category.iter().position(|r| r.is_empty()) {
+                self.add(IssueKind::EmptyDist, &format!("reels[{c}][{i}]"), "reel is empty".to_string());
+            }
+        }
+    }
+
+    /// jackpot values of `map_jack` are coin values, so they must be drawn by some coin distribution
+    pub fn check_jackpots(&mut self, map_jack: &HashMap<i32, char>, dist_coin: &[BTreeMap<i32, i32>]) {
+        let values = dist_coin.iter().flat_map(|d| d.values().cloned()).collect::<BTreeSet<_>>();
+        let mut missing = map_jack.keys().filter(|k| !values.contains(k)).collect::<Vec<_>>();
+        missing.sort();
+        for k in missing {
+            self.add(IssueKind::JackpotCoin, "mapJack", format!("jackpot {k} is absent in distCoin"));
+        }
+    }
+
+    pub fn merge(&mut self, other: ConfigValidator) {
+        self.issues.extend(other.issues);
+    }
+}
+
+impl Display for ConfigValidator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} issues", self.name, self.issues.len())?;
+        for issue in self.issues.iter() {
+            write!(f, "\n  {issue}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1172 @@
+use crate::bonus::{BonusData, PickBoard, PickTrigger};
+use crate::cascade::CascadeEngine;
+use crate::cluster::ClusterEvaluator;
+use crate::config::{BaseConfig, ReelDist};
+use crate::config_validator::{ConfigValidator, IssueKind};
+use crate::free::{FreeConfig, FreeSpins};
+use crate::fsm::{FsmDefinition, SlotFSM, FSM};
+use crate::gamble::{GambleChoice, GambleData, GambleLimit, Gambler};
+use crate::hold::{HoldArg, HoldConfig, HoldEngine, HoldInfo};
+use crate::lines::{LineEvaluator, PayDirection};
+use crate::math::{BetCalculator, DefaultBetCalculator, GameInitArg, JoinArg, MathSettings, Request, SlotMath, SpinArg, Step};
+use crate::protocol::{deserialize_lines, deserialize_vec_reels, id, serialize_vec_reels, BasePoint, Gain, GameData, GameResult, InitialData, SpinData, StartInfo, Win};
+use crate::rand::{BaseRandom, GroupRandom, ReelRandom, Result as RandResult};
+use crate::rng::{DefaultSource, RngSource};
+use crate::seed::Seed;
+use crate::validator::RequestValidator;
+use crate::ways::WaysEvaluator;
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use fugaso_data::fugaso_action;
+use fugaso_data::fugaso_action::ActionKind;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+pub const BASE_CATEGORY: usize = 0;
+
+/// holds of a grid without the held cells, one per cell
+fn empty_holds(grid: &Vec<Vec<char>>) -> Vec<i32> {
+    grid.iter().flat_map(|r| r.iter().map(|_| 0)).collect()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EvaluatorKind {
+    #[default]
+    #[serde(rename = "LINES")]
+    Lines,
+    #[serde(rename = "WAYS")]
+    Ways,
+    #[serde(rename = "CLUSTER")]
+    Cluster,
+}
+
+fn default_bet_counters() -> Vec<usize> {
+    vec![1]
+}
+
+/// a whole game as data, the base spins take the category 0 of the reels, the free spins take the categories of `free`
+/// and the respins of `hold` take `hold_category`, the reel distributions go before the strips when they are present
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameDefinition {
+    pub cols: usize,
+    pub rows: usize,
+    #[serde(deserialize_with = "deserialize_vec_reels", serialize_with = "serialize_vec_reels")]
+    pub reels: Vec<Vec<Vec<char>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reel_dist: Option<ReelDist>,
+    #[serde(default)]
+    pub evaluator: EvaluatorKind,
+    #[serde(default, deserialize_with = "deserialize_lines")]
+    pub lines: Vec<Vec<usize>>,
+    #[serde(default)]
+    pub direction: PayDirection,
+    pub wins: HashMap<char, HashMap<usize, i32>>,
+    #[serde(default)]
+    pub wilds: Vec<char>,
+    /// symbol paid anywhere on the grid by the factors of `scatter_wins` of the playing bet
+    #[serde(default)]
+    pub scatter: Option<char>,
+    #[serde(default)]
+    pub scatter_wins: BTreeMap<usize, i32>,
+    /// coins of a spin of the ways and the clusters, the line games take the count of the lines
+    #[serde(default)]
+    pub cost: usize,
+    #[serde(default = "default_bet_counters")]
+    pub bet_counters: Vec<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub free: Option<FreeConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hold: Option<HoldConfig>,
+    #[serde(default)]
+    pub hold_category: usize,
+    /// cap of the win of a round in the playing bets, 0 without the cap
+    #[serde(default)]
+    pub max_win: i32,
+    /// transitions of the game, the default transitions of `SlotFSM` without them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fsm: Option<FsmDefinition>,
+    /// multipliers of the cascades by their number from the spin, the winning cells of a base grid fall out and the strips
+    /// of the reels drop the new symbols while the grid wins, the base game does not cascade without them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cascade: Option<Vec<i32>>,
+    /// pick bonus started by a base grid, the board is dealt by the first pick and pays by the playing bet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bonus: Option<PickTrigger>,
+    /// the win of a round is gambled by the cards and halved by the half collects before the collect
+    #[serde(default)]
+    pub gamble: bool,
+}
+
+impl BaseConfig for GameDefinition {
+    fn reels(&self) -> &Vec<Vec<Vec<char>>> {
+        &self.reels
+    }
+}
+
+impl GameDefinition {
+    pub fn coins(&self) -> usize {
+        match self.evaluator {
+            EvaluatorKind::Lines => self.lines.len(),
+            _ => self.cost,
+        }
+    }
+
+    pub fn settings(&self) -> MathSettings {
+        MathSettings {
+            lines: vec![self.coins()],
+            reels: vec![self.cols],
+            bet_counters: self.bet_counters.clone(),
+        }
+    }
+
+    pub fn validate(&self) -> ConfigValidator {
+        let mut v = ConfigValidator::new(stringify!(GameDefinition));
+        let free_categories = self.free.as_ref().map(|f| f.categories.clone()).unwrap_or_default();
+        let categories = free_categories.iter().chain([BASE_CATEGORY, self.hold_category].iter()).max().map(|c| c + 1).unwrap_or(1);
+        v.check_reels(&self.reels, categories);
+        if let Some(c) = self.reels.iter().position(|c| c.len() != self.cols) {
+            v.add(IssueKind::ReelCategories, &format!("reels[{c}]"), format!("{} columns of {}", self.reels[c].len(), self.cols));
+        }
+        if let Some(dist) = self.reel_dist.as_ref() {
+            v.check_reel_dist(dist, self.reels.len(), self.cols, self.rows);
+        }
+        v.check_paytable(&self.wins, &self.reels, self.reel_dist.as_ref(), &[], self.cols);
+        match self.evaluator {
+            EvaluatorKind::Lines => v.check_lines(&self.lines, self.rows, self.cols),
+            _ if self.cost == 0 => v.add(IssueKind::LineLength, "cost", format!("{:?} without the cost", self.evaluator)),
+            _ => {}
+        }
+        if let Some(s) = self.scatter.filter(|s| self.wins.contains_key(s)) {
+            v.add(IssueKind::UnusedSymbol, "wins", format!("scatter {s} is paid by scatterWins"));
+        }
+        v.check_counter_values(&self.bet_counters);
+        if let Some(free) = self.free.as_ref() {
+            v.merge(free.validate());
+        }
+        if let Some(hold) = self.hold.as_ref() {
+            v.merge(hold.validate());
+        }
+        if let Some(fsm) = self.fsm.as_ref() {
+            v.merge(fsm.verify());
+        }
+        if let Some(cascade) = self.cascade.as_ref() {
+            if cascade.is_empty() {
+                v.add(IssueKind::EmptyFeature, "cascade", "cascade without the multipliers".to_string());
+            }
+            if let Some(i) = cascade.iter().position(|m| *m < 1) {
+                v.add(IssueKind::IllegalMultiplier, &format!("cascade[{i}]"), format!("multiplier {}", cascade[i]));
+            }
+            if self.reel_dist.is_some() {
+                v.add(IssueKind::ReelCategories, "cascade", "the cascades refill the strips of the reels, not the reel distributions".to_string());
+            }
+        }
+        if let Some(bonus) = self.bonus.as_ref() {
+            v.merge(bonus.validate());
+        }
+        v
+    }
+}
+
+pub struct ConfigurableRandom<R: RngSource = DefaultSource> {
+    pub p: GroupRandom<GameDefinition, R>,
+}
+
+impl ConfigurableRandom {
+    pub fn new(config: Arc<GameDefinition>) -> Self {
+        Self::with_rand(config, DefaultSource::from_entropy())
+    }
+}
+
+impl<R: RngSource> ConfigurableRandom<R> {
+    pub fn with_rand(config: Arc<GameDefinition>, rand: R) -> Self {
+        Self {
+            p: GroupRandom {
+                reels_cfg: Arc::new(config.reel_dist.clone().unwrap_or_default()),
+                base: BaseRandom {
+                    rand,
+                    rows: config.rows,
+                    config,
+                },
+            },
+        }
+    }
+
+    pub fn reseed(&mut self, seed: Seed) {
+        self.p.reseed(seed)
+    }
+
+    pub fn rand_grid(&mut self, category: usize, combos: Option<Vec<usize>>) -> RandResult<(Vec<usize>, Vec<Vec<char>>)> {
+        if self.p.config().reel_dist.is_some() {
+            self.p.rand_cols_group(category, combos)
+        } else {
+            Ok(self.p.base.rand_cols(category, combos))
+        }
+    }
+}
+
+/// math of a `GameDefinition`, the special of a result is the `HoldInfo` of the hold and win or only the total of the round without it
+pub struct ConfigurableSlotMath {
+    pub result: Arc<GameData<HoldInfo, StartInfo>>,
+    pub config: Arc<GameDefinition>,
+    pub rand: ConfigurableRandom,
+    /// number of the cascade of the result, 0 is the spin
+    pub cascade: usize,
+    pub gambler: Gambler,
+}
+
+impl ConfigurableSlotMath {
+    /// the definition is validated here, a broken one never reaches the players
+    pub fn new(json: &str) -> Result<Self, ServerError> {
+        let config: GameDefinition = serde_json::from_str(json).map_err(|e| err_on!(e))?;
+        config.validate().into_result()?;
+        let config = Arc::new(config);
+        let rand = ConfigurableRandom::new(Arc::clone(&config));
+        Self::custom(rand, config)
+    }
+
+    pub fn custom(mut rand: ConfigurableRandom, config: Arc<GameDefinition>) -> Result<Self, ServerError> {
+        let (stops, grid) = rand.rand_grid(BASE_CATEGORY, None)?;
+        Ok(Self {
+            rand,
+            result: Arc::new(GameData::Spin(SpinData {
+                id: id::GAME_DATA,
+                result: GameResult {
+                    stops,
+                    holds: empty_holds(&grid),
+                    grid,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })),
+            config,
+            cascade: 0,
+            gambler: Gambler::new(DefaultSource::from_entropy()),
+        })
+    }
+
+    /// result of the round under the gamble, the gamble goes on from the result of the last gamble
+    fn gamble_spin<'a>(config: &GameDefinition, result: &'a GameData<HoldInfo, StartInfo>) -> Result<&'a SpinData<HoldInfo, StartInfo>, ServerError> {
+        if !config.gamble {
+            return Err(err_on!("gamble is not supported!"));
+        }
+        match result {
+            GameData::Spin(v) | GameData::ReSpin(v) | GameData::FreeSpin(v) | GameData::Drop(v) => Ok(v),
+            GameData::Bonus(v) => Ok(&v.spin),
+            GameData::Gamble(v) => Ok(&v.spin),
+            _ => Err(err_on!("Illegal state!")),
+        }
+    }
+
+    fn check_counter(&self, request: &Request) -> Result<(), ServerError> {
+        if self.config.bet_counters.contains(&request.bet_counter) {
+            Ok(())
+        } else {
+            Err(err_on!("illegal bet counter!"))
+        }
+    }
+
+    fn calc_playing_bet(&self, request: &Request, multiplier: i32) -> i64 {
+        self.create_bet_calculator().calc_playing_bet(request) * multiplier as i64
+    }
+
+    fn calc_max_win(&self, request: &Request, multiplier: i32) -> i64 {
+        self.calc_playing_bet(request, multiplier) * self.config.max_win as i64
+    }
+
+    /// total of the round by the cap with the stop of the cap
+    fn cap(&self, total: i64, max_win: i64) -> (i64, Option<i32>) {
+        if max_win > 0 && total >= max_win {
+            (max_win, Some(self.config.max_win))
+        } else {
+            (total, None)
+        }
+    }
+
+    /// wins of the grid by the evaluator and the scatter, a factor 1 of the paytable pays the coin of the request
+    pub fn evaluate(&self, request: &Request, round_multiplier: i32, grid: &Vec<Vec<char>>, multi: i32) -> Vec<Gain> {
+        let config = &self.config;
+        let coin = request.bet as i64 * request.denom as i64 * round_multiplier as i64;
+        let mut gains = match config.evaluator {
+            EvaluatorKind::Lines => LineEvaluator::new(&config.lines, &config.wins)
+                .with_direction(config.direction)
+                .with_wilds(&config.wilds)
+                .with_breakers(&config.scatter.into_iter().collect::<Vec<_>>())
+                .eval(grid, request.line, coin),
+            EvaluatorKind::Ways => WaysEvaluator::new(&config.wins).with_wilds(&config.wilds).eval(grid, coin),
+            EvaluatorKind::Cluster => ClusterEvaluator::new(&config.wins).with_wilds(&config.wilds).eval(grid, coin),
+        };
+        if let Some(scatter) = config.scatter {
+            let columns = grid.len();
+            let cells = grid.iter().enumerate().flat_map(|(c, col)| col.iter().enumerate().filter(|(_, s)| **s == scatter).map(move |(r, _)| (c, r))).collect::<Vec<_>>();
+            let factor = config.scatter_wins.range(..=cells.len()).next_back().map(|(_, f)| *f).unwrap_or(0);
+            if factor > 0 {
+                gains.push(Gain {
+                    symbol: scatter,
+                    count: cells.len(),
+                    amount: factor as i64 * self.calc_playing_bet(request, round_multiplier),
+                    multi: 1,
+                    columns: Some(columns),
+                    indexes: cells.iter().map(|(c, r)| r * columns + c).collect(),
+                    points: cells.iter().map(|(c, r)| BasePoint { x: *c, y: *r }).collect(),
+                    ..Default::default()
+                });
+            }
+        }
+        if multi > 1 {
+            gains.iter_mut().for_each(|g| {
+                g.amount *= multi as i64;
+                g.multi *= multi;
+            });
+        }
+        gains
+    }
+
+    /// the hold and win goes before the free spins when a grid starts both, the free spins start after the last respin,
+    /// the pick bonus goes before the cascades and the free spins and the grid of the hold and win does not start it
+    fn spin_grid(&mut self, request: &Request, arg: SpinArg, stops: Vec<usize>, grid: Vec<Vec<char>>) -> Result<GameData<HoldInfo, StartInfo>, ServerError> {
+        let config = Arc::clone(&self.config);
+        let max_win = self.calc_max_win(request, arg.round_multiplier);
+        let mut gains = self.evaluate(request, arg.round_multiplier, &grid, 1);
+        let (total, stop) = self.cap(gains.iter().map(|g| g.amount).sum(), max_win);
+
+        let hold = config.hold.as_ref().map(HoldEngine::new).filter(|e| stop.is_none() && e.is_triggered(&grid));
+        let trigger = config.free.as_ref().filter(|_| stop.is_none()).and_then(|f| FreeSpins::new(f).trigger(&grid, total));
+        let (special, next_act, restore, free) = if let Some(engine) = hold {
+            let hold_arg = HoldArg {
+                unit: self.calc_playing_bet(request, arg.round_multiplier),
+                max_win,
+                prev_total: total,
+            };
+            let (hold_gains, info) = engine.start(&mut self.rand.p.base.rand, &grid, hold_arg)?;
+            gains.extend(hold_gains);
+            let restore = Some(StartInfo {
+                grid: Some(grid.clone()),
+                mults: info.mults.clone(),
+                ..Default::default()
+            });
+            let next_act = if info.respins > 0 {
+                ActionKind::RESPIN
+            } else {
+                ActionKind::CLOSE
+            };
+            let free = trigger.filter(|_| info.stop.is_none());
+            (info, next_act, restore, free)
+        } else {
+            let info = HoldInfo {
+                total,
+                stop,
+                ..Default::default()
+            };
+            // the free spins are triggered by the last grid of the cascades
+            let cascade = config.cascade.is_some() && stop.is_none() && !gains.is_empty();
+            let bonus = config.bonus.as_ref().filter(|b| stop.is_none() && b.is_triggered(&grid)).is_some();
+            match trigger {
+                f if bonus => (info, ActionKind::BONUS, None, f),
+                _ if cascade => (info, ActionKind::DROP, None, None),
+                Some(f) => (info, ActionKind::FREE_SPIN, None, Some(f)),
+                None => (info, ActionKind::CLOSE, None, None),
+            }
+        };
+
+        Ok(GameData::Spin(SpinData {
+            id: id::GAME_DATA,
+            balance: arg.balance - arg.stake,
+            credit_type: 100,
+            result: GameResult {
+                total: special.total,
+                stops,
+                holds: empty_holds(&grid),
+                grid,
+                special: Some(special),
+                gains,
+                restore,
+                ..Default::default()
+            },
+            curr_lines: request.line,
+            curr_bet: request.bet,
+            curr_denom: request.denom,
+            curr_reels: request.reels,
+            next_act,
+            category: BASE_CATEGORY,
+            round_id: arg.round_id,
+            round_type: arg.round_type,
+            round_multiplier: arg.round_multiplier,
+            promo: arg.promo,
+            free,
+        }))
+    }
+}
+
+impl SlotMath for ConfigurableSlotMath {
+    type Special = HoldInfo;
+    type Calculator = DefaultBetCalculator;
+    type Restore = StartInfo;
+    type PlayFSM = SlotFSM;
+    type Rand = ConfigurableRandom;
+    type Input = Request;
+    type V = RequestValidator;
+
+    fn join(&self, arg: JoinArg) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        let (result, free) = match self.result.as_ref() {
+            GameData::Initial(v) => (v.result.clone(), v.free.clone()),
+            GameData::Spin(v) | GameData::ReSpin(v) | GameData::FreeSpin(v) | GameData::Drop(v) | GameData::Collect(v) => (v.result.clone(), v.free.clone()),
+            GameData::Bonus(v) => (v.spin.result.clone(), v.spin.free.clone()),
+            GameData::Gamble(v) => (v.spin.result.clone(), v.spin.free.clone()),
+        };
+        let scatter_wins = self.config.scatter.iter().flat_map(|s| {
+            self.config.scatter_wins.iter().map(|(count, factor)| Win {
+                symbol: *s,
+                count: *count,
+                factor: *factor,
+            })
+        });
+        let mut wins = self
+            .config
+            .wins
+            .iter()
+            .flat_map(|p| {
+                p.1.iter().map(|w| Win {
+                    symbol: *p.0,
+                    count: *w.0,
+                    factor: *w.1,
+                })
+            })
+            .chain(scatter_wins)
+            .collect::<Vec<Win>>();
+        wins.sort_by(|a, b| a.symbol.cmp(&b.symbol).then(a.count.cmp(&b.count)).then(a.factor.cmp(&b.factor)));
+
+        Ok(GameData::Initial(InitialData {
+            id: id::GAME_DATA,
+            balance: arg.balance,
+            credit_type: 100,
+            min_bet: 0,
+            max_bet: 0,
+            lines: self.config.lines.clone(),
+            reels: self.config.reels.clone(),
+            wins,
+            category: 0,
+            result,
+            poss_lines: arg.poss_lines,
+            poss_bets: arg.poss_bets,
+            poss_denom: arg.poss_denom,
+            poss_reels: arg.poss_reels,
+            poss_bet_counters: arg.poss_bet_counters,
+            curr_lines: arg.curr_lines,
+            curr_bet: arg.curr_bet,
+            curr_denom: arg.curr_denom,
+            curr_reels: arg.curr_reels,
+            bet_counter: arg.bet_counter,
+            next_act: arg.next_act,
+            round_id: arg.round_id,
+            round_type: arg.round_type,
+            round_multiplier: arg.round_multiplier,
+            promo: arg.promo,
+            free,
+            ..Default::default()
+        }))
+    }
+
+    fn init(&mut self, arg: GameInitArg, actions: &Vec<fugaso_action::Model>) -> Result<(), ServerError> {
+        if actions.is_empty() {
+            return Ok(());
+        }
+        self.gambler.init(actions)?;
+        let action = &actions[actions.len() - 1];
+        if let Some(next) = &action.next_act {
+            let result0: GameResult<Self::Special, Self::Restore> = GameResult::from_action(&actions[0])?;
+            let mut result_on: GameResult<Self::Special, Self::Restore> = GameResult::from_action(action)?;
+            let free = FreeSpins::restore(actions)?;
+
+            result_on.total = result_on.special.as_ref().map(|s| s.total).unwrap_or(result_on.total);
+            if *next == ActionKind::BET {
+                result_on.stops = result0.stops.clone();
+                result_on.grid = result0.grid.clone();
+                result_on.special = result0.special.clone();
+                result_on.holds = result0.holds.clone();
+            }
+            result_on.restore = result0.special.as_ref().filter(|s| !s.held.is_empty()).map(|s| StartInfo {
+                mults: s.mults.clone(),
+                grid: Some(result0.grid.clone()),
+                ..Default::default()
+            });
+
+            let spin_data = SpinData {
+                id: id::GAME_DATA,
+                balance: 0,
+                credit_type: 100,
+                result: result_on,
+                next_act: next.clone(),
+                category: action.reel_combo as usize,
+                curr_lines: arg.curr_lines,
+                curr_bet: arg.curr_bet,
+                curr_denom: arg.curr_denom,
+                curr_reels: arg.curr_reels,
+                round_id: arg.round_id,
+                round_type: arg.round_type,
+                round_multiplier: arg.round_multiplier,
+                promo: arg.promo,
+                free,
+            };
+            match action.act_descr.as_ref() {
+                None => {}
+                Some(a) => match a {
+                    ActionKind::RESPIN => self.result = Arc::new(GameData::ReSpin(spin_data)),
+                    ActionKind::FREE_SPIN => self.result = Arc::new(GameData::FreeSpin(spin_data)),
+                    ActionKind::DROP => self.result = Arc::new(GameData::Drop(spin_data)),
+                    ActionKind::BONUS => match PickBoard::restore(actions)? {
+                        Some(board) => self.result = Arc::new(GameData::Bonus(BonusData::new(spin_data, board))),
+                        None => self.result = Arc::new(GameData::Spin(spin_data)),
+                    },
+                    ActionKind::GAMBLE_PLAY | ActionKind::HALF_COLLECT => match self.gambler.state.clone() {
+                        Some(gamble) => {
+                            self.result = Arc::new(GameData::Gamble(GambleData {
+                                spin: spin_data,
+                                gamble,
+                            }))
+                        }
+                        None => self.result = Arc::new(GameData::Spin(spin_data)),
+                    },
+                    _ => self.result = Arc::new(GameData::Spin(spin_data)),
+                },
+            }
+            self.cascade = actions.iter().filter(|a| a.act_descr == Some(ActionKind::DROP)).count();
+        }
+        Ok(())
+    }
+
+    fn spin(&mut self, request: &Request, arg: SpinArg, _step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        self.check_counter(request)?;
+        let (stops, grid) = self.rand.rand_grid(BASE_CATEGORY, combo)?;
+        self.cascade = 0;
+        self.gambler.reset();
+        self.spin_grid(request, arg, stops, grid)
+    }
+
+    fn free_spin(&mut self, request: &Request, arg: SpinArg, _step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        let config = Arc::clone(&self.config);
+        let free_cfg = config.free.as_ref().ok_or_else(|| err_on!("free_spin is not supported!"))?;
+        let prev = Arc::clone(&self.result);
+        let prev_total = match prev.as_ref() {
+            GameData::Spin(v) | GameData::ReSpin(v) | GameData::FreeSpin(v) | GameData::Drop(v) => v.result.total,
+            GameData::Bonus(v) => v.spin.result.total,
+            _ => return Err(err_on!("Illegal state!")),
+        };
+        let prev_free = prev.free().ok_or_else(|| err_on!("wrong state - free is none!"))?;
+
+        let spins = FreeSpins::new(free_cfg);
+        let category = prev_free.category;
+        let (stops, mut grid) = self.rand.rand_grid(category, combo)?;
+        spins.apply_wilds(prev_free, &mut grid);
+        let gains = self.evaluate(request, arg.round_multiplier, &grid, spins.multiplier(prev_free));
+        let max_win = self.calc_max_win(request, arg.round_multiplier);
+        let (total, stop) = self.cap(prev_total + gains.iter().map(|g| g.amount).sum::<i64>(), max_win);
+
+        let mut free_game = spins.play(prev_free, &grid, total - prev_total);
+        if stop.is_some() {
+            free_game.left = 0;
+        }
+
+        Ok(GameData::FreeSpin(SpinData {
+            id: id::GAME_DATA,
+            balance: arg.balance,
+            credit_type: 100,
+            result: GameResult {
+                total,
+                stops,
+                holds: empty_holds(&grid),
+                grid,
+                gains,
+                special: Some(HoldInfo {
+                    total,
+                    stop,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            curr_lines: request.line,
+            curr_bet: request.bet,
+            curr_denom: request.denom,
+            curr_reels: request.reels,
+            next_act: ActionKind::CLOSE,
+            category,
+            round_id: arg.round_id,
+            round_type: arg.round_type,
+            round_multiplier: arg.round_multiplier,
+            promo: arg.promo,
+            free: Some(free_game),
+        }))
+    }
+
+    fn respin(&mut self, request: &Request, arg: SpinArg, _step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        let config = Arc::clone(&self.config);
+        let engine = HoldEngine::new(config.hold.as_ref().ok_or_else(|| err_on!("respin is not supported!"))?);
+        let prev = Arc::clone(&self.result);
+        let (prev_total, prev_info, prev_restore) = match prev.as_ref() {
+            GameData::Spin(v) | GameData::ReSpin(v) => (v.result.total, v.result.special.as_ref().ok_or_else(|| err_on!("Illegal state!"))?, &v.result.restore),
+            _ => return Err(err_on!("Illegal state!")),
+        };
+
+        let category = config.hold_category;
+        let (stops, mut grid) = self.rand.rand_grid(category, combo)?;
+        let hold_arg = HoldArg {
+            unit: self.calc_playing_bet(request, arg.round_multiplier),
+            max_win: self.calc_max_win(request, arg.round_multiplier),
+            prev_total,
+        };
+        let (gains, info) = engine.respin(&mut self.rand.p.base.rand, prev_info, &grid, hold_arg)?;
+        engine.apply_prev(&mut grid, &info.held);
+        let free = prev.free().cloned().filter(|_| info.stop.is_none());
+        let (next_act, restore, extra_data) = if info.respins > 0 {
+            (ActionKind::RESPIN, prev_restore.clone(), None)
+        } else {
+            (ActionKind::CLOSE, None, prev_restore.clone())
+        };
+
+        Ok(GameData::ReSpin(SpinData {
+            id: id::GAME_DATA,
+            balance: arg.balance,
+            credit_type: 100,
+            result: GameResult {
+                total: info.total,
+                stops,
+                holds: empty_holds(&grid),
+                grid,
+                special: Some(info),
+                gains,
+                restore,
+                extra_data,
+                ..Default::default()
+            },
+            curr_lines: request.line,
+            curr_bet: request.bet,
+            curr_denom: request.denom,
+            curr_reels: request.reels,
+            next_act,
+            category,
+            round_id: arg.round_id,
+            round_type: arg.round_type,
+            round_multiplier: arg.round_multiplier,
+            promo: arg.promo,
+            free,
+        }))
+    }
+
+    /// the winning cells of the previous grid fall out and the strips of the base reels refill the columns,
+    /// the wins of the new grid go by the multiplier of the cascade
+    fn drop(&mut self, request: &Request, arg: SpinArg, _step: &Step, _combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        let config = Arc::clone(&self.config);
+        let engine = CascadeEngine::new(config.cascade.clone().ok_or_else(|| err_on!("drop is not supported!"))?);
+        let prev = Arc::clone(&self.result);
+        let prev_result = match prev.as_ref() {
+            GameData::Spin(v) | GameData::Drop(v) => &v.result,
+            _ => return Err(err_on!("Illegal state!")),
+        };
+        let step = engine
+            .next(&self.rand.p.base, BASE_CATEGORY, self.cascade, &prev_result.stops, &prev_result.grid, &prev_result.gains)?
+            .ok_or_else(|| err_on!("grid of the cascade does not win!"))?;
+        let gains = self.evaluate(request, arg.round_multiplier, &step.grid, step.multiplier);
+        let max_win = self.calc_max_win(request, arg.round_multiplier);
+        let (total, stop) = self.cap(prev_result.total + gains.iter().map(|g| g.amount).sum::<i64>(), max_win);
+
+        let (next_act, free) = if stop.is_none() && !gains.is_empty() {
+            (ActionKind::DROP, None)
+        } else {
+            match config.free.as_ref().filter(|_| stop.is_none()).and_then(|f| FreeSpins::new(f).trigger(&step.grid, total)) {
+                Some(f) => (ActionKind::FREE_SPIN, Some(f)),
+                None => (ActionKind::CLOSE, None),
+            }
+        };
+
+        Ok(GameData::Drop(SpinData {
+            id: id::GAME_DATA,
+            balance: arg.balance,
+            credit_type: 100,
+            result: GameResult {
+                total,
+                stops: step.stops,
+                holds: empty_holds(&step.grid),
+                grid: step.grid,
+                gains,
+                special: Some(HoldInfo {
+                    total,
+                    stop,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            curr_lines: request.line,
+            curr_bet: request.bet,
+            curr_denom: request.denom,
+            curr_reels: request.reels,
+            next_act,
+            category: BASE_CATEGORY,
+            round_id: arg.round_id,
+            round_type: arg.round_type,
+            round_multiplier: arg.round_multiplier,
+            promo: arg.promo,
+            free,
+        }))
+    }
+
+    /// the board is dealt by the first pick, the wins of the board go on top of the total of the spin up to the cap of the round
+    fn bonus(&mut self, request: &Request, arg: SpinArg, _step: &Step, pick: usize) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        let config = Arc::clone(&self.config);
+        let trigger = config.bonus.as_ref().ok_or_else(|| err_on!("bonus is not supported!"))?;
+        let stake = self.calc_playing_bet(request, arg.round_multiplier);
+        let prev = Arc::clone(&self.result);
+        let (spin, mut board) = match prev.as_ref() {
+            GameData::Bonus(v) => (&v.spin, v.board.clone()),
+            GameData::Spin(v) => (v, trigger.pick.deal(&mut self.rand.p.base.rand, stake, v.result.total)),
+            _ => return Err(err_on!("Illegal state!")),
+        };
+        board.pick(pick)?;
+        let max_win = self.calc_max_win(request, arg.round_multiplier);
+        let (total, stop) = self.cap(board.start_total + board.total(), max_win);
+
+        let mut data = board.play(spin, arg);
+        if let GameData::Bonus(v) = &mut data {
+            v.spin.result.total = total;
+            v.spin.result.special = Some(HoldInfo {
+                total,
+                stop,
+                ..Default::default()
+            });
+            if stop.is_some() {
+                v.spin.next_act = ActionKind::CLOSE;
+                v.spin.free = None;
+            }
+        }
+        Ok(data)
+    }
+
+    /// the cards come from the gambler reseeded with the math, see `reseed`
+    fn gamble(&mut self, _request: &Request, arg: SpinArg, choice: GambleChoice, limit: &GambleLimit) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        let prev = Arc::clone(&self.result);
+        let spin = Self::gamble_spin(&self.config, &prev)?;
+        self.gambler.gamble(spin, arg, choice, limit)
+    }
+
+    fn half_collect(&mut self, _request: &Request, arg: SpinArg) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        let prev = Arc::clone(&self.result);
+        let spin = Self::gamble_spin(&self.config, &prev)?;
+        self.gambler.half_collect(spin, arg)
+    }
+
+    /// the drop may be played again for the allowed win, so its cascade is counted here
+    fn post_process(&mut self, kind: ActionKind, mut data: GameData<Self::Special, Self::Restore>) -> Result<Arc<GameData<Self::Special, Self::Restore>>, ServerError> {
+        if let GameData::Drop(_) = &data {
+            self.cascade += 1;
+        }
+        data.set_next_act(kind);
+        self.result = Arc::new(data);
+        Ok(self.result.clone())
+    }
+
+    fn close(&self, next_act: ActionKind) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        match self.result.as_ref() {
+            GameData::Spin(v) => Ok(GameData::Spin(SpinData {
+                next_act,
+                ..v.clone()
+            })),
+            GameData::ReSpin(v) => Ok(GameData::ReSpin(SpinData {
+                next_act,
+                ..v.clone()
+            })),
+            GameData::FreeSpin(v) => Ok(GameData::FreeSpin(SpinData {
+                next_act,
+                ..v.clone()
+            })),
+            GameData::Drop(v) => Ok(GameData::Drop(SpinData {
+                next_act,
+                ..v.clone()
+            })),
+            GameData::Bonus(v) => Ok(GameData::Bonus(BonusData {
+                spin: SpinData {
+                    next_act,
+                    ..v.spin.clone()
+                },
+                ..v.clone()
+            })),
+            GameData::Gamble(v) => Ok(GameData::Gamble(GambleData {
+                spin: SpinData {
+                    next_act,
+                    ..v.spin.clone()
+                },
+                ..v.clone()
+            })),
+            _ => Err(err_on!("Illegal state!")),
+        }
+    }
+
+    fn collect(&self, request: &Request, arg: SpinArg) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        let (category, result, free_game) = match self.result.as_ref() {
+            GameData::Initial(v) => (v.category, v.result.clone(), v.free.clone()),
+            GameData::Spin(v) | GameData::ReSpin(v) | GameData::FreeSpin(v) | GameData::Drop(v) | GameData::Collect(v) => (v.category, v.result.clone(), v.free.clone()),
+            GameData::Bonus(v) => (v.spin.category, v.spin.result.clone(), v.spin.free.clone()),
+            GameData::Gamble(v) => (v.spin.category, v.spin.result.clone(), v.spin.free.clone()),
+        };
+        info!("collect: {}", result.total);
+        Ok(GameData::Collect(SpinData {
+            id: id::GAME_DATA,
+            balance: arg.balance + result.total,
+            credit_type: 100,
+            result,
+            curr_lines: request.line,
+            curr_bet: request.bet,
+            curr_denom: request.denom,
+            curr_reels: request.reels,
+            next_act: arg.next_act,
+            category,
+            round_id: arg.round_id,
+            round_type: arg.round_type,
+            round_multiplier: arg.round_multiplier,
+            promo: arg.promo,
+            free: free_game,
+        }))
+    }
+
+    fn create_fsm(&self, game_name: &str) -> Self::PlayFSM {
+        match self.config.fsm.as_ref() {
+            Some(fsm) => SlotFSM::default(game_name).with_definition(fsm.clone()),
+            None => SlotFSM::default(game_name),
+        }
+    }
+
+    fn settings(&self) -> MathSettings {
+        self.config.settings()
+    }
+
+    fn set_rand(&mut self, rand: Self::Rand) {
+        self.rand = rand;
+    }
+
+    fn reseed(&mut self, seed: Seed) {
+        self.rand.reseed(seed);
+        self.gambler.reseed(seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config_validator::IssueKind;
+    use crate::configurable::{ConfigurableRandom, ConfigurableSlotMath, GameDefinition};
+    use crate::gamble::{GambleChoice, GambleLimit};
+    use crate::hold::HoldInfo;
+    use crate::math::{Request, SlotMath, SpinArg, Step};
+    use crate::protocol::{GameData, Promo, StartInfo};
+    use crate::rng::{DefaultSource, RngSource};
+    use fugaso_data::fugaso_action::ActionKind;
+    use fugaso_data::fugaso_round::RoundDetail;
+    use std::sync::Arc;
+
+    fn holds(data: &GameData<HoldInfo, StartInfo>) -> &Vec<i32> {
+        match data {
+            GameData::Spin(v) | GameData::ReSpin(v) | GameData::FreeSpin(v) | GameData::Drop(v) => &v.result.holds,
+            _ => panic!("spin data is expected!"),
+        }
+    }
+
+    #[test]
+    pub fn test_configurable_math() {
+        let json = r#"{
+            "cols": 3,
+            "rows": 3,
+            "reels": [["AABBS", "AABBS", "AWBBS"], ["AABBS", "AABBS", "AWBBS"]],
+            "evaluator": "LINES",
+            "lines": ["---", "^^^", "___"],
+            "wins": {"A": {"3": 5}, "B": {"3": 10}},
+            "wilds": ["W"],
+            "scatter": "S",
+            "scatterWins": {"3": 2},
+            "free": {"scatter": "S", "triggers": {"3": 5}, "categories": [1], "multipliers": [2]},
+            "maxWin": 15
+        }"#;
+        let definition: GameDefinition = serde_json::from_str(json).unwrap();
+        assert!(definition.validate().is_valid(), "{}", definition.validate());
+        let mut math = ConfigurableSlotMath::new(json).unwrap();
+        math.set_rand(ConfigurableRandom::with_rand(Arc::clone(&math.config), DefaultSource::from_seed(1)));
+        let request = Request {
+            bet: 1,
+            line: 3,
+            denom: 1,
+            bet_counter: 1,
+            ..Default::default()
+        };
+        let arg = SpinArg {
+            balance: 1_000,
+            round_id: 1,
+            round_type: RoundDetail::SIMPLE,
+            round_multiplier: 1,
+            next_act: ActionKind::BET,
+            promo: Promo::default(),
+            stake: 3,
+        };
+        assert_eq!(math.settings().lines, vec![3]);
+
+        // the wild completes the middle line
+        let result = math.spin(&request, arg.clone(), &Step::default(), Some(vec![0, 0, 0])).unwrap();
+        assert_eq!(result.total(), 20);
+        assert_eq!(result.grid_on()[2], vec!['A', 'W', 'B']);
+        assert!(result.free().is_none());
+        assert_eq!(holds(&result).len(), 9);
+
+        // the scatters break the top line, pay the playing bet and start the free spins
+        let result = math.spin(&request, arg.clone(), &Step::default(), Some(vec![4, 4, 4])).unwrap();
+        assert_eq!(result.total(), 16);
+        let free = result.free().cloned().unwrap();
+        assert_eq!((free.left, free.category, free.total_win), (5, 1, 16));
+        math.post_process(ActionKind::FREE_SPIN, result).unwrap();
+
+        // the multiplier of the free spins doubles the wins and the cap ends them
+        let result = math.free_spin(&request, arg.clone(), &Step::default(), Some(vec![0, 0, 0])).unwrap();
+        assert_eq!(result.total(), 45);
+        assert_eq!(holds(&result).len(), 9);
+        let free = result.free().cloned().unwrap();
+        assert_eq!((free.left, free.done, free.total_win), (0, 1, 45));
+
+        let broken = json.replace("\"___\"", "\"___V\"").replace("\"categories\": [1]", "\"categories\": [2]");
+        let issues = serde_json::from_str::<GameDefinition>(&broken).unwrap().validate().issues.into_iter().map(|i| i.kind).collect::<Vec<_>>();
+        assert!(issues.contains(&IssueKind::LineLength));
+        assert!(issues.contains(&IssueKind::ReelCategories));
+        assert!(ConfigurableSlotMath::new(&broken).is_err());
+    }
+
+    #[test]
+    pub fn test_configurable_hold_free() {
+        let json = r#"{
+            "cols": 3,
+            "rows": 3,
+            "reels": [["KSAB", "KSAB", "KSAB"], ["AABB", "AABB", "AABB"], ["AABB", "AABB", "AABB"]],
+            "evaluator": "LINES",
+            "lines": ["---", "^^^", "___"],
+            "wins": {"A": {"3": 5}, "B": {"3": 10}},
+            "scatter": "S",
+            "free": {"scatter": "S", "triggers": {"3": 5}, "categories": [1], "multipliers": [1]},
+            "hold": {"symbols": {"K": {"COIN": {"dist": {"100": 2}}}}, "respins": 1, "trigger": 3},
+            "holdCategory": 2
+        }"#;
+        let mut math = ConfigurableSlotMath::new(json).unwrap();
+        let request = Request {
+            bet: 1,
+            line: 3,
+            denom: 1,
+            bet_counter: 1,
+            ..Default::default()
+        };
+        let arg = SpinArg {
+            balance: 1_000,
+            round_id: 1,
+            round_type: RoundDetail::SIMPLE,
+            round_multiplier: 1,
+            next_act: ActionKind::BET,
+            promo: Promo::default(),
+            stake: 3,
+        };
+
+        // the grid starts both, the free spins wait for the respins
+        let result = math.spin(&request, arg.clone(), &Step::default(), Some(vec![0, 0, 0])).unwrap();
+        assert!(result.has_respin());
+        assert_eq!(result.free().map(|f| f.left), Some(5));
+        assert_eq!(holds(&result).len(), 9);
+        math.post_process(ActionKind::RESPIN, result).unwrap();
+
+        let result = math.respin(&request, arg.clone(), &Step::default(), Some(vec![0, 0, 0])).unwrap();
+        assert!(!result.has_respin());
+        assert_eq!(result.free().map(|f| f.left), Some(5));
+        assert_eq!(holds(&result).len(), 9);
+        math.post_process(ActionKind::FREE_SPIN, result).unwrap();
+
+        let result = math.free_spin(&request, arg.clone(), &Step::default(), Some(vec![0, 0, 0])).unwrap();
+        assert_eq!(result.free().map(|f| (f.left, f.done)), Some((4, 1)));
+    }
+
+    #[test]
+    pub fn test_configurable_cascade() {
+        let json = r#"{
+            "cols": 3,
+            "rows": 3,
+            "reels": [["AAAA", "AAAA", "AAAA"]],
+            "evaluator": "LINES",
+            "lines": ["^^^"],
+            "wins": {"A": {"3": 2}},
+            "maxWin": 10,
+            "cascade": [1, 2, 3]
+        }"#;
+        let mut math = ConfigurableSlotMath::new(json).unwrap();
+        let request = Request {
+            bet: 1,
+            line: 1,
+            denom: 1,
+            bet_counter: 1,
+            ..Default::default()
+        };
+        let arg = SpinArg {
+            balance: 1_000,
+            round_id: 1,
+            round_type: RoundDetail::SIMPLE,
+            round_multiplier: 1,
+            next_act: ActionKind::BET,
+            promo: Promo::default(),
+            stake: 1,
+        };
+
+        let result = math.spin(&request, arg.clone(), &Step::default(), Some(vec![0, 0, 0])).unwrap();
+        assert!(result.has_drop());
+        assert_eq!(result.total(), 2);
+        math.post_process(ActionKind::DROP, result).unwrap();
+
+        // the top row falls out and the strips go on from the stops before the window
+        let result = math.drop(&request, arg.clone(), &Step::default(), None).unwrap();
+        assert!(matches!(result, GameData::Drop(_)));
+        assert!(result.has_drop());
+        assert_eq!(result.total(), 6);
+        assert_eq!(holds(&result).len(), 9);
+        match &result {
+            GameData::Drop(v) => assert_eq!(v.result.stops, vec![3, 3, 3]),
+            _ => panic!("drop data is expected!"),
+        }
+        math.post_process(ActionKind::DROP, result).unwrap();
+
+        // the third multiplier pays 6 and the cap ends the cascades
+        let result = math.drop(&request, arg.clone(), &Step::default(), None).unwrap();
+        assert!(!result.has_drop());
+        assert_eq!(result.total(), 10);
+        math.post_process(ActionKind::COLLECT, result).unwrap();
+        assert_eq!(math.cascade, 2);
+
+        let broken = json.replace("[1, 2, 3]", "[1, 0]");
+        let issues = serde_json::from_str::<GameDefinition>(&broken).unwrap().validate().issues.into_iter().map(|i| i.kind).collect::<Vec<_>>();
+        assert_eq!(issues, vec![IssueKind::IllegalMultiplier]);
+    }
+
+    #[test]
+    pub fn test_configurable_pick_bonus() {
+        let json = r#"{
+            "cols": 3,
+            "rows": 3,
+            "reels": [["KKKK", "KKKK", "KKKK"]],
+            "evaluator": "LINES",
+            "lines": ["^^^"],
+            "wins": {"K": {"3": 1}},
+            "bonus": {"symbol": "K", "trigger": 3, "prizes": [{"kind": "WIN", "value": 5}, {"kind": "WIN", "value": 5}, {"kind": "WIN", "value": 5}], "picks": 2}
+        }"#;
+        let mut math = ConfigurableSlotMath::new(json).unwrap();
+        let request = Request {
+            bet: 1,
+            line: 1,
+            denom: 1,
+            bet_counter: 1,
+            ..Default::default()
+        };
+        let arg = SpinArg {
+            balance: 1_000,
+            round_id: 1,
+            round_type: RoundDetail::SIMPLE,
+            round_multiplier: 1,
+            next_act: ActionKind::BET,
+            promo: Promo::default(),
+            stake: 1,
+        };
+
+        let result = math.spin(&request, arg.clone(), &Step::default(), None).unwrap();
+        assert!(result.has_bonus());
+        assert_eq!(result.total(), 1);
+        math.post_process(ActionKind::BONUS, result).unwrap();
+
+        // the first pick deals the board, the wins go on top of the spin
+        let result = math.bonus(&request, arg.clone(), &Step::default(), 0).unwrap();
+        assert!(matches!(result, GameData::Bonus(_)));
+        assert!(result.has_bonus());
+        assert_eq!(result.total(), 6);
+        math.post_process(ActionKind::BONUS, result).unwrap();
+        assert!(math.bonus(&request, arg.clone(), &Step::default(), 0).is_err());
+
+        let result = math.bonus(&request, arg.clone(), &Step::default(), 2).unwrap();
+        assert!(!result.has_bonus());
+        assert_eq!(result.total(), 11);
+        match &result {
+            GameData::Bonus(v) => assert!(v.bonus.finished),
+            _ => panic!("bonus data is expected!"),
+        }
+
+        // the cap of the round ends the board before its last pick
+        let capped = json.replace("\"picks\": 2", "\"picks\": 3").replace("\"wins\"", "\"maxWin\": 8, \"wins\"");
+        let mut math = ConfigurableSlotMath::new(&capped).unwrap();
+        let result = math.spin(&request, arg.clone(), &Step::default(), None).unwrap();
+        math.post_process(ActionKind::BONUS, result).unwrap();
+        let result = math.bonus(&request, arg.clone(), &Step::default(), 0).unwrap();
+        math.post_process(ActionKind::BONUS, result).unwrap();
+        let result = math.bonus(&request, arg.clone(), &Step::default(), 1).unwrap();
+        assert!(!result.has_bonus());
+        assert_eq!(result.total(), 8);
+
+        let broken = json.replace("\"trigger\": 3", "\"trigger\": 0");
+        let issues = serde_json::from_str::<GameDefinition>(&broken).unwrap().validate().issues.into_iter().map(|i| i.kind).collect::<Vec<_>>();
+        assert_eq!(issues, vec![IssueKind::IllegalCount]);
+    }
+
+    #[test]
+    pub fn test_configurable_gamble() {
+        let json = r#"{
+            "cols": 3,
+            "rows": 3,
+            "reels": [["AAAA", "AAAA", "AAAA"]],
+            "evaluator": "LINES",
+            "lines": ["^^^"],
+            "wins": {"A": {"3": 2}},
+            "gamble": true
+        }"#;
+        let mut math = ConfigurableSlotMath::new(json).unwrap();
+        let request = Request {
+            bet: 1,
+            line: 1,
+            denom: 1,
+            bet_counter: 1,
+            ..Default::default()
+        };
+        let arg = SpinArg {
+            balance: 1_000,
+            round_id: 1,
+            round_type: RoundDetail::SIMPLE,
+            round_multiplier: 1,
+            next_act: ActionKind::COLLECT,
+            promo: Promo::default(),
+            stake: 1,
+        };
+
+        let result = math.spin(&request, arg.clone(), &Step::default(), None).unwrap();
+        assert_eq!(result.total(), 2);
+        math.post_process(ActionKind::COLLECT, result).unwrap();
+
+        // the banked half stays with the round whatever the card is
+        let result = math.half_collect(&request, arg.clone()).unwrap();
+        assert_eq!(result.total(), 2);
+        math.post_process(ActionKind::COLLECT, result).unwrap();
+        assert!(math.half_collect(&request, arg.clone()).is_err());
+        let result = math.gamble(&request, arg.clone(), GambleChoice::Red, &GambleLimit::default()).unwrap();
+        let gamble = match &result {
+            GameData::Gamble(v) => v.gamble.clone(),
+            _ => panic!("gamble data is expected!"),
+        };
+        let expected = if GambleChoice::Red.wins(&gamble.history[0]) {
+            3
+        } else {
+            1
+        };
+        assert_eq!((result.total(), gamble.banked), (expected, 1));
+
+        // the next round starts without the gamble
+        math.post_process(ActionKind::COLLECT, result).unwrap();
+        let result = math.spin(&request, arg.clone(), &Step::default(), None).unwrap();
+        math.post_process(ActionKind::COLLECT, result).unwrap();
+        assert!(math.gambler.state.is_none());
+
+        let mut math = ConfigurableSlotMath::new(&json.replace("\"gamble\": true", "\"gamble\": false")).unwrap();
+        let result = math.spin(&request, arg.clone(), &Step::default(), None).unwrap();
+        math.post_process(ActionKind::COLLECT, result).unwrap();
+        assert!(math.gamble(&request, arg.clone(), GambleChoice::Red, &GambleLimit::default()).is_err());
+    }
+}
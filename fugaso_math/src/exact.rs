@@ -0,0 +1,247 @@
+use crate::lines::{LineEvaluator, LineState, PayDirection};
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+
+/// visible symbols of one column for a stop with its weight
+#[derive(Debug, Clone)]
+pub struct ReelWindow {
+    pub weight: u64,
+    pub symbols: Vec<char>,
+}
+
+/// windows of a reel strip, every stop has the same weight like in `BaseRandom::rand_cols`
+pub fn strip_windows(reel: &Vec<char>, rows: usize) -> Vec<ReelWindow> {
+    (0..reel.len())
+        .map(|s| ReelWindow {
+            weight: 1,
+            symbols: (0..rows).map(|r| reel[(s + r) % reel.len()]).collect(),
+        })
+        .collect()
+}
+
+/// windows of a weighted column of `ReelDist`, the keys are cumulative bounds like in `GroupRandom::rand_cols_group`
+pub fn dist_windows(dist: &BTreeMap<i32, Vec<char>>) -> Result<Vec<ReelWindow>, ServerError> {
+    let mut prev = 0;
+    dist.iter()
+        .map(|(bound, symbols)| {
+            if *bound <= prev {
+                return Err(err_on!(format!("illegal weight bound {bound}!")));
+            }
+            let weight = (*bound - prev) as u64;
+            prev = *bound;
+            Ok(ReelWindow {
+                weight,
+                symbols: symbols.clone(),
+            })
+        })
+        .collect()
+}
+
+/// identical windows of a column give identical lines, so they are joined to shrink the enumeration
+fn merge_windows(windows: &Vec<ReelWindow>) -> Vec<ReelWindow> {
+    let mut merged: BTreeMap<Vec<char>, u64> = BTreeMap::new();
+    for w in windows {
+        *merged.entry(w.symbols.clone()).or_insert(0) += w.weight;
+    }
+    merged
+        .into_iter()
+        .map(|(symbols, weight)| ReelWindow {
+            weight,
+            symbols,
+        })
+        .collect()
+}
+
+/// every broken line without a win is the same state
+fn dead() -> LineState {
+    LineState {
+        symbol: ' ',
+        count: 0,
+        alive: false,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolHit {
+    pub symbol: char,
+    pub count: usize,
+    pub factor: i32,
+    /// expected number of such line wins per spin
+    pub frequency: f64,
+    pub rtp: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExactReport {
+    pub combinations: u128,
+    pub total_weight: f64,
+    pub stake: f64,
+    pub rtp: f64,
+    pub hit_frequency: f64,
+    pub hits: Vec<SymbolHit>,
+}
+
+impl Display for ExactReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "combinations:  {}", self.combinations)?;
+        writeln!(f, "total weight:  {}", self.total_weight)?;
+        writeln!(f, "stake:         {}", self.stake)?;
+        writeln!(f, "line rtp:      {:.6}%", self.rtp * 100.0)?;
+        write!(f, "hit frequency: {:.6}%", self.hit_frequency * 100.0)?;
+        for h in self.hits.iter() {
+            write!(f, "\n  {} x{} factor {:>6} frequency {:.8} rtp {:.6}%", h.symbol, h.count, h.factor, h.frequency, h.rtp * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// the lines are stepped by the left to right rules of `evaluator` with its wilds and breakers
+pub struct ExactCalculator<'a> {
+    pub evaluator: LineEvaluator<'a>,
+}
+
+impl<'a> ExactCalculator<'a> {
+    pub fn new(evaluator: LineEvaluator<'a>) -> Self {
+        Self {
+            evaluator,
+        }
+    }
+
+    fn factor(&self, state: &LineState) -> i32 {
+        self.evaluator.factor(state.symbol, state.count)
+    }
+
+    fn step(&self, state: &LineState, ch: char) -> LineState {
+        self.evaluator.step(state, ch)
+    }
+
+    /// weights of all the stop combinations are summed up column by column for the states of the lines,
+    /// so the result is exact without visiting every combination, `stake` is the total bet in line bets
+    pub fn calc(&self, columns: &Vec<Vec<ReelWindow>>, stake: f64) -> Result<ExactReport, ServerError> {
+        if columns.is_empty() || columns.iter().any(|c| c.is_empty()) {
+            return Err(err_on!("columns are empty!"));
+        }
+        if self.evaluator.direction != PayDirection::LeftToRight || !self.evaluator.wild_mults.is_empty() {
+            return Err(err_on!("only the left to right lines without the wild multipliers are calculated!"));
+        }
+        if let Some(l) = self.evaluator.lines.iter().find(|l| l.len() != columns.len()) {
+            return Err(err_on!(format!("line {l:?} does not match {} columns!", columns.len())));
+        }
+        if let Some(l) = self.evaluator.lines.iter().find(|l| l.iter().enumerate().any(|(c, r)| columns[c].iter().any(|w| *r >= w.symbols.len()))) {
+            return Err(err_on!(format!("line {l:?} is out of rows!")));
+        }
+        let combinations = columns.iter().map(|c| c.len() as u128).product::<u128>();
+        let columns = columns.iter().map(merge_windows).collect::<Vec<_>>();
+
+        let mut states: HashMap<Vec<LineState>, u128> = HashMap::new();
+        for w in columns[0].iter() {
+            let state = self.evaluator.lines.iter().map(|l| self.step(&LineState::start(w.symbols[l[0]]), w.symbols[l[0]])).collect::<Vec<_>>();
+            *states.entry(state).or_insert(0) += w.weight as u128;
+        }
+        for c in 1..columns.len() {
+            let mut next: HashMap<Vec<LineState>, u128> = HashMap::new();
+            for (state, weight) in states.iter() {
+                if state.iter().all(|s| !s.alive) {
+                    let sum = columns[c].iter().map(|w| w.weight as u128).sum::<u128>();
+                    *next.entry(state.clone()).or_insert(0) += weight * sum;
+                    continue;
+                }
+                for w in columns[c].iter() {
+                    let state_on = state
+                        .iter()
+                        .zip(self.evaluator.lines.iter())
+                        .map(|(s, l)| {
+                            let s = self.step(s, w.symbols[l[c]]);
+                            if !s.alive && self.factor(&s) == 0 {
+                                dead()
+                            } else {
+                                s
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    *next.entry(state_on).or_insert(0) += weight * w.weight as u128;
+                }
+            }
+            states = next;
+        }
+
+        let mut hits: BTreeMap<(char, usize), (i32, u128)> = BTreeMap::new();
+        let mut hit_weight = 0u128;
+        let mut total_weight = 0u128;
+        for (state, weight) in states.iter() {
+            total_weight += weight;
+            let mut is_hit = false;
+            for s in state.iter() {
+                let factor = self.factor(s);
+                if factor > 0 {
+                    is_hit = true;
+                    hits.entry((s.symbol, s.count)).or_insert((factor, 0)).1 += weight;
+                }
+            }
+            if is_hit {
+                hit_weight += weight;
+            }
+        }
+
+        let total = total_weight as f64;
+        let hits = hits
+            .into_iter()
+            .map(|((symbol, count), (factor, weight))| {
+                let frequency = weight as f64 / total;
+                SymbolHit {
+                    symbol,
+                    count,
+                    factor,
+                    frequency,
+                    rtp: frequency * factor as f64 / stake,
+                }
+            })
+            .collect::<Vec<_>>();
+        Ok(ExactReport {
+            combinations,
+            total_weight: total,
+            stake,
+            rtp: hits.iter().map(|h| h.rtp).sum(),
+            hit_frequency: hit_weight as f64 / total,
+            hits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exact::{ExactCalculator, ReelWindow};
+    use crate::lines::{LineEvaluator, PayDirection};
+    use std::collections::HashMap;
+
+    #[test]
+    pub fn test_exact_lines() {
+        let lines = vec![vec![0, 0, 0]];
+        let wins = HashMap::from([('A', HashMap::from([(3, 10)])), ('W', HashMap::from([(3, 100)]))]);
+        let column = vec![
+            ReelWindow {
+                weight: 1,
+                symbols: vec!['A'],
+            },
+            ReelWindow {
+                weight: 2,
+                symbols: vec!['B'],
+            },
+            ReelWindow {
+                weight: 1,
+                symbols: vec!['W'],
+            },
+        ];
+        let evaluator = LineEvaluator::new(&lines, &wins).with_wilds(&['W']);
+        let report = ExactCalculator::new(evaluator.clone()).calc(&vec![column.clone(), column.clone(), column], 1.0).expect("error calc");
+        assert_eq!(report.total_weight, 64.0);
+        // 7 of 8 A/W lines pay A, WWW pays W
+        assert!((report.rtp - (7.0 * 10.0 + 100.0) / 64.0).abs() < 1e-12);
+        assert!(ExactCalculator::new(evaluator.with_direction(PayDirection::BothWays)).calc(&vec![vec![]], 1.0).is_err());
+    }
+}
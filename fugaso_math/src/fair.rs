@@ -0,0 +1,315 @@
+use crate::config::sha256_hex;
+use crate::rng::{ChaChaSource, RngSource};
+use essential_core::digest::calc_hmac_sha256;
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use fugaso_data::{fugaso_fair_seed, fugaso_round};
+use sea_orm::prelude::DateTime;
+use serde::{Deserialize, Serialize};
+
+pub const CLIENT_SEED_LEN: usize = 64;
+
+/// commitment of the server seed, it is the plain SHA-256 of the seed like `config::config_hash`
+pub fn server_hash(server_seed: &str) -> String {
+    sha256_hex(server_seed)
+}
+
+/// key of the round of the nonce, it is the whole `HMAC-SHA256(server_seed, client_seed:nonce)` with the seeds as UTF-8 and the nonce
+/// in decimal. The seeded action `i` of the round draws from `ChaCha20Rng::from_seed(key)` of rand_chacha with `set_stream(i)`,
+/// a number of `[0, n)` is the first `next_u64` below `u64::MAX - u64::MAX % n` taken modulo `n`, see `rng::ChaChaSource`
+pub fn fair_key(server_seed: &str, client_seed: &str, nonce: u64) -> Result<[u8; 32], ServerError> {
+    let digest = calc_hmac_sha256(server_seed, &format!("{client_seed}:{nonce}")).map_err(|e| err_on!(e))?;
+    let mut key = [0u8; 32];
+    if digest.len() != 2 * key.len() {
+        return Err(err_on!("digest is not 32 bytes!"));
+    }
+    for (i, b) in key.iter_mut().enumerate() {
+        *b = digest.get(2 * i..2 * i + 2).and_then(|h| u8::from_str_radix(h, 16).ok()).ok_or_else(|| err_on!("digest is not hex!"))?;
+    }
+    Ok(key)
+}
+
+/// seed of the round of the key which is stored with the round, it is the first 8 bytes of the key, the draws take the whole key
+pub fn key_seed(key: &[u8; 32]) -> u64 {
+    let mut head = [0u8; 8];
+    head.copy_from_slice(&key[..8]);
+    u64::from_be_bytes(head)
+}
+
+/// the client seed is chosen by the player, it is printable without the separator of the nonce
+pub fn check_client_seed(client_seed: &str) -> Result<(), ServerError> {
+    if client_seed.is_empty() || client_seed.len() > CLIENT_SEED_LEN {
+        return Err(err_on!(format!("client seed must have 1 to {CLIENT_SEED_LEN} chars!")));
+    }
+    if !client_seed.chars().all(|c| c.is_ascii_graphic() && c != ':') {
+        return Err(err_on!("illegal char of client seed!"));
+    }
+    Ok(())
+}
+
+fn random_hex(rand: &mut ChaChaSource, words: usize) -> String {
+    (0..words).map(|_| format!("{:016x}", rand.next_u64())).collect()
+}
+
+/// the id of a server seed is random, so the seeds of the sessions do not share it
+fn random_id(rand: &mut ChaChaSource) -> i64 {
+    (rand.next_u64() >> 1) as i64
+}
+
+/// what the player sees before the rounds of the server seed, `nonce` is the nonce of the next round
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FairCommitment {
+    pub seed_id: i64,
+    pub server_hash: String,
+    pub client_seed: String,
+    pub nonce: u64,
+}
+
+/// the server seed after the rotation, the rounds of the nonces `0..nonce` were played with it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FairReveal {
+    pub seed_id: i64,
+    pub server_seed: String,
+    pub server_hash: String,
+    pub client_seed: String,
+    pub nonce: u64,
+}
+
+impl FairReveal {
+    /// the revealed seed is the committed one
+    pub fn verify(&self) -> bool {
+        server_hash(&self.server_seed) == self.server_hash
+    }
+
+    pub fn round_key(&self, nonce: u64) -> Result<[u8; 32], ServerError> {
+        if nonce >= self.nonce {
+            return Err(err_on!(format!("nonce {nonce} is not played!")));
+        }
+        fair_key(&self.server_seed, &self.client_seed, nonce)
+    }
+}
+
+/// seeds of a round of the provably fair mode, the round keeps the commitment and the server seed is taken from
+/// `fugaso_fair_seed` when the seed is retired, after the rotation or the end of the session which played it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FairRound {
+    pub seed_id: i64,
+    pub server_seed: String,
+    pub server_hash: String,
+    pub client_seed: String,
+    pub nonce: u64,
+}
+
+impl FairRound {
+    /// seeds of the stored round with its server seed, none for a round of the other modes or for the seed of another round
+    pub fn stored(round: &fugaso_round::Model, seed: &fugaso_fair_seed::Model) -> Option<Self> {
+        if round.seed_id != Some(seed.id) || round.fair_hash.as_ref() != Some(&seed.server_hash) {
+            return None;
+        }
+        Some(Self {
+            seed_id: seed.id,
+            server_seed: seed.server_seed.clone(),
+            server_hash: seed.server_hash.clone(),
+            client_seed: round.client_seed.clone()?,
+            nonce: round.nonce? as u64,
+        })
+    }
+
+    /// the server seed is the committed one
+    pub fn verify(&self) -> bool {
+        server_hash(&self.server_seed) == self.server_hash
+    }
+
+    pub fn key(&self) -> Result<[u8; 32], ServerError> {
+        fair_key(&self.server_seed, &self.client_seed, self.nonce)
+    }
+}
+
+/// seeds of the provably fair mode of a session, the server seed is kept secret until `rotate` replaces it,
+/// it is stored apart from the rounds and the seed of an ended session is revealed like the replaced one
+pub struct FairSeeds {
+    rand: ChaChaSource,
+    seed_id: i64,
+    server_seed: String,
+    server_hash: String,
+    client_seed: String,
+    nonce: u64,
+}
+
+impl FairSeeds {
+    /// a random client seed is used until the player sets one
+    pub fn new(client_seed: Option<String>) -> Result<Self, ServerError> {
+        let mut rand = ChaChaSource::from_entropy();
+        let client_seed = match client_seed {
+            Some(c) => c,
+            None => random_hex(&mut rand, 1),
+        };
+        check_client_seed(&client_seed)?;
+        let server_seed = random_hex(&mut rand, 4);
+        Ok(Self {
+            seed_id: random_id(&mut rand),
+            server_hash: server_hash(&server_seed),
+            rand,
+            server_seed,
+            client_seed,
+            nonce: 0,
+        })
+    }
+
+    pub fn commitment(&self) -> FairCommitment {
+        FairCommitment {
+            seed_id: self.seed_id,
+            server_hash: self.server_hash.clone(),
+            client_seed: self.client_seed.clone(),
+            nonce: self.nonce,
+        }
+    }
+
+    pub fn seed_id(&self) -> i64 {
+        self.seed_id
+    }
+
+    /// the current server seed to store, it is not revealed yet
+    pub fn seed_model(&self, user_id: i64, game_id: i64, now: DateTime) -> fugaso_fair_seed::Model {
+        fugaso_fair_seed::Model {
+            id: self.seed_id,
+            user_id,
+            game_id,
+            server_seed: self.server_seed.clone(),
+            server_hash: self.server_hash.clone(),
+            timestamp_open: now,
+            revealed: None,
+        }
+    }
+
+    /// seeds of the next round
+    pub fn next_round(&mut self) -> FairRound {
+        let round = FairRound {
+            seed_id: self.seed_id,
+            server_seed: self.server_seed.clone(),
+            server_hash: self.server_hash.clone(),
+            client_seed: self.client_seed.clone(),
+            nonce: self.nonce,
+        };
+        self.nonce += 1;
+        round
+    }
+
+    /// a new server seed is committed with the new client seed or the current one and the nonce starts from 0,
+    /// the replaced server seed is revealed
+    pub fn rotate(&mut self, client_seed: Option<String>) -> Result<FairReveal, ServerError> {
+        if let Some(c) = client_seed.as_ref() {
+            check_client_seed(c)?;
+        }
+        let server_seed = random_hex(&mut self.rand, 4);
+        let seed_id = random_id(&mut self.rand);
+        let reveal = FairReveal {
+            seed_id: std::mem::replace(&mut self.seed_id, seed_id),
+            server_seed: std::mem::replace(&mut self.server_seed, server_seed),
+            server_hash: std::mem::replace(&mut self.server_hash, server_hash(&self.server_seed)),
+            client_seed: match client_seed {
+                Some(c) => std::mem::replace(&mut self.client_seed, c),
+                None => self.client_seed.clone(),
+            },
+            nonce: std::mem::replace(&mut self.nonce, 0),
+        };
+        Ok(reveal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fair::{FairRound, FairSeeds};
+    use crate::seed::{stored_seed, RoundRecord, Seed};
+    use essential_core::digest::calc_hmac_sha256;
+    use fugaso_data::fugaso_round;
+    use sea_orm::prelude::DateTime;
+
+    #[test]
+    fn test_fair_seeds() {
+        let mut fair = FairSeeds::new(Some("lucky".to_string())).unwrap();
+        let commitment = fair.commitment();
+        let stored = fair.seed_model(1, 1, DateTime::default());
+        assert_eq!((stored.id, &stored.server_hash, stored.revealed), (commitment.seed_id, &commitment.server_hash, None));
+        assert_eq!((commitment.seed_id, commitment.client_seed.as_str(), commitment.nonce), (fair.seed_id(), "lucky", 0));
+        let rounds = (0..3).map(|_| fair.next_round()).collect::<Vec<_>>();
+        assert_eq!(rounds.iter().map(|r| r.nonce).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert!(rounds.iter().all(|r| r.verify()));
+        let keys = rounds.iter().map(|r| r.key().unwrap()).collect::<Vec<_>>();
+        assert_ne!(keys[0], keys[1]);
+        // the key is the whole HMAC of the seeds
+        let digest = calc_hmac_sha256(&rounds[1].server_seed, "lucky:1").unwrap();
+        assert_eq!(keys[1].iter().map(|b| format!("{b:02x}")).collect::<String>(), digest.to_lowercase());
+        assert!(FairSeeds::new(Some("a:b".to_string())).is_err());
+        assert!(fair.rotate(Some(String::new())).is_err());
+
+        // the rotation reveals the committed seed, it gives the keys of the played nonces
+        let reveal = fair.rotate(Some("other".to_string())).unwrap();
+        assert!(reveal.verify());
+        assert_eq!((reveal.seed_id, &reveal.server_hash), (commitment.seed_id, &commitment.server_hash));
+        assert_eq!((reveal.client_seed.as_str(), reveal.nonce), ("lucky", 3));
+        for (nonce, key) in keys.iter().enumerate() {
+            assert_eq!(reveal.round_key(nonce as u64).unwrap(), *key);
+        }
+        assert!(reveal.round_key(3).is_err());
+        let next = fair.commitment();
+        assert_ne!((next.seed_id, &next.server_hash), (commitment.seed_id, &commitment.server_hash));
+        assert_eq!((next.client_seed.as_str(), next.nonce), ("other", 0));
+
+        // the round keeps the commitment, the stored server seed keys its actions
+        let seed = Seed::Fair {
+            key: keys[1],
+            stream: 0,
+        };
+        let round = fugaso_round::Model {
+            id: 1,
+            common_id: Some(1),
+            seed: Some(seed.head() as i64),
+            fair_hash: Some(reveal.server_hash.clone()),
+            client_seed: Some(reveal.client_seed.clone()),
+            nonce: Some(1),
+            seed_id: Some(reveal.seed_id),
+            ..Default::default()
+        };
+        assert_eq!(FairRound::stored(&round, &stored), Some(rounds[1].clone()));
+        assert_eq!(stored_seed(&round, Some(&stored)).unwrap(), Some(seed));
+        assert!(stored_seed(&round, None).is_err());
+        let other = fair.seed_model(1, 1, DateTime::default());
+        assert_eq!(FairRound::stored(&round, &other), None);
+        assert_eq!(
+            seed.action(2),
+            Seed::Fair {
+                key: keys[1],
+                stream: 2,
+            }
+        );
+        let record = RoundRecord::new(&round, &[]).unwrap();
+        assert!(record.round_seed().is_err());
+        assert_eq!(record.verify_fair(&reveal.server_seed).unwrap(), keys[1]);
+        assert!(record.verify_fair("forged").is_err());
+        assert_eq!(
+            RoundRecord {
+                server_seed: Some(reveal.server_seed.clone()),
+                ..record.clone()
+            }
+            .round_seed()
+            .unwrap(),
+            seed
+        );
+        assert!(RoundRecord {
+            nonce: Some(2),
+            ..record.clone()
+        }
+        .verify_fair(&reveal.server_seed)
+        .is_err());
+        assert!(RoundRecord {
+            fair_hash: None,
+            ..record
+        }
+        .verify_fair(&reveal.server_seed)
+        .is_err());
+    }
+}
@@ -0,0 +1,323 @@
+use crate::config_validator::{ConfigValidator, IssueKind};
+use crate::protocol::{BasePoint, DatabaseStore, FreeGame, FreeLevel};
+use essential_core::error::ServerError;
+use fugaso_data::fugaso_action;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WildMode {
+    #[default]
+    #[serde(rename = "NONE")]
+    None,
+    /// the wilds stay on their cells for the rest of the free spins
+    #[serde(rename = "STICKY")]
+    Sticky,
+    /// a wild fills its column
+    #[serde(rename = "EXPANDING")]
+    Expanding,
+}
+
+/// what moves the progress of the levels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Progress {
+    #[default]
+    #[serde(rename = "NONE")]
+    None,
+    /// every free spin
+    #[serde(rename = "SPINS")]
+    Spins,
+    /// every symbol on the grid like the wilds of bonanza
+    #[serde(rename = "SYMBOL")]
+    Symbol(char),
+}
+
+fn default_multipliers() -> Vec<i32> {
+    vec![1]
+}
+
+/// config of the free spins of a game, the games of `triggers` and `retriggers` are found by the greatest count not above the scatters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FreeConfig {
+    pub scatter: char,
+    pub triggers: BTreeMap<usize, i32>,
+    #[serde(default)]
+    pub retriggers: BTreeMap<usize, i32>,
+    /// reel category of every level, the last one stays for the levels above
+    pub categories: Vec<usize>,
+    /// multiplier of every level
+    #[serde(default = "default_multipliers")]
+    pub multipliers: Vec<i32>,
+    #[serde(default)]
+    pub progress: Progress,
+    /// progress of a level, 0 without the levels
+    #[serde(default)]
+    pub level_step: usize,
+    /// games added by every new level
+    #[serde(default)]
+    pub level_games: i32,
+    #[serde(default)]
+    pub wild: Option<char>,
+    #[serde(default)]
+    pub wild_mode: WildMode,
+    /// cap of the games of the free spins with the retriggers, 0 without the cap
+    #[serde(default)]
+    pub max_games: i32,
+}
+
+impl FreeConfig {
+    pub fn validate(&self) -> ConfigValidator {
+        let mut v = ConfigValidator::new(stringify!(FreeConfig));
+        if self.triggers.is_empty() {
+            v.add(IssueKind::EmptyFeature, "triggers", "free triggers are empty".to_string());
+        }
+        for (name, map) in [("triggers", &self.triggers), ("retriggers", &self.retriggers)] {
+            if let Some((count, games)) = map.iter().find(|(c, g)| **c == 0 || **g <= 0) {
+                v.add(IssueKind::IllegalCount, name, format!("{count} scatters give {games} games"));
+            }
+        }
+        if self.categories.is_empty() {
+            v.add(IssueKind::ReelCategories, "categories", "free categories are empty".to_string());
+        }
+        if self.multipliers.is_empty() || self.multipliers.contains(&0) {
+            v.add(IssueKind::IllegalMultiplier, "multipliers", format!("illegal multipliers {:?}", self.multipliers));
+        }
+        if self.level_step > 0 && self.progress == Progress::None {
+            v.add(IssueKind::MissingProgress, "progress", "levels without the progress".to_string());
+        }
+        if self.wild_mode != WildMode::None && self.wild.is_none() {
+            v.add(IssueKind::MissingSymbol, "wild", format!("wild mode {:?} without the wild", self.wild_mode));
+        }
+        v
+    }
+}
+
+/// controller of the free spins, the state of the free spins is the `FreeGame` of the result which goes to the action by `FreeGame::to_db`
+pub struct FreeSpins<'a> {
+    pub config: &'a FreeConfig,
+}
+
+impl<'a> FreeSpins<'a> {
+    pub fn new(config: &'a FreeConfig) -> Self {
+        Self {
+            config,
+        }
+    }
+
+    pub fn scatters(&self, grid: &Vec<Vec<char>>) -> usize {
+        grid.iter().flatten().filter(|s| **s == self.config.scatter).count()
+    }
+
+    fn games(map: &BTreeMap<usize, i32>, scatters: usize) -> i32 {
+        map.range(..=scatters).next_back().map(|(_, g)| *g).unwrap_or(0)
+    }
+
+    /// games of the cap, `initial` are the games given already
+    fn capped(&self, initial: i32, games: i32) -> i32 {
+        if self.config.max_games > 0 {
+            std::cmp::min(games, std::cmp::max(0, self.config.max_games - initial))
+        } else {
+            games
+        }
+    }
+
+    fn level(&self, level: usize, progress: usize) -> FreeLevel {
+        FreeLevel {
+            level,
+            progress,
+            multi: self.config.multipliers.get(level).or(self.config.multipliers.last()).copied().unwrap_or(1),
+        }
+    }
+
+    /// free spins started by the grid of a spin, none without the trigger
+    pub fn trigger(&self, grid: &Vec<Vec<char>>, total_win: i64) -> Option<FreeGame> {
+        let games = self.capped(0, Self::games(&self.config.triggers, self.scatters(grid)));
+        if games > 0 {
+            Some(FreeGame {
+                total_win,
+                symbol: self.config.scatter,
+                category: *self.config.categories.first()?,
+                initial: games,
+                left: games,
+                done: 0,
+                level: if self.config.level_step > 0 || self.config.multipliers.len() > 1 {
+                    Some(self.level(0, 0))
+                } else {
+                    None
+                },
+                sticky: vec![],
+            })
+        } else {
+            None
+        }
+    }
+
+    /// multiplier of the wins of the next free spin
+    pub fn multiplier(&self, free: &FreeGame) -> i32 {
+        free.level.map(|l| l.multi).or(self.config.multipliers.first().copied()).unwrap_or(1)
+    }
+
+    /// the sticky wilds of the previous free spins are put on the grid, an expanding wild fills its column
+    pub fn apply_wilds(&self, free: &FreeGame, grid: &mut Vec<Vec<char>>) {
+        let wild = match self.config.wild {
+            Some(w) => w,
+            None => return,
+        };
+        match self.config.wild_mode {
+            WildMode::None => {}
+            WildMode::Sticky => free.sticky.iter().for_each(|p| grid[p.x][p.y] = wild),
+            WildMode::Expanding => grid.iter_mut().filter(|c| c.contains(&wild)).for_each(|c| c.iter_mut().for_each(|s| *s = wild)),
+        }
+    }
+
+    /// state after a free spin with the grid of `apply_wilds` and its `win`, the retriggers and the new levels add the games
+    pub fn play(&self, prev: &FreeGame, grid: &Vec<Vec<char>>, win: i64) -> FreeGame {
+        let mut free = prev.clone();
+        free.play();
+        free.total_win += win;
+
+        let games = self.capped(free.initial, Self::games(&self.config.retriggers, self.scatters(grid)));
+        free.add(games);
+
+        if let (WildMode::Sticky, Some(wild)) = (self.config.wild_mode, self.config.wild) {
+            grid.iter().enumerate().for_each(|(c, col)| {
+                col.iter().enumerate().filter(|(_, s)| **s == wild).for_each(|(r, _)| {
+                    let p = BasePoint { x: c, y: r };
+                    if !free.sticky.contains(&p) {
+                        free.sticky.push(p);
+                    }
+                })
+            });
+        }
+
+        if let Some(l) = prev.level {
+            let step = match self.config.progress {
+                Progress::None => 0,
+                Progress::Spins => 1,
+                Progress::Symbol(s) => grid.iter().flatten().filter(|v| **v == s).count(),
+            };
+            let progress = l.progress + step;
+            let level = if self.config.level_step > 0 {
+                std::cmp::min(self.config.multipliers.len().saturating_sub(1), progress / self.config.level_step)
+            } else {
+                l.level
+            };
+            if level > l.level {
+                let games = self.capped(free.initial, (level - l.level) as i32 * self.config.level_games);
+                free.add(games);
+                free.category = self.config.categories.get(level).or(self.config.categories.last()).copied().unwrap_or(free.category);
+            }
+            free.level = Some(self.level(level, progress));
+        }
+        free
+    }
+
+    /// free spins of the last action of the round which has them
+    pub fn restore(actions: &[fugaso_action::Model]) -> Result<Option<FreeGame>, ServerError> {
+        match actions.iter().filter(|a| a.free_games.is_some()).max_by_key(|a| a.id) {
+            Some(fugaso_action::Model {
+                free_games: Some(f),
+                ..
+            }) => FreeGame::from_db(f).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config_validator::IssueKind;
+    use crate::free::{FreeConfig, FreeSpins, Progress, WildMode};
+    use crate::protocol::{BasePoint, DatabaseStore, FreeGame, FreeLevel};
+    use fugaso_data::fugaso_action;
+    use std::collections::BTreeMap;
+
+    #[test]
+    pub fn test_free_spins() {
+        let config = FreeConfig {
+            scatter: 'S',
+            triggers: BTreeMap::from([(3, 10), (4, 15)]),
+            retriggers: BTreeMap::from([(3, 5)]),
+            categories: vec![1, 2],
+            multipliers: vec![1, 2, 3],
+            progress: Progress::Spins,
+            level_step: 2,
+            level_games: 2,
+            wild: Some('W'),
+            wild_mode: WildMode::Sticky,
+            max_games: 20,
+        };
+        assert!(config.validate().is_valid());
+        let broken = FreeConfig {
+            triggers: BTreeMap::new(),
+            retriggers: BTreeMap::from([(3, 0)]),
+            multipliers: vec![],
+            progress: Progress::None,
+            wild: None,
+            ..config.clone()
+        };
+        let kinds = broken.validate().issues.iter().map(|i| i.kind).collect::<Vec<_>>();
+        let expected = vec![IssueKind::EmptyFeature, IssueKind::IllegalCount, IssueKind::IllegalMultiplier, IssueKind::MissingProgress, IssueKind::MissingSymbol];
+        assert_eq!(kinds, expected);
+        // the games are not multiplied without the multipliers
+        assert_eq!(FreeSpins::new(&broken).level(3, 0).multi, 1);
+        let spins = FreeSpins::new(&config);
+        let blank = vec![vec!['A'; 3]; 5];
+        let scatters = vec![vec!['S', 'A', 'A'], vec!['W', 'S', 'A'], vec!['A', 'A', 'S'], vec!['A'; 3], vec!['A'; 3]];
+
+        assert!(spins.trigger(&blank, 0).is_none());
+        let mut four = scatters.clone();
+        four[3][0] = 'S';
+        assert_eq!(spins.trigger(&four, 0).map(|f| f.initial), Some(15));
+        let free = spins.trigger(&scatters, 50).unwrap();
+        assert_eq!((free.initial, free.left, free.category, free.level), (10, 10, 1, Some(FreeLevel { level: 0, progress: 0, multi: 1 })));
+
+        // the retrigger adds the games and the wild sticks
+        let free = spins.play(&free, &scatters, 100);
+        assert_eq!((free.initial, free.left, free.done, free.total_win), (15, 14, 1, 150));
+        assert_eq!(free.sticky, vec![BasePoint { x: 1, y: 0 }]);
+        let mut grid = blank.clone();
+        spins.apply_wilds(&free, &mut grid);
+        assert_eq!(grid[1][0], 'W');
+
+        // the second spin opens the next level with its games, category and multiplier
+        let free = spins.play(&free, &grid, 0);
+        assert_eq!((free.initial, free.left, free.category, spins.multiplier(&free)), (17, 15, 2, 2));
+        assert_eq!(free.sticky.len(), 1);
+
+        // the cap limits the retriggers
+        let free = spins.play(&free, &scatters, 0);
+        assert_eq!((free.initial, free.left), (20, 17));
+        let free = spins.play(&free, &scatters, 0);
+        assert_eq!((free.initial, free.left), (20, 16));
+        assert_eq!(free.level, Some(FreeLevel { level: 2, progress: 4, multi: 3 }));
+
+        // the state goes to the action and back
+        let stored = free.to_db().unwrap();
+        let restored = FreeGame::from_db(&stored).unwrap();
+        assert_eq!((restored.left, restored.done, restored.initial, restored.level, restored.sticky.clone()), (free.left, free.done, free.initial, free.level, free.sticky.clone()));
+        assert_eq!(restored.to_db().unwrap(), stored);
+        let actions = vec![
+            fugaso_action::Model {
+                id: 1,
+                free_games: Some(stored.clone()),
+                ..Default::default()
+            },
+            fugaso_action::Model {
+                id: 2,
+                ..Default::default()
+            },
+        ];
+        assert_eq!(FreeSpins::restore(&actions).unwrap().map(|f| f.to_db().unwrap()), Some(stored));
+
+        let expanding = FreeConfig {
+            wild_mode: WildMode::Expanding,
+            ..config.clone()
+        };
+        let mut grid = scatters.clone();
+        FreeSpins::new(&expanding).apply_wilds(&free, &mut grid);
+        assert_eq!(grid[1], vec!['W'; 3]);
+        assert_eq!(grid[0], scatters[0]);
+    }
+}
@@ -1,8 +1,10 @@
+use crate::config_validator::{ConfigValidator, IssueKind};
 use essential_core::err_on;
 use essential_core::error::ServerError;
 use fugaso_data::fugaso_action::ActionKind;
 use maplit::{hashmap, hashset};
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 pub trait FSM {
     fn default(game_name: &str) -> Self;
@@ -13,17 +15,16 @@ pub trait FSM {
     fn current(&self) -> ActionKind;
 }
 
-pub struct SlotFSM {
-    current: ActionKind,
-    input: ActionKind,
-    prev: ActionKind,
-    game_name: String,
-    transitions: HashMap<ActionKind, HashMap<ActionKind, ActionKind>>,
-    client_acts: HashSet<ActionKind>,
+/// transitions of the fsm of a game, `transitions[state][action]` is the next state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FsmDefinition {
+    pub transitions: HashMap<ActionKind, HashMap<ActionKind, ActionKind>>,
+    pub client_acts: HashSet<ActionKind>,
 }
 
-impl SlotFSM {
-    pub fn new(current: ActionKind, input: ActionKind, game_name: &str, prev: ActionKind) -> Self {
+impl Default for FsmDefinition {
+    fn default() -> Self {
         let transitions = hashmap! {
             ActionKind::BET => hashmap! {
                 ActionKind::BET => ActionKind::SPIN,
@@ -51,7 +52,8 @@ impl SlotFSM {
                 ActionKind::RESPIN => ActionKind::CLOSE
             },
             ActionKind::BONUS => hashmap! {
-                ActionKind::BONUS => ActionKind::BONUS
+                ActionKind::BONUS => ActionKind::BONUS,
+                ActionKind::CLOSE => ActionKind::CLOSE,
             },
             ActionKind::FREE_COLLECT => hashmap! {
                 ActionKind::FREE_COLLECT => ActionKind::FREE_SPIN,
@@ -85,6 +87,158 @@ impl SlotFSM {
                 ActionKind::DROP, ActionKind::BONUS
         };
 
+        Self {
+            transitions,
+            client_acts,
+        }
+    }
+}
+
+/// client actions of `SlotAdmin` with the states they are issued in, the admin rejects the other actions of its fsm
+pub const ADMIN_CLIENT_ACTS: [(ActionKind, ActionKind); 9] = [
+    (ActionKind::BET, ActionKind::BET),
+    (ActionKind::SPIN, ActionKind::SPIN),
+    (ActionKind::RESPIN, ActionKind::RESPIN),
+    (ActionKind::BONUS, ActionKind::BONUS),
+    (ActionKind::COLLECT, ActionKind::GAMBLE_PLAY),
+    (ActionKind::FREE_COLLECT, ActionKind::GAMBLE_PLAY),
+    (ActionKind::COLLECT, ActionKind::HALF_COLLECT),
+    (ActionKind::DROP, ActionKind::DROP),
+    (ActionKind::FREE_SPIN, ActionKind::FREE_SPIN),
+];
+
+/// server actions of `SlotAdmin` with the states they are issued in, the results of the plays are closed from `CLOSE`
+pub const ADMIN_SERVER_ACTS: [(ActionKind, ActionKind); 14] = [
+    (ActionKind::CLOSE, ActionKind::CLOSE),
+    (ActionKind::CLOSE, ActionKind::BONUS_START),
+    (ActionKind::CLOSE, ActionKind::RESPIN_START),
+    (ActionKind::CLOSE, ActionKind::FREESPIN_START),
+    (ActionKind::CLOSE, ActionKind::DROP_START),
+    (ActionKind::CLOSE, ActionKind::GAMBLE_END),
+    (ActionKind::CLOSE, ActionKind::COLLECT_START),
+    (ActionKind::CLOSE, ActionKind::FREE_COLLECT_START),
+    (ActionKind::CLOSE, ActionKind::GAMBLE_FREE_END),
+    (ActionKind::BONUS, ActionKind::CLOSE),
+    (ActionKind::COLLECT, ActionKind::COLLECT),
+    (ActionKind::GAMBLE_END, ActionKind::COLLECT),
+    (ActionKind::BET, ActionKind::FREESPIN_START),
+    (ActionKind::BET, ActionKind::DROP_START),
+];
+
+impl FsmDefinition {
+    pub fn from_json(json: &str) -> Result<Self, ServerError> {
+        serde_json::from_str(json).map_err(|e| err_on!(e))
+    }
+
+    /// every state of the transitions sorted by the name
+    pub fn states(&self) -> Vec<ActionKind> {
+        let mut states = self.transitions.keys().chain(self.transitions.values().flat_map(|t| t.values())).cloned().collect::<Vec<_>>();
+        states.sort_by_key(|s| format!("{s:?}"));
+        states.dedup();
+        states
+    }
+
+    /// every transition as `(state, action, next)` sorted by the names
+    pub fn edges(&self) -> Vec<(ActionKind, ActionKind, ActionKind)> {
+        let mut edges = self.transitions.iter().flat_map(|(from, t)| t.iter().map(move |(action, to)| (from.clone(), action.clone(), to.clone()))).collect::<Vec<_>>();
+        edges.sort_by_key(|(f, a, _)| format!("{f:?} {a:?}"));
+        edges
+    }
+
+    /// states reached from `start`, the edges are reversed by `reverse`
+    fn reach(&self, start: ActionKind, reverse: bool) -> HashSet<ActionKind> {
+        let edges = self.edges();
+        let mut seen = hashset! {start.clone()};
+        let mut queue = VecDeque::from([start]);
+        while let Some(s) = queue.pop_front() {
+            for (from, _, to) in &edges {
+                let (a, b) = if reverse {
+                    (to, from)
+                } else {
+                    (from, to)
+                };
+                if *a == s && seen.insert(b.clone()) {
+                    queue.push_back(b.clone());
+                }
+            }
+        }
+        seen
+    }
+
+    /// transitions of `SlotAdmin` which are missing in the states reached from `BET`
+    pub fn missing_admin_acts(&self) -> Vec<(ActionKind, ActionKind)> {
+        let reached = self.reach(ActionKind::BET, false);
+        ADMIN_CLIENT_ACTS
+            .iter()
+            .chain(ADMIN_SERVER_ACTS.iter())
+            .filter(|(state, action)| reached.contains(state) && !self.transitions.get(state).map(|t| t.contains_key(action)).unwrap_or(false))
+            .cloned()
+            .collect()
+    }
+
+    /// every state is reached from `BET` and returns to `BET`, the actions of `SlotAdmin` are present
+    pub fn verify(&self) -> ConfigValidator {
+        let mut v = ConfigValidator::new(stringify!(FsmDefinition));
+        let reached = self.reach(ActionKind::BET, false);
+        let returned = self.reach(ActionKind::BET, true);
+        for s in self.states() {
+            if !reached.contains(&s) {
+                v.add(IssueKind::UnreachableState, "transitions", format!("state {s:?} is not reached from BET"));
+            }
+            if !returned.contains(&s) {
+                v.add(IssueKind::NoReturn, "transitions", format!("state {s:?} never returns to BET"));
+            }
+        }
+        for (state, action) in self.missing_admin_acts() {
+            v.add(IssueKind::MissingTransition, &format!("transitions.{state:?}"), format!("action {action:?} of the admin is missing"));
+        }
+        for (state, action) in ADMIN_CLIENT_ACTS.iter().filter(|(s, a)| reached.contains(s) && !self.client_acts.contains(a)) {
+            v.add(IssueKind::MissingTransition, "clientActs", format!("client action {action:?} of the state {state:?} is missing"));
+        }
+        v
+    }
+
+    /// graph of the fsm in the dot language, the server actions are dashed
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut dot = format!("digraph \"{name}\" {{\n");
+        for (from, action, to) in self.edges() {
+            let style = if self.client_acts.contains(&action) {
+                ""
+            } else {
+                ", style=dashed"
+            };
+            dot.push_str(&format!("    \"{from:?}\" -> \"{to:?}\" [label=\"{action:?}\"{style}];\n"));
+        }
+        dot.push('}');
+        dot
+    }
+
+    /// graph of the fsm as the mermaid state diagram
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = "stateDiagram-v2\n    [*] --> BET\n".to_string();
+        for (from, action, to) in self.edges() {
+            mermaid.push_str(&format!("    {from:?} --> {to:?}: {action:?}\n"));
+        }
+        mermaid
+    }
+}
+
+pub struct SlotFSM {
+    current: ActionKind,
+    input: ActionKind,
+    prev: ActionKind,
+    game_name: String,
+    transitions: HashMap<ActionKind, HashMap<ActionKind, ActionKind>>,
+    client_acts: HashSet<ActionKind>,
+}
+
+impl SlotFSM {
+    pub fn new(current: ActionKind, input: ActionKind, game_name: &str, prev: ActionKind) -> Self {
+        let FsmDefinition {
+            transitions,
+            client_acts,
+        } = FsmDefinition::default();
+
         Self {
             prev,
             current,
@@ -94,6 +248,13 @@ impl SlotFSM {
             client_acts,
         }
     }
+
+    /// the transitions of the game replace the default ones
+    pub fn with_definition(mut self, definition: FsmDefinition) -> Self {
+        self.transitions = definition.transitions;
+        self.client_acts = definition.client_acts;
+        self
+    }
 }
 
 impl FSM for SlotFSM {
@@ -161,3 +322,45 @@ impl FSM for SlotFSM {
         self.current.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::config_validator::IssueKind;
+    use crate::fsm::{FsmDefinition, SlotFSM, FSM};
+    use fugaso_data::fugaso_action::ActionKind;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_fsm_definition() {
+        let default = FsmDefinition::default();
+        assert!(default.verify().is_valid(), "{}", default.verify());
+        assert!(default.missing_admin_acts().is_empty());
+        assert_eq!(FsmDefinition::from_json(&serde_json::to_string(&default).unwrap()).unwrap(), default);
+
+        // the respins are closed from CLOSE by the admin
+        let mut broken = default.clone();
+        broken.transitions.get_mut(&ActionKind::CLOSE).unwrap().remove(&ActionKind::RESPIN_START);
+        broken.transitions.insert(ActionKind::DROP_START, HashMap::from([(ActionKind::DROP_START, ActionKind::DROP_START)]));
+        assert_eq!(broken.missing_admin_acts(), vec![(ActionKind::CLOSE, ActionKind::RESPIN_START)]);
+        let issues = broken.verify().issues;
+        let kinds = issues.iter().map(|i| i.kind).collect::<Vec<_>>();
+        assert!(kinds.contains(&IssueKind::MissingTransition));
+        assert!(kinds.contains(&IssueKind::UnreachableState));
+        assert!(kinds.contains(&IssueKind::NoReturn));
+        assert!(issues.iter().all(|i| i.message.contains("DROP_START") || i.message.contains("RESPIN")));
+
+        let mut slot_fsm = SlotFSM::default("test").with_definition(broken);
+        slot_fsm.client_act(ActionKind::BET).unwrap();
+        assert_eq!(slot_fsm.client_act(ActionKind::SPIN).unwrap(), ActionKind::CLOSE);
+        assert!(slot_fsm.server_act(ActionKind::RESPIN_START).is_err());
+        assert_eq!(slot_fsm.server_act(ActionKind::FREESPIN_START).unwrap(), ActionKind::FREE_SPIN);
+
+        let dot = default.to_dot("test");
+        assert!(dot.starts_with("digraph \"test\" {\n"));
+        assert!(dot.contains("    \"BET\" -> \"SPIN\" [label=\"BET\"];\n"));
+        assert!(dot.contains("    \"CLOSE\" -> \"RESPIN\" [label=\"RESPIN_START\", style=dashed];\n"));
+        let mermaid = default.to_mermaid();
+        assert!(mermaid.starts_with("stateDiagram-v2\n    [*] --> BET\n"));
+        assert!(mermaid.contains("    SPIN --> CLOSE: SPIN\n"));
+    }
+}
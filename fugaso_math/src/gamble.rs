@@ -0,0 +1,414 @@
+use crate::math::{IPlayResponse, SpinArg};
+use crate::protocol::{id, Card, DatabaseStore, FreeGame, GameData, GameResult, Promo, SpinData};
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use crate::rng::{DefaultSource, RngSource};
+use crate::seed::Seed;
+use fugaso_data::fugaso_action;
+use fugaso_data::fugaso_action::ActionKind;
+use num_traits::ToPrimitive;
+use sea_orm::prelude::Decimal;
+use serde::{Deserialize, Serialize};
+
+pub const COLOR_RED: &str = "RED";
+pub const COLOR_BLACK: &str = "BLACK";
+pub const SUITS: [(&str, &str); 4] = [("HEARTS", COLOR_RED), ("DIAMONDS", COLOR_RED), ("CLUBS", COLOR_BLACK), ("SPADES", COLOR_BLACK)];
+pub const RANKS: [(&str, &str); 13] = [
+    ("2", "Two"),
+    ("3", "Three"),
+    ("4", "Four"),
+    ("5", "Five"),
+    ("6", "Six"),
+    ("7", "Seven"),
+    ("8", "Eight"),
+    ("9", "Nine"),
+    ("10", "Ten"),
+    ("J", "Jack"),
+    ("Q", "Queen"),
+    ("K", "King"),
+    ("A", "Ace"),
+];
+
+pub fn deal_card<R: RngSource>(rand: &mut R) -> Card {
+    let rank = RANKS[rand.random(0, RANKS.len())];
+    let suit = SUITS[rand.random(0, SUITS.len())];
+    Card {
+        symbol: rank.0.to_string(),
+        color: suit.1.to_string(),
+        symbol_string: rank.1.to_string(),
+        suit: suit.0.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GambleChoice {
+    #[serde(rename = "RED")]
+    Red,
+    #[serde(rename = "BLACK")]
+    Black,
+    #[serde(rename = "HEARTS")]
+    Hearts,
+    #[serde(rename = "DIAMONDS")]
+    Diamonds,
+    #[serde(rename = "CLUBS")]
+    Clubs,
+    #[serde(rename = "SPADES")]
+    Spades,
+}
+
+impl GambleChoice {
+    /// a color doubles the amount and a suit quadruples it
+    pub fn factor(&self) -> i64 {
+        match self {
+            GambleChoice::Red | GambleChoice::Black => 2,
+            _ => 4,
+        }
+    }
+
+    pub fn wins(&self, card: &Card) -> bool {
+        match self {
+            GambleChoice::Red => card.color == COLOR_RED,
+            GambleChoice::Black => card.color == COLOR_BLACK,
+            GambleChoice::Hearts => card.suit == SUITS[0].0,
+            GambleChoice::Diamonds => card.suit == SUITS[1].0,
+            GambleChoice::Clubs => card.suit == SUITS[2].0,
+            GambleChoice::Spades => card.suit == SUITS[3].0,
+        }
+    }
+}
+
+/// limit of the gamble of a round, the total after a gamble can not be above `max_win`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GambleLimit {
+    pub max_win: i64,
+    /// gambles of a round, 0 without a limit
+    pub max_steps: usize,
+}
+
+impl Default for GambleLimit {
+    fn default() -> Self {
+        Self {
+            max_win: i64::MAX,
+            max_steps: 0,
+        }
+    }
+}
+
+impl GambleLimit {
+    /// `max_win` of the bet settings is in euro, `rate` is the exchange rate of the currency of the player
+    pub fn from_max_win(max_win: Decimal, rate: Decimal, max_steps: usize) -> Self {
+        let max_win = if max_win > Decimal::ZERO && rate > Decimal::ZERO {
+            (max_win * Decimal::new(100, 0) / rate).floor().to_i64().unwrap_or(i64::MAX)
+        } else {
+            i64::MAX
+        };
+        Self {
+            max_win,
+            max_steps,
+        }
+    }
+
+    pub fn allows(&self, state: &GambleState, choice: GambleChoice) -> bool {
+        state.amount > 0
+            && (self.max_steps == 0 || state.history.len() < self.max_steps)
+            && state.amount.checked_mul(choice.factor()).and_then(|a| a.checked_add(state.banked)).map(|t| t <= self.max_win).unwrap_or(false)
+    }
+
+    /// the smallest gamble is allowed
+    pub fn can_gamble(&self, state: &GambleState) -> bool {
+        self.allows(state, GambleChoice::Red)
+    }
+}
+
+/// gamble of the win of a round, `banked` are the halves taken by the half collects, the cards are the history of the gambles
+/// and `choices` are the choices of the player for them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GambleState {
+    pub amount: i64,
+    pub banked: i64,
+    pub history: Vec<Card>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<GambleChoice>,
+}
+
+impl GambleState {
+    pub fn start(amount: i64) -> Self {
+        Self {
+            amount,
+            ..Default::default()
+        }
+    }
+
+    pub fn total(&self) -> i64 {
+        self.amount + self.banked
+    }
+
+    /// the amount is lost with a wrong choice
+    pub fn play(&mut self, choice: GambleChoice, card: Card) -> bool {
+        let win = choice.wins(&card);
+        self.amount = if win {
+            self.amount * choice.factor()
+        } else {
+            0
+        };
+        self.history.push(card);
+        self.choices.push(choice);
+        win
+    }
+
+    /// banks the half of the amount and gambles the rest
+    pub fn half(&mut self) -> Result<i64, ServerError> {
+        if self.amount < 2 {
+            return Err(err_on!("amount is too small to halve!"));
+        }
+        let half = self.amount / 2;
+        self.banked += half;
+        self.amount -= half;
+        Ok(half)
+    }
+
+    /// state of the last gamble action of the round
+    pub fn restore(actions: &[fugaso_action::Model]) -> Result<Option<Self>, ServerError> {
+        match actions
+            .iter()
+            .filter(|a| a.act_descr == Some(ActionKind::GAMBLE_PLAY) || a.act_descr == Some(ActionKind::HALF_COLLECT))
+            .max_by_key(|a| a.id)
+        {
+            Some(fugaso_action::Model {
+                cards: Some(c),
+                ..
+            }) => Self::from_db(c).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl DatabaseStore for GambleState {
+    fn from_db(value: &str) -> Result<Self, ServerError> {
+        serde_json::from_str(value).map_err(|e| err_on!(e))
+    }
+
+    fn to_db(&self) -> Result<String, ServerError> {
+        serde_json::to_string(self).map_err(|e| err_on!(e))
+    }
+}
+
+/// gamble of a math, the math keeps it with its result and calls it from `SlotMath::gamble` and `SlotMath::half_collect`,
+/// the cards come from the source of the math so the seeded and the fair rounds reach the gamble
+pub struct Gambler<R: RngSource = DefaultSource> {
+    pub rand: R,
+    pub state: Option<GambleState>,
+}
+
+impl<R: RngSource> Gambler<R> {
+    pub fn new(rand: R) -> Self {
+        Self {
+            rand,
+            state: None,
+        }
+    }
+
+    pub fn init(&mut self, actions: &[fugaso_action::Model]) -> Result<(), ServerError> {
+        self.state = GambleState::restore(actions)?;
+        Ok(())
+    }
+
+    /// the card of the next gamble comes from the seed of the action, see `seed::Seed`
+    pub fn reseed(&mut self, seed: Seed) {
+        self.rand.reseed(seed);
+    }
+
+    /// the new round starts without the gamble
+    pub fn reset(&mut self) {
+        self.state = None;
+    }
+
+    pub fn gamble<S: DatabaseStore + Default + Clone, R: Default + Clone>(
+        &mut self,
+        prev: &SpinData<S, R>,
+        arg: SpinArg,
+        choice: GambleChoice,
+        limit: &GambleLimit,
+    ) -> Result<GameData<S, R>, ServerError> {
+        let mut state = self.state.clone().unwrap_or_else(|| GambleState::start(prev.result.total));
+        if !limit.allows(&state, choice) {
+            return Err(err_on!("gamble limit is exceeded!"));
+        }
+        let card = deal_card(&mut self.rand);
+        state.play(choice, card);
+        self.state = Some(state.clone());
+        Ok(GambleData::create(prev, state, arg))
+    }
+
+    pub fn half_collect<S: DatabaseStore + Default + Clone, R: Default + Clone>(&mut self, prev: &SpinData<S, R>, arg: SpinArg) -> Result<GameData<S, R>, ServerError> {
+        let mut state = self.state.clone().unwrap_or_else(|| GambleState::start(prev.result.total));
+        state.half()?;
+        self.state = Some(state.clone());
+        Ok(GambleData::create(prev, state, arg))
+    }
+}
+
+/// result of a gamble, the total of the round is the total of the gamble and the state goes to the `cards` of the action
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GambleData<S: DatabaseStore + Default, R: Default> {
+    #[serde(flatten)]
+    pub spin: SpinData<S, R>,
+    pub gamble: GambleState,
+}
+
+impl<S: DatabaseStore + Default + Clone, R: Default + Clone> GambleData<S, R> {
+    pub fn create(prev: &SpinData<S, R>, state: GambleState, arg: SpinArg) -> GameData<S, R> {
+        GameData::Gamble(GambleData {
+            spin: SpinData {
+                id: id::GAME_DATA,
+                balance: arg.balance,
+                result: GameResult {
+                    total: state.total(),
+                    cards: state.history.clone(),
+                    gains: vec![],
+                    ..prev.result.clone()
+                },
+                next_act: arg.next_act,
+                round_id: arg.round_id,
+                round_type: arg.round_type,
+                round_multiplier: arg.round_multiplier,
+                promo: arg.promo,
+                ..prev.clone()
+            },
+            gamble: state,
+        })
+    }
+}
+
+impl<S: DatabaseStore + Default + 'static, R: Default + 'static> IPlayResponse for GambleData<S, R> {
+    fn create_action_default(&self) -> Result<fugaso_action::Model, ServerError> {
+        Ok(fugaso_action::Model {
+            cards: Some(self.gamble.to_db()?),
+            ..self.spin.create_action_default()?
+        })
+    }
+
+    fn free(&self) -> Option<&FreeGame> {
+        self.spin.free()
+    }
+
+    fn has_bonus(&self) -> bool {
+        false
+    }
+
+    fn has_respin(&self) -> bool {
+        false
+    }
+
+    fn has_drop(&self) -> bool {
+        false
+    }
+
+    fn total(&self) -> i64 {
+        self.spin.total()
+    }
+
+    fn is_gamble_end(&self, _total_bet: i64) -> bool {
+        self.gamble.amount == 0
+    }
+
+    fn stops_on(&self) -> Vec<usize> {
+        self.spin.stops_on()
+    }
+
+    fn grid_on(&self) -> Vec<Vec<char>> {
+        self.spin.grid_on()
+    }
+
+    fn promo(&self) -> Promo {
+        self.spin.promo()
+    }
+
+    fn set_next_act(&mut self, kind: ActionKind) {
+        self.spin.set_next_act(kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gamble::{deal_card, GambleChoice, GambleLimit, GambleState, Gambler};
+    use crate::hold::HoldInfo;
+    use crate::math::SpinArg;
+    use crate::protocol::{GameResult, Promo, SpinData, StartInfo};
+    use crate::rng::{CounterSource, ScriptedSource};
+    use crate::seed::Seed;
+    use essential_rand::random::RandomGenerator;
+    use fugaso_data::fugaso_action::ActionKind;
+    use fugaso_data::fugaso_round::RoundDetail;
+    use sea_orm::prelude::Decimal;
+
+    #[test]
+    pub fn test_gamble() {
+        let mut rand = RandomGenerator::from_seed(11);
+        let cards = (0..200).map(|_| deal_card(&mut rand)).collect::<Vec<_>>();
+        let reds = cards.iter().filter(|c| GambleChoice::Red.wins(c)).count();
+        assert!(reds > 60 && reds < 140);
+        assert!(cards.iter().all(|c| GambleChoice::Red.wins(c) != GambleChoice::Black.wins(c)));
+
+        // max win of 10 euro with the rate of 2 is 500 in the currency of the player
+        let limit = GambleLimit::from_max_win(Decimal::new(10, 0), Decimal::new(2, 0), 3);
+        assert_eq!(limit.max_win, 500);
+        let mut state = GambleState::start(100);
+        let card = cards.iter().find(|c| c.suit == "HEARTS").unwrap().clone();
+        assert!(limit.allows(&state, GambleChoice::Hearts));
+        assert!(state.play(GambleChoice::Red, card.clone()));
+        assert_eq!(state.total(), 200);
+        assert!(!limit.allows(&state, GambleChoice::Hearts));
+        assert_eq!(state.half().unwrap(), 100);
+        assert_eq!((state.amount, state.banked, state.total()), (100, 100, 200));
+        assert!(!state.play(GambleChoice::Black, card.clone()));
+        assert_eq!((state.amount, state.total()), (0, 100));
+        assert!(!limit.can_gamble(&state));
+        assert_eq!(state.choices, vec![GambleChoice::Red, GambleChoice::Black]);
+        assert_eq!(GambleState::from_db(&state.to_db().unwrap()).unwrap(), state);
+
+        let mut steps = GambleState::start(1);
+        (0..3).for_each(|_| {
+            steps.play(GambleChoice::Red, card.clone());
+        });
+        assert!(!limit.can_gamble(&steps));
+    }
+
+    #[test]
+    pub fn test_gambler_source() {
+        // the same seed of the action deals the same card
+        let deal = |seed: Seed| {
+            let mut gambler = Gambler::new(CounterSource::default());
+            gambler.reseed(seed);
+            deal_card(&mut gambler.rand)
+        };
+        assert_eq!(deal(Seed::Plain(5)), deal(Seed::Plain(5)));
+
+        // the script deals the two of hearts and then the two of clubs
+        let mut gambler = Gambler::new(ScriptedSource::new(vec![0, 0, 0, 2]));
+        let prev = SpinData::<HoldInfo, StartInfo> {
+            result: GameResult {
+                total: 100,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let arg = SpinArg {
+            balance: 1_000,
+            round_id: 1,
+            round_type: RoundDetail::SIMPLE,
+            round_multiplier: 1,
+            next_act: ActionKind::CLOSE,
+            promo: Promo::default(),
+            stake: 10,
+        };
+        let result = gambler.gamble(&prev, arg.clone(), GambleChoice::Red, &GambleLimit::default()).unwrap();
+        assert_eq!(result.total(), 200);
+        gambler.reseed(Seed::Plain(1));
+        let result = gambler.gamble(&prev, arg.clone(), GambleChoice::Red, &GambleLimit::default()).unwrap();
+        assert_eq!(result.total(), 0);
+        assert_eq!(gambler.state.map(|s| s.choices), Some(vec![GambleChoice::Red, GambleChoice::Red]));
+    }
+}
@@ -0,0 +1,380 @@
+use crate::config_validator::{ConfigValidator, IssueKind};
+use crate::protocol::{BasePoint, DatabaseStore, Gain};
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use crate::rng::RngSource;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// what a special symbol of a link game does when it lands, the values are the factors of `HoldArg::unit`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum SymbolHandler {
+    /// value of the distribution like the coins of the thunder games
+    #[serde(rename = "COIN")]
+    Coin { dist: BTreeMap<i32, i32> },
+    /// fixed value of the jackpot tier
+    #[serde(rename = "JACKPOT")]
+    Jackpot { tier: usize, value: i32 },
+    /// multiplies the values of every held cell
+    #[serde(rename = "MULTIPLIER")]
+    Multiplier { dist: BTreeMap<i32, i32> },
+    /// takes the sum of the values of the held cells
+    #[serde(rename = "COLLECTOR")]
+    Collector,
+    /// multiplies the values of the held cells of its column like the lifts
+    #[serde(rename = "COLUMN_UPGRADE")]
+    ColumnUpgrade { dist: BTreeMap<i32, i32> },
+}
+
+impl SymbolHandler {
+    /// the symbols of a landing with the values go first, then the ones which change the values, the collectors are the last
+    fn order(&self) -> usize {
+        match self {
+            SymbolHandler::Coin { .. } | SymbolHandler::Jackpot { .. } => 0,
+            SymbolHandler::Multiplier { .. } | SymbolHandler::ColumnUpgrade { .. } => 1,
+            SymbolHandler::Collector => 2,
+        }
+    }
+}
+
+fn empty_symbol() -> char {
+    '@'
+}
+
+/// config of a hold and win feature, the special symbols are held while the respins go and every landing resets the respins
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldConfig {
+    pub symbols: HashMap<char, SymbolHandler>,
+    pub respins: i32,
+    /// specials of a spin which start the feature
+    pub trigger: usize,
+    /// symbol of the cells which are not held
+    #[serde(default = "empty_symbol")]
+    pub empty: char,
+    /// symbol and value paid when every cell is held
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grand: Option<(char, i32)>,
+    /// the feature stops when the total of the round reaches `HoldArg::max_win`
+    #[serde(default)]
+    pub stop_factor: i32,
+}
+
+impl HoldConfig {
+    pub fn validate(&self) -> ConfigValidator {
+        let mut v = ConfigValidator::new(stringify!(HoldConfig));
+        if self.symbols.is_empty() {
+            v.add(IssueKind::EmptyFeature, "symbols", "hold symbols are empty".to_string());
+        }
+        if self.symbols.contains_key(&self.empty) {
+            v.add(IssueKind::SpecialSymbol, "empty", format!("empty symbol {} is special", self.empty));
+        }
+        if self.respins <= 0 || self.trigger == 0 {
+            v.add(IssueKind::IllegalCount, "respins", format!("illegal respins {} or trigger {}", self.respins, self.trigger));
+        }
+        let mut symbols = self.symbols.iter().collect::<Vec<_>>();
+        symbols.sort_by_key(|(s, _)| **s);
+        for (s, h) in symbols {
+            match h {
+                SymbolHandler::Coin { dist } | SymbolHandler::Multiplier { dist } | SymbolHandler::ColumnUpgrade { dist } => v.check_dist(&format!("symbols[{s}]"), dist),
+                _ => {}
+            }
+        }
+        v
+    }
+}
+
+/// state of the feature, the specials of the game can be this one or keep it inside
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldInfo {
+    #[serde(default)]
+    pub total: i64,
+    pub respins: i32,
+    #[serde(default)]
+    pub accum: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<i32>,
+    /// held symbols, the empty symbol of the config for the free cells
+    pub held: Vec<Vec<char>>,
+    /// values of the held cells
+    pub mults: Vec<Vec<i32>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jackpots: Vec<usize>,
+    /// cells of the specials of the last landing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub landed: Vec<BasePoint>,
+}
+
+impl HoldInfo {
+    pub fn is_full(&self, empty: char) -> bool {
+        self.held.iter().flatten().all(|s| *s != empty)
+    }
+
+    pub fn sum(&self) -> i64 {
+        self.mults.iter().flatten().map(|v| *v as i64).sum()
+    }
+}
+
+impl DatabaseStore for HoldInfo {
+    fn from_db(value: &str) -> Result<Self, ServerError> {
+        serde_json::from_str(&value).map_err(|e| err_on!(e))
+    }
+
+    fn to_db(&self) -> Result<String, ServerError> {
+        serde_json::to_string(self).map_err(|e| err_on!(e))
+    }
+
+    fn respins(&self) -> i32 {
+        self.respins
+    }
+
+    fn stop(&self) -> i32 {
+        self.stop.unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HoldArg {
+    /// win of the value 1 like the playing bet by the round multiplier
+    pub unit: i64,
+    /// cap of the total of the round, 0 without the cap
+    pub max_win: i64,
+    /// total of the round before the respin
+    pub prev_total: i64,
+}
+
+/// the loop of the link games: the specials are held, a new landing resets the respins and the values are paid when the respins end
+pub struct HoldEngine<'a> {
+    pub config: &'a HoldConfig,
+}
+
+impl<'a> HoldEngine<'a> {
+    pub fn new(config: &'a HoldConfig) -> Self {
+        Self {
+            config,
+        }
+    }
+
+    pub fn is_special(&self, c: char) -> bool {
+        self.config.symbols.contains_key(&c)
+    }
+
+    pub fn specials(&self, grid: &Vec<Vec<char>>) -> usize {
+        grid.iter().flatten().filter(|s| self.is_special(**s)).count()
+    }
+
+    pub fn is_triggered(&self, grid: &Vec<Vec<char>>) -> bool {
+        self.specials(grid) >= self.config.trigger
+    }
+
+    /// the held symbols stay on the grid of the next respin
+    pub fn apply_prev(&self, current: &mut Vec<Vec<char>>, held: &Vec<Vec<char>>) {
+        for c in 0..held.len() {
+            for r in 0..held[c].len() {
+                if held[c][r] != self.config.empty {
+                    current[c][r] = held[c][r]
+                }
+            }
+        }
+    }
+
+    /// the feature starts with the specials of the grid of the spin
+    pub fn start<R: RngSource>(&self, rand: &mut R, grid: &Vec<Vec<char>>, arg: HoldArg) -> Result<(Vec<Gain>, HoldInfo), ServerError> {
+        let prev = HoldInfo {
+            held: grid.iter().map(|c| vec![self.config.empty; c.len()]).collect(),
+            mults: grid.iter().map(|c| vec![0; c.len()]).collect(),
+            ..Default::default()
+        };
+        self.respin(rand, &prev, grid, arg)
+    }
+
+    /// the specials of `grid` on the free cells land, the gains are only on the last respin
+    pub fn respin<R: RngSource>(&self, rand: &mut R, prev: &HoldInfo, grid: &Vec<Vec<char>>, arg: HoldArg) -> Result<(Vec<Gain>, HoldInfo), ServerError> {
+        let empty = self.config.empty;
+        let mut held = prev.held.clone();
+        let mut mults = prev.mults.clone();
+        let mut jackpots = prev.jackpots.clone();
+        let mut landed = grid
+            .iter()
+            .enumerate()
+            .flat_map(|(c, col)| col.iter().enumerate().map(move |(r, s)| (c, r, *s)))
+            .filter(|(c, r, s)| held[*c][*r] == empty && self.is_special(*s))
+            .collect::<Vec<_>>();
+        landed.sort_by_key(|(c, r, s)| (self.config.symbols[s].order(), *c, *r));
+
+        for (c, r, s) in landed.iter().cloned() {
+            held[c][r] = s;
+            match &self.config.symbols[&s] {
+                SymbolHandler::Coin { dist } => mults[c][r] = rand.rand_value(dist)?,
+                SymbolHandler::Jackpot { tier, value } => {
+                    mults[c][r] = *value;
+                    jackpots.push(*tier);
+                }
+                SymbolHandler::Multiplier { dist } => {
+                    let m = rand.rand_value(dist)?;
+                    mults.iter_mut().flatten().for_each(|v| *v *= m);
+                }
+                SymbolHandler::ColumnUpgrade { dist } => {
+                    let m = rand.rand_value(dist)?;
+                    mults[c].iter_mut().for_each(|v| *v *= m);
+                }
+                SymbolHandler::Collector => {
+                    let sum = mults.iter().flatten().sum();
+                    mults[c][r] = sum;
+                }
+            }
+        }
+
+        let mut info = HoldInfo {
+            respins: if landed.is_empty() {
+                prev.respins - 1
+            } else {
+                self.config.respins
+            },
+            held,
+            mults,
+            jackpots,
+            landed: landed.iter().map(|(c, r, _)| BasePoint { x: *c, y: *r }).collect(),
+            ..Default::default()
+        };
+        let grand = self.config.grand.filter(|_| info.is_full(empty));
+        if grand.is_some() {
+            info.respins = 0;
+        }
+        let win = (info.sum() + grand.map(|g| g.1 as i64).unwrap_or(0)) * arg.unit;
+        if arg.max_win > 0 && arg.prev_total + win >= arg.max_win {
+            info.respins = 0;
+            info.stop = Some(self.config.stop_factor);
+        }
+
+        let gains = if info.respins <= 0 {
+            self.gains(&info, grand, arg.unit)
+        } else {
+            vec![]
+        };
+        let sum = gains.iter().map(|g| g.amount).sum::<i64>();
+        let (total, accum) = if arg.max_win > 0 {
+            (std::cmp::min(arg.max_win, arg.prev_total + sum), std::cmp::min(arg.max_win, prev.accum + sum))
+        } else {
+            (arg.prev_total + sum, prev.accum + sum)
+        };
+        info.total = total;
+        info.accum = accum;
+        Ok((gains, info))
+    }
+
+    /// a gain of every held cell with the value, `line_num` is the index of the cell by columns
+    fn gains(&self, info: &HoldInfo, grand: Option<(char, i32)>, unit: i64) -> Vec<Gain> {
+        let columns = info.held.len();
+        let mut gains = info
+            .held
+            .iter()
+            .enumerate()
+            .flat_map(|(c, col)| col.iter().enumerate().map(move |(r, s)| (c, r, *s)))
+            .filter(|(c, r, _)| info.mults[*c][*r] > 0)
+            .map(|(c, r, s)| Gain {
+                symbol: s,
+                count: 1,
+                amount: info.mults[c][r] as i64 * unit,
+                line_num: r * columns + c,
+                multi: 1,
+                points: vec![BasePoint { x: c, y: r }],
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        if let Some((symbol, value)) = grand {
+            gains.push(Gain {
+                symbol,
+                count: 1,
+                amount: value as i64 * unit,
+                multi: 1,
+                ..Default::default()
+            });
+        }
+        gains
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config_validator::IssueKind;
+    use crate::hold::{HoldArg, HoldConfig, HoldEngine, SymbolHandler};
+    use essential_rand::random::RandomGenerator;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    pub fn test_hold_engine() {
+        let config = HoldConfig {
+            symbols: HashMap::from([
+                ('K', SymbolHandler::Coin { dist: BTreeMap::from([(100, 2)]) }),
+                ('J', SymbolHandler::Jackpot { tier: 1, value: 50 }),
+                ('X', SymbolHandler::Multiplier { dist: BTreeMap::from([(100, 3)]) }),
+                ('C', SymbolHandler::Collector),
+            ]),
+            respins: 3,
+            trigger: 2,
+            empty: '@',
+            grand: Some(('G', 1000)),
+            stop_factor: 0,
+        };
+        assert!(config.validate().is_valid());
+        let broken = HoldConfig {
+            symbols: HashMap::from([('@', SymbolHandler::Collector)]),
+            respins: 0,
+            ..config.clone()
+        };
+        let kinds = broken.validate().issues.iter().map(|i| i.kind).collect::<Vec<_>>();
+        assert_eq!(kinds, vec![IssueKind::SpecialSymbol, IssueKind::IllegalCount]);
+        let empty = HoldConfig {
+            symbols: HashMap::new(),
+            ..config.clone()
+        };
+        let kinds = empty.validate().issues.iter().map(|i| i.kind).collect::<Vec<_>>();
+        assert_eq!(kinds, vec![IssueKind::EmptyFeature]);
+        let engine = HoldEngine::new(&config);
+        let mut rand = RandomGenerator::from_seed(3);
+        let arg = HoldArg {
+            unit: 10,
+            ..Default::default()
+        };
+        let blank = vec![vec!['A'; 2]; 3];
+
+        let grid = vec![vec!['K', 'A'], vec!['A', 'K'], vec!['A', 'A']];
+        assert!(engine.is_triggered(&grid));
+        let (gains, info) = engine.start(&mut rand, &grid, arg).unwrap();
+        assert!(gains.is_empty());
+        assert_eq!((info.respins, info.mults.clone()), (3, vec![vec![2, 0], vec![0, 2], vec![0, 0]]));
+
+        let (_, info) = engine.respin(&mut rand, &info, &blank, arg).unwrap();
+        assert_eq!(info.respins, 2);
+        let mut held = blank.clone();
+        engine.apply_prev(&mut held, &info.held);
+        assert_eq!(held, grid);
+
+        // the multiplier goes before the collector of the same landing
+        let grid = vec![vec!['A', 'C'], vec!['A', 'A'], vec!['A', 'X']];
+        let (_, info) = engine.respin(&mut rand, &info, &grid, arg).unwrap();
+        assert_eq!((info.respins, info.mults.clone()), (3, vec![vec![6, 12], vec![0, 6], vec![0, 0]]));
+
+        let info = (0..2).fold(info, |i, _| engine.respin(&mut rand, &i, &blank, arg).unwrap().1);
+        let (gains, info) = engine.respin(&mut rand, &info, &blank, arg).unwrap();
+        assert_eq!(info.respins, 0);
+        assert_eq!(gains.iter().map(|g| g.amount).sum::<i64>(), 240);
+        assert_eq!(info.total, 240);
+
+        // the full grid pays the grand and the cap stops the feature
+        let full = vec![vec!['J'; 2]; 3];
+        let (gains, info) = engine.start(&mut rand, &full, arg).unwrap();
+        assert_eq!((info.respins, info.jackpots.len()), (0, 6));
+        assert_eq!(gains.last().map(|g| (g.symbol, g.amount)), Some(('G', 10_000)));
+        let capped = HoldArg {
+            max_win: 1_000,
+            prev_total: 200,
+            ..arg
+        };
+        let (_, info) = engine.start(&mut rand, &full, capped).unwrap();
+        assert_eq!((info.total, info.stop), (1_000, Some(0)));
+    }
+}
@@ -5,4 +5,21 @@ pub mod fsm;
 pub mod protocol;
 pub mod rand;
 pub mod config;
-pub mod validator;
\ No newline at end of file
+pub mod validator;
+pub mod simulate;
+pub mod exact;
+pub mod par;
+pub mod config_validator;
+pub mod lines;
+pub mod ways;
+pub mod cluster;
+pub mod cascade;
+pub mod bonus;
+pub mod gamble;
+pub mod hold;
+pub mod free;
+pub mod configurable;
+pub mod seed;
+pub mod rng;
+pub mod rng_check;
+pub mod fair;
@@ -0,0 +1,238 @@
+use crate::math::Request;
+use crate::protocol::{BasePoint, Gain};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PayDirection {
+    #[default]
+    LeftToRight,
+    RightToLeft,
+    BothWays,
+}
+
+/// symbol of a line with the positions of the counted symbols as `(column, row)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineHit {
+    pub symbol: char,
+    pub positions: Vec<(usize, usize)>,
+}
+
+/// symbol and count of a line after some columns of the evaluation, a broken line is not `alive`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LineState {
+    pub symbol: char,
+    pub count: usize,
+    pub alive: bool,
+}
+
+impl LineState {
+    pub fn start(symbol: char) -> Self {
+        Self {
+            symbol,
+            count: 0,
+            alive: true,
+        }
+    }
+}
+
+/// pays of the lines of `lines` with `wins`, a wild takes the symbol of the line and a line starting with wilds takes the first other symbol,
+/// the breakers stop a line like scatters
+#[derive(Debug, Clone)]
+pub struct LineEvaluator<'a> {
+    pub lines: &'a Vec<Vec<usize>>,
+    pub wins: &'a HashMap<char, HashMap<usize, i32>>,
+    pub direction: PayDirection,
+    pub wilds: Vec<char>,
+    /// the multipliers of the wilds on a line are multiplied
+    pub wild_mults: HashMap<char, i32>,
+    pub breakers: Vec<char>,
+}
+
+impl<'a> LineEvaluator<'a> {
+    pub fn new(lines: &'a Vec<Vec<usize>>, wins: &'a HashMap<char, HashMap<usize, i32>>) -> Self {
+        Self {
+            lines,
+            wins,
+            direction: PayDirection::default(),
+            wilds: vec![],
+            wild_mults: HashMap::new(),
+            breakers: vec![],
+        }
+    }
+
+    pub fn with_direction(mut self, direction: PayDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn with_wilds(mut self, wilds: &[char]) -> Self {
+        self.wilds = wilds.to_vec();
+        self
+    }
+
+    pub fn with_wild_mult(mut self, wild: char, mult: i32) -> Self {
+        if !self.wilds.contains(&wild) {
+            self.wilds.push(wild);
+        }
+        self.wild_mults.insert(wild, mult);
+        self
+    }
+
+    pub fn with_breakers(mut self, breakers: &[char]) -> Self {
+        self.breakers = breakers.to_vec();
+        self
+    }
+
+    pub fn is_wild(&self, c: char) -> bool {
+        self.wilds.contains(&c)
+    }
+
+    pub fn factor(&self, symbol: char, count: usize) -> i32 {
+        *self.wins.get(&symbol).and_then(|m| m.get(&count)).unwrap_or(&0)
+    }
+
+    /// one column of a line, the wild rules of the evaluator are here only, `exact::ExactCalculator` steps the lines with it too
+    pub fn step(&self, state: &LineState, ch: char) -> LineState {
+        if !state.alive {
+            return state.clone();
+        }
+        if self.breakers.contains(&ch) {
+            return LineState {
+                alive: false,
+                ..state.clone()
+            };
+        }
+        let w = if self.is_wild(state.symbol) {
+            ch
+        } else {
+            state.symbol
+        };
+        if w == ch || self.is_wild(ch) {
+            LineState {
+                symbol: w,
+                count: state.count + 1,
+                alive: true,
+            }
+        } else {
+            LineState {
+                symbol: w,
+                count: state.count,
+                alive: false,
+            }
+        }
+    }
+
+    /// symbols of the line in the order of `columns`
+    fn hit(&self, grid: &Vec<Vec<char>>, line: &Vec<usize>, columns: impl Iterator<Item = usize>) -> Option<LineHit> {
+        let mut columns = columns.peekable();
+        let first = *columns.peek()?;
+        let mut state = LineState::start(grid[first][line[first]]);
+        let mut positions = vec![];
+        for c in columns {
+            state = self.step(&state, grid[c][line[c]]);
+            if !state.alive {
+                break;
+            }
+            positions.push((c, line[c]));
+        }
+        Some(LineHit {
+            symbol: state.symbol,
+            positions,
+        })
+    }
+
+    /// hits of a line by the direction, a line of all the columns is paid once for both ways
+    pub fn eval_line(&self, grid: &Vec<Vec<char>>, line: &Vec<usize>) -> Vec<LineHit> {
+        let len = line.len();
+        let ltr = || self.hit(grid, line, 0..len);
+        let rtl = || self.hit(grid, line, (0..len).rev());
+        match self.direction {
+            PayDirection::LeftToRight => ltr().into_iter().collect(),
+            PayDirection::RightToLeft => rtl().into_iter().collect(),
+            PayDirection::BothWays => {
+                let left = ltr();
+                let full = left.as_ref().map(|h| h.positions.len() == len).unwrap_or(false);
+                let right = if full {
+                    None
+                } else {
+                    rtl()
+                };
+                left.into_iter().chain(right).collect()
+            }
+        }
+    }
+
+    fn multi(&self, grid: &Vec<Vec<char>>, hit: &LineHit) -> i32 {
+        hit.positions.iter().filter_map(|(c, r)| self.wild_mults.get(&grid[*c][*r])).product()
+    }
+
+    /// wins of the first `lines_played` lines, `line_bet` already includes the multipliers of the round
+    pub fn eval(&self, grid: &Vec<Vec<char>>, lines_played: usize, line_bet: i64) -> Vec<Gain> {
+        let columns = grid.len();
+        self.lines
+            .iter()
+            .take(lines_played)
+            .enumerate()
+            .flat_map(|(line_num, l)| {
+                self.eval_line(grid, l).into_iter().filter_map(move |hit| {
+                    let count = hit.positions.len();
+                    let factor = self.factor(hit.symbol, count);
+                    if factor > 0 {
+                        let multi = self.multi(grid, &hit);
+                        Some(Gain {
+                            symbol: hit.symbol,
+                            count,
+                            amount: factor as i64 * line_bet * multi as i64,
+                            line_num,
+                            multi,
+                            columns: Some(columns),
+                            indexes: hit.positions.iter().map(|(c, r)| r * columns + c).collect(),
+                            points: hit.positions.iter().map(|(c, r)| BasePoint { x: *c, y: *r }).collect(),
+                            ..Default::default()
+                        })
+                    } else {
+                        None
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// wins of the lines played by the request with the line bet of `req.bet`
+    pub fn eval_request(&self, req: &Request, grid: &Vec<Vec<char>>, multiplier: i32) -> Vec<Gain> {
+        self.eval(grid, req.line, req.bet as i64 * multiplier as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lines::{LineEvaluator, PayDirection};
+    use crate::protocol::BasePoint;
+    use std::collections::HashMap;
+
+    #[test]
+    pub fn test_line_evaluator() {
+        let lines = vec![vec![0, 0, 0], vec![1, 1, 1]];
+        let wins = HashMap::from([('A', HashMap::from([(2, 5), (3, 10)])), ('B', HashMap::from([(2, 3)]))]);
+        // columns of rows: A W B / B B A
+        let grid = vec![vec!['A', 'B'], vec!['W', 'B'], vec!['B', 'A']];
+        let evaluator = LineEvaluator::new(&lines, &wins).with_wild_mult('W', 2);
+
+        let gains = evaluator.eval(&grid, 2, 1);
+        assert_eq!(gains.len(), 2);
+        assert_eq!((gains[0].symbol, gains[0].count, gains[0].multi, gains[0].amount), ('A', 2, 2, 10));
+        assert_eq!(gains[0].points, vec![BasePoint { x: 0, y: 0 }, BasePoint { x: 1, y: 0 }]);
+        assert_eq!(gains[0].indexes, vec![0, 1]);
+        assert_eq!((gains[1].symbol, gains[1].line_num, gains[1].amount), ('B', 1, 3));
+        assert_eq!(gains[1].indexes, vec![3, 4]);
+        assert_eq!(evaluator.eval(&grid, 1, 1).len(), 1);
+
+        let gains = evaluator.clone().with_direction(PayDirection::RightToLeft).eval(&grid, 2, 1);
+        assert_eq!(gains.len(), 1);
+        assert_eq!((gains[0].symbol, gains[0].count, gains[0].amount), ('B', 2, 6));
+
+        let gains = evaluator.with_direction(PayDirection::BothWays).eval(&grid, 2, 1);
+        assert_eq!(gains.iter().map(|g| g.amount).sum::<i64>(), 19);
+    }
+}
@@ -1,10 +1,13 @@
+use crate::bonus::{BonusData, PickBoard};
 use crate::fsm::FSM;
+use crate::gamble::{GambleChoice, GambleData, GambleLimit, GambleState};
 use crate::protocol::{id, DatabaseStore, FreeGame, GameResult, SpinData};
 use crate::protocol::{GameData, Promo};
+use crate::rng::{DefaultSource, RngSource};
+use crate::seed::{action_seed, Seed};
 use crate::validator::{SimpleValidator, Validator};
 use essential_core::err_on;
 use essential_core::error::ServerError;
-use essential_rand::random::RandomGenerator;
 use fugaso_data::fugaso_action::ActionKind;
 use fugaso_data::fugaso_round::RoundDetail;
 use fugaso_data::{fugaso_action, fugaso_round};
@@ -119,6 +122,40 @@ pub trait SlotMath {
         Err(err_on!("respin is not supported!"))
     }
 
+    /// next cascade of the grid after the winning symbols of the previous result fall out
+    #[allow(unused_variables)]
+    fn drop(&mut self, request: &Self::Input, arg: SpinArg, step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        Err(err_on!("drop is not supported!"))
+    }
+
+    /// reveals the prize of the `pick` on the board of the bonus
+    #[allow(unused_variables)]
+    fn bonus(&mut self, request: &Self::Input, arg: SpinArg, step: &Step, pick: usize) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        Err(err_on!("bonus is not supported!"))
+    }
+
+    /// doubles up the win of the round by a card, the math opts in with `gamble::Gambler`
+    #[allow(unused_variables)]
+    fn gamble(&mut self, request: &Self::Input, arg: SpinArg, choice: GambleChoice, limit: &GambleLimit) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        Err(err_on!("gamble is not supported!"))
+    }
+
+    #[allow(unused_variables)]
+    fn half_collect(&mut self, request: &Self::Input, arg: SpinArg) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        Err(err_on!("half collect is not supported!"))
+    }
+
+    /// request of a bought bonus made from the request of the player, the math opts in with the bet counter of its buy
+    #[allow(unused_variables)]
+    fn buy_request(&self, request: &Self::Input) -> Result<Self::Input, ServerError> {
+        Err(err_on!("bonus buy is not supported!"))
+    }
+
+    /// spin of a bought bonus with the request of `buy_request`
+    fn buy_spin(&mut self, request: &Self::Input, arg: SpinArg, step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        self.spin(request, arg, step, combo)
+    }
+
     fn post_process(&mut self, kind: ActionKind, game_data: GameData<Self::Special, Self::Restore>) -> Result<Arc<GameData<Self::Special, Self::Restore>>, ServerError>;
 
     fn close(&self, next_act: ActionKind) -> Result<GameData<Self::Special, Self::Restore>, ServerError>;
@@ -137,6 +174,10 @@ pub trait SlotMath {
 
     #[allow(unused_variables)]
     fn set_rand(&mut self, rand: Self::Rand);
+
+    /// the draws of the next action come from the `seed` of `seed::Seed::action`, a math without its own rand ignores it
+    #[allow(unused_variables)]
+    fn reseed(&mut self, seed: Seed) {}
 }
 
 pub trait SlotBaseMath {
@@ -167,6 +208,42 @@ pub trait SlotBaseMath {
         let p = self.parent_mut();
         p.respin(request, arg, step, combo)
     }
+
+    fn drop(&mut self, request: &<Self::M as SlotMath>::Input, arg: SpinArg, step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<<Self::M as SlotMath>::Special, <Self::M as SlotMath>::Restore>, ServerError> {
+        let p = self.parent_mut();
+        p.drop(request, arg, step, combo)
+    }
+
+    fn bonus(&mut self, request: &<Self::M as SlotMath>::Input, arg: SpinArg, step: &Step, pick: usize) -> Result<GameData<<Self::M as SlotMath>::Special, <Self::M as SlotMath>::Restore>, ServerError> {
+        let p = self.parent_mut();
+        p.bonus(request, arg, step, pick)
+    }
+
+    fn gamble(&mut self, request: &<Self::M as SlotMath>::Input, arg: SpinArg, choice: GambleChoice, limit: &GambleLimit) -> Result<GameData<<Self::M as SlotMath>::Special, <Self::M as SlotMath>::Restore>, ServerError> {
+        let p = self.parent_mut();
+        p.gamble(request, arg, choice, limit)
+    }
+
+    fn half_collect(&mut self, request: &<Self::M as SlotMath>::Input, arg: SpinArg) -> Result<GameData<<Self::M as SlotMath>::Special, <Self::M as SlotMath>::Restore>, ServerError> {
+        let p = self.parent_mut();
+        p.half_collect(request, arg)
+    }
+
+    fn buy_request(&self, request: &<Self::M as SlotMath>::Input) -> Result<<Self::M as SlotMath>::Input, ServerError> {
+        let p = self.parent();
+        p.buy_request(request)
+    }
+
+    fn buy_spin(
+        &mut self,
+        request: &<Self::M as SlotMath>::Input,
+        arg: SpinArg,
+        step: &Step,
+        combo: Option<Vec<usize>>,
+    ) -> Result<GameData<<Self::M as SlotMath>::Special, <Self::M as SlotMath>::Restore>, ServerError> {
+        let p = self.parent_mut();
+        p.buy_spin(request, arg, step, combo)
+    }
 }
 
 impl<S: SlotBaseMath> SlotMath for S {
@@ -206,6 +283,30 @@ impl<S: SlotBaseMath> SlotMath for S {
         self.respin(request, arg, step, combo)
     }
 
+    fn drop(&mut self, request: &Self::Input, arg: SpinArg, step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        SlotBaseMath::drop(self, request, arg, step, combo)
+    }
+
+    fn bonus(&mut self, request: &Self::Input, arg: SpinArg, step: &Step, pick: usize) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        SlotBaseMath::bonus(self, request, arg, step, pick)
+    }
+
+    fn gamble(&mut self, request: &Self::Input, arg: SpinArg, choice: GambleChoice, limit: &GambleLimit) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        SlotBaseMath::gamble(self, request, arg, choice, limit)
+    }
+
+    fn half_collect(&mut self, request: &Self::Input, arg: SpinArg) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        SlotBaseMath::half_collect(self, request, arg)
+    }
+
+    fn buy_request(&self, request: &Self::Input) -> Result<Self::Input, ServerError> {
+        SlotBaseMath::buy_request(self, request)
+    }
+
+    fn buy_spin(&mut self, request: &Self::Input, arg: SpinArg, step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        SlotBaseMath::buy_spin(self, request, arg, step, combo)
+    }
+
     fn post_process(&mut self, kind: ActionKind, game_data: GameData<Self::Special, Self::Restore>) -> Result<Arc<GameData<Self::Special, Self::Restore>>, ServerError> {
         let p = self.parent_mut();
         p.post_process(kind, game_data)
@@ -221,6 +322,11 @@ impl<S: SlotBaseMath> SlotMath for S {
         p.collect(request, arg)
     }
 
+    fn create_fsm(&self, game_name: &str) -> Self::PlayFSM {
+        let p = self.parent();
+        p.create_fsm(game_name)
+    }
+
     fn settings(&self) -> MathSettings {
         let p = self.parent();
         p.settings()
@@ -230,6 +336,11 @@ impl<S: SlotBaseMath> SlotMath for S {
         let p = self.parent_mut();
         p.set_rand(rand)
     }
+
+    fn reseed(&mut self, seed: Seed) {
+        let p = self.parent_mut();
+        p.reseed(seed)
+    }
 }
 
 pub trait IRequest {
@@ -319,6 +430,11 @@ pub trait BetCalculator {
         self.calc_total_bet(request)
     }
 
+    /// price of a bonus buy, `multiplier` is the price of the buy in the playing bets
+    fn calc_buy_bet(&self, request: &Self::I, multiplier: i64) -> i64 {
+        self.calc_playing_bet(request) * multiplier
+    }
+
     fn calc_inputs(&self, game_play: GamePlayInput) -> Vec<GameInput> {
         let end = if game_play.denomination.len() > 2 {
             game_play.denomination.len()
@@ -487,20 +603,35 @@ impl Default for Step {
     }
 }
 
+impl Step {
+    /// the step repeats the results above the allowed win
+    pub fn is_limited(&self) -> bool {
+        self.take < MAX_TAKE || self.win < i64::MAX
+    }
+}
+
 const MAX_ATTEMPTS: usize = 100;
 pub struct ProxyMath<M: SlotMath> {
     imp: M,
-    rand: RandomGenerator,
+    rand: DefaultSource,
+    /// the results of the provably fair rounds are not repeated for the allowed win, so the seed alone gives them,
+    /// the admin turns the mode on only for a step without the limit or for the operators which waive it, see `FairConfig`
+    fair: bool,
 }
 
 impl<M: SlotMath> ProxyMath<M> {
     pub fn new(m: M) -> Self {
         Self {
             imp: m,
-            rand: RandomGenerator::new(),
+            rand: DefaultSource::from_entropy(),
+            fair: false,
         }
     }
 
+    pub fn set_fair(&mut self, fair: bool) {
+        self.fair = fair;
+    }
+
     fn calc_allowed(&mut self, step: &Step) -> i64 {
         if self.rand.random_usize(100) < step.take {
             step.win
@@ -541,6 +672,17 @@ impl<M: SlotMath> ProxyMath<M> {
         }
         Ok(result)
     }
+
+    fn play<F>(&mut self, step: &Step, mut f: F) -> Result<GameData<<ProxyMath<M> as SlotMath>::Special, <ProxyMath<M> as SlotMath>::Restore>, ServerError>
+    where
+        F: FnMut(&mut M) -> Result<GameData<<ProxyMath<M> as SlotMath>::Special, <ProxyMath<M> as SlotMath>::Restore>, ServerError>,
+    {
+        if self.fair {
+            return f(&mut self.imp);
+        }
+        let allowed = self.calc_allowed(step);
+        Self::run(allowed, &mut self.imp, f)
+    }
 }
 
 impl<M: SlotMath> SlotMath for ProxyMath<M> {
@@ -567,21 +709,40 @@ impl<M: SlotMath> SlotMath for ProxyMath<M> {
     }
 
     fn spin(&mut self, request: &Self::Input, arg: SpinArg, step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
-        let allowed = self.calc_allowed(step);
-        let f = |m: &mut M| m.spin(request, arg.clone(), step, combo.clone());
-        Self::run(allowed, &mut self.imp, f)
+        self.play(step, |m: &mut M| m.spin(request, arg.clone(), step, combo.clone()))
     }
 
     fn free_spin(&mut self, request: &Self::Input, arg: SpinArg, step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
-        let allowed = self.calc_allowed(step);
-        let f = |m: &mut M| m.free_spin(request, arg.clone(), step, combo.clone());
-        Self::run(allowed, &mut self.imp, f)
+        self.play(step, |m: &mut M| m.free_spin(request, arg.clone(), step, combo.clone()))
     }
 
     fn respin(&mut self, request: &Self::Input, arg: SpinArg, step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
-        let allowed = self.calc_allowed(step);
-        let f = |m: &mut M| m.respin(request, arg.clone(), step, combo.clone());
-        Self::run(allowed, &mut self.imp, f)
+        self.play(step, |m: &mut M| m.respin(request, arg.clone(), step, combo.clone()))
+    }
+
+    fn drop(&mut self, request: &Self::Input, arg: SpinArg, step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        self.play(step, |m: &mut M| m.drop(request, arg.clone(), step, combo.clone()))
+    }
+
+    /// the prizes of the board are dealt already, so a pick is not repeated for the allowed win
+    fn bonus(&mut self, request: &Self::Input, arg: SpinArg, step: &Step, pick: usize) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        self.imp.bonus(request, arg, step, pick)
+    }
+
+    fn gamble(&mut self, request: &Self::Input, arg: SpinArg, choice: GambleChoice, limit: &GambleLimit) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        self.imp.gamble(request, arg, choice, limit)
+    }
+
+    fn half_collect(&mut self, request: &Self::Input, arg: SpinArg) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        self.imp.half_collect(request, arg)
+    }
+
+    fn buy_request(&self, request: &Self::Input) -> Result<Self::Input, ServerError> {
+        self.imp.buy_request(request)
+    }
+
+    fn buy_spin(&mut self, request: &Self::Input, arg: SpinArg, step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        self.play(step, |m: &mut M| m.buy_spin(request, arg.clone(), step, combo.clone()))
     }
 
     fn post_process(&mut self, kind: ActionKind, game_data: GameData<Self::Special, Self::Restore>) -> Result<Arc<GameData<Self::Special, Self::Restore>>, ServerError> {
@@ -611,6 +772,14 @@ impl<M: SlotMath> SlotMath for ProxyMath<M> {
     fn set_rand(&mut self, rand: Self::Rand) {
         self.imp.set_rand(rand)
     }
+
+    /// the retries of the allowed win take their own stream of the seed, the fair rounds are not retried
+    fn reseed(&mut self, seed: Seed) {
+        if let Seed::Plain(s) = seed {
+            self.rand.reseed(action_seed(s, 0).into());
+        }
+        self.imp.reseed(seed)
+    }
 }
 
 pub struct ReplayMath<M: SlotMath> {
@@ -648,6 +817,10 @@ impl<M: SlotMath> ReplayMath<M> {
                 if a.act_descr == Some(ActionKind::BET)
                     || a.act_descr == Some(ActionKind::RESPIN)
                     || a.act_descr == Some(ActionKind::FREE_SPIN)
+                    || a.act_descr == Some(ActionKind::DROP)
+                    || a.act_descr == Some(ActionKind::BONUS)
+                    || a.act_descr == Some(ActionKind::GAMBLE_PLAY)
+                    || a.act_descr == Some(ActionKind::HALF_COLLECT)
                     || a.act_descr == Some(ActionKind::COLLECT)
                 {
                     let mut next_act = a.next_act.clone().ok_or_else(|| err_on!("next action is none!"))?;
@@ -693,6 +866,19 @@ impl<M: SlotMath> ReplayMath<M> {
                         Ok(Some(GameData::ReSpin(spin_data)))
                     } else if a.act_descr == Some(ActionKind::FREE_SPIN) {
                         Ok(Some(GameData::FreeSpin(spin_data)))
+                    } else if a.act_descr == Some(ActionKind::DROP) {
+                        Ok(Some(GameData::Drop(spin_data)))
+                    } else if a.act_descr == Some(ActionKind::BONUS) {
+                        let board = a.choice.as_ref().map_or_else(|| Ok(PickBoard::default()), |c| PickBoard::from_choice(c))?;
+                        Ok(Some(GameData::Bonus(BonusData::new(spin_data, board))))
+                    } else if a.act_descr == Some(ActionKind::GAMBLE_PLAY) || a.act_descr == Some(ActionKind::HALF_COLLECT) {
+                        let gamble = a.cards.as_ref().map_or_else(|| Ok(GambleState::default()), |c| GambleState::from_db(c))?;
+                        let mut spin = spin_data;
+                        spin.result.cards = gamble.history.clone();
+                        Ok(Some(GameData::Gamble(GambleData {
+                            spin,
+                            gamble,
+                        })))
                     } else if a.act_descr == Some(ActionKind::COLLECT) {
                         Ok(Some(GameData::Collect(spin_data)))
                     } else {
@@ -720,3 +906,69 @@ impl<M: SlotMath> ReplayMath<M> {
         Ok(next)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::configurable::ConfigurableSlotMath;
+    use crate::math::{ProxyMath, Request, SlotMath, SpinArg, Step};
+    use crate::protocol::Promo;
+    use crate::seed::{action_seed, ActionRecord};
+    use fugaso_data::fugaso_action::ActionKind;
+    use fugaso_data::fugaso_round::RoundDetail;
+
+    #[test]
+    fn test_fair_math() {
+        let json = r#"{
+            "cols": 3,
+            "rows": 3,
+            "reels": [["AABK", "ABBK", "AKBK"]],
+            "evaluator": "LINES",
+            "lines": ["---", "^^^", "___"],
+            "wins": {"A": {"3": 5}, "B": {"3": 10}, "K": {"3": 2}},
+            "maxWin": 1000
+        }"#;
+        let request = Request {
+            bet: 1,
+            line: 3,
+            denom: 1,
+            bet_counter: 1,
+            ..Default::default()
+        };
+        let arg = SpinArg {
+            balance: 1_000,
+            round_id: 1,
+            round_type: RoundDetail::SIMPLE,
+            round_multiplier: 1,
+            next_act: ActionKind::BET,
+            promo: Promo::default(),
+            stake: 3,
+        };
+        // no win is allowed by the step, so the wins of the other rounds are played again
+        let step = Step {
+            win: 0,
+            take: 100,
+        };
+        let mut repeated = 0;
+        for seed in 0..50 {
+            let mut plain = ConfigurableSlotMath::new(json).unwrap();
+            plain.reseed(action_seed(seed, 0).into());
+            let expected = plain.spin(&request, arg.clone(), &step, None).unwrap();
+            let expected = ActionRecord::new(&expected.create_action_default().unwrap());
+
+            // the result of a fair round is the one of its seed whatever the step is
+            let mut fair = ProxyMath::new(ConfigurableSlotMath::new(json).unwrap());
+            fair.set_fair(true);
+            fair.reseed(action_seed(seed, 0).into());
+            let result = fair.spin(&request, arg.clone(), &step, None).unwrap();
+            assert_eq!(ActionRecord::new(&result.create_action_default().unwrap()), expected);
+
+            let mut other = ProxyMath::new(ConfigurableSlotMath::new(json).unwrap());
+            other.reseed(action_seed(seed, 0).into());
+            let result = other.spin(&request, arg.clone(), &step, None).unwrap();
+            if ActionRecord::new(&result.create_action_default().unwrap()) != expected {
+                repeated += 1;
+            }
+        }
+        assert!(repeated > 0);
+    }
+}
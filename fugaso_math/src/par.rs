@@ -0,0 +1,265 @@
+use crate::config::ReelDist;
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+
+/// symbols of the `-^_V` notation of the lines by rows
+pub const LINE_SYMBOLS: [char; 4] = ['^', '-', '_', 'V'];
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReelStat {
+    pub stops: usize,
+    pub total_weight: u64,
+    /// stops of a strip or weights of the windows of a distribution with the symbol
+    pub symbols: BTreeMap<char, u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReelSet {
+    pub category: usize,
+    pub combinations: u128,
+    pub total_weight: u128,
+    pub reels: Vec<ReelStat>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayEntry {
+    pub symbol: char,
+    pub count: usize,
+    pub factor: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineDef {
+    pub number: usize,
+    pub notation: String,
+    pub rows: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DistEntry {
+    pub value: serde_json::Value,
+    pub weight: i32,
+    pub probability: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedDist {
+    pub name: String,
+    pub category: Option<usize>,
+    pub entries: Vec<DistEntry>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParSheet {
+    pub game: String,
+    pub reels: Vec<ReelSet>,
+    pub reel_dists: Vec<ReelSet>,
+    pub paytable: Vec<PayEntry>,
+    pub lines: Vec<LineDef>,
+    pub dists: Vec<NamedDist>,
+}
+
+fn reel_set(category: usize, reels: Vec<ReelStat>) -> ReelSet {
+    ReelSet {
+        category,
+        combinations: reels.iter().map(|r| r.stops as u128).product(),
+        total_weight: reels.iter().map(|r| r.total_weight as u128).product(),
+        reels,
+    }
+}
+
+/// the keys of a distribution are cumulative bounds like in `RandomGenerator::rand_value`
+pub fn normalize<V: Serialize>(dist: &BTreeMap<i32, V>) -> Result<Vec<DistEntry>, ServerError> {
+    let total = *dist.keys().last().ok_or_else(|| err_on!("distribution is empty!"))? as f64;
+    let mut prev = 0;
+    dist.iter()
+        .map(|(bound, value)| {
+            if *bound <= prev {
+                return Err(err_on!(format!("illegal weight bound {bound}!")));
+            }
+            let weight = *bound - prev;
+            prev = *bound;
+            Ok(DistEntry {
+                value: serde_json::to_value(value).map_err(|e| err_on!(e))?,
+                weight,
+                probability: weight as f64 / total,
+            })
+        })
+        .collect()
+}
+
+impl ParSheet {
+    pub fn new(game: &str) -> Self {
+        Self {
+            game: game.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_reels(mut self, reels: &Vec<Vec<Vec<char>>>) -> Self {
+        self.reels = reels
+            .iter()
+            .enumerate()
+            .map(|(category, strips)| {
+                let stats = strips
+                    .iter()
+                    .map(|strip| {
+                        let mut symbols = BTreeMap::new();
+                        strip.iter().for_each(|c| *symbols.entry(*c).or_insert(0) += 1);
+                        ReelStat {
+                            stops: strip.len(),
+                            total_weight: strip.len() as u64,
+                            symbols,
+                        }
+                    })
+                    .collect();
+                reel_set(category, stats)
+            })
+            .collect();
+        self
+    }
+
+    pub fn with_reel_dist(mut self, dist: &ReelDist) -> Result<Self, ServerError> {
+        self.reel_dists = dist
+            .iter()
+            .enumerate()
+            .map(|(category, columns)| {
+                let stats = columns
+                    .iter()
+                    .map(|column| {
+                        let mut symbols = BTreeMap::new();
+                        let mut prev = 0;
+                        for (bound, window) in column.iter() {
+                            if *bound <= prev {
+                                return Err(err_on!(format!("illegal weight bound {bound} of category {category}!")));
+                            }
+                            window.iter().for_each(|c| *symbols.entry(*c).or_insert(0) += (*bound - prev) as u64);
+                            prev = *bound;
+                        }
+                        Ok(ReelStat {
+                            stops: column.len(),
+                            total_weight: prev as u64,
+                            symbols,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(reel_set(category, stats))
+            })
+            .collect::<Result<Vec<_>, ServerError>>()?;
+        Ok(self)
+    }
+
+    pub fn with_paytable(mut self, wins: &HashMap<char, HashMap<usize, i32>>) -> Self {
+        let mut paytable = wins
+            .iter()
+            .flat_map(|(symbol, m)| {
+                m.iter().map(|(count, factor)| PayEntry {
+                    symbol: *symbol,
+                    count: *count,
+                    factor: *factor,
+                })
+            })
+            .collect::<Vec<_>>();
+        paytable.sort_by_key(|p| (p.symbol, p.count));
+        self.paytable = paytable;
+        self
+    }
+
+    pub fn with_lines(mut self, lines: &Vec<Vec<usize>>) -> Self {
+        self.lines = lines
+            .iter()
+            .enumerate()
+            .map(|(i, rows)| LineDef {
+                number: i + 1,
+                notation: rows.iter().map(|r| LINE_SYMBOLS.get(*r).cloned().unwrap_or('?')).collect(),
+                rows: rows.clone(),
+            })
+            .collect();
+        self
+    }
+
+    pub fn add_dist<V: Serialize>(&mut self, name: &str, category: Option<usize>, dist: &BTreeMap<i32, V>) -> Result<(), ServerError> {
+        let entries = normalize(dist).map_err(|e| err_on!(format!("{name}: {e:?}")))?;
+        self.dists.push(NamedDist {
+            name: name.to_string(),
+            category,
+            entries,
+        });
+        Ok(())
+    }
+
+    pub fn add_dists<V: Serialize>(&mut self, name: &str, dists: &[BTreeMap<i32, V>]) -> Result<(), ServerError> {
+        for (category, dist) in dists.iter().enumerate() {
+            self.add_dist(name, Some(category), dist)?;
+        }
+        Ok(())
+    }
+
+    /// chance `(hits, total)` of the tuple distributions like `dist_coin` of `MegaThunderConfig`
+    pub fn add_chance(&mut self, name: &str, chance: (i32, i32)) -> Result<(), ServerError> {
+        if chance.1 <= 0 {
+            return Err(err_on!(format!("{name}: illegal total {}!", chance.1)));
+        }
+        let entries = [(true, chance.0), (false, chance.1 - chance.0)]
+            .into_iter()
+            .map(|(value, weight)| DistEntry {
+                value: serde_json::Value::Bool(value),
+                weight,
+                probability: weight as f64 / chance.1 as f64,
+            })
+            .collect();
+        self.dists.push(NamedDist {
+            name: name.to_string(),
+            category: None,
+            entries,
+        });
+        Ok(())
+    }
+}
+
+fn fmt_reel_sets(f: &mut Formatter<'_>, title: &str, sets: &Vec<ReelSet>) -> std::fmt::Result {
+    for set in sets.iter() {
+        write!(f, "\n{title} category {}: combinations {} total weight {}", set.category, set.combinations, set.total_weight)?;
+        for (i, reel) in set.reels.iter().enumerate() {
+            let symbols = reel.symbols.iter().map(|(c, n)| format!("{c}:{n}")).collect::<Vec<_>>().join(" ");
+            write!(f, "\n  reel {} stops {} weight {} | {symbols}", i + 1, reel.stops, reel.total_weight)?;
+        }
+    }
+    Ok(())
+}
+
+impl Display for ParSheet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "game: {}", self.game)?;
+        fmt_reel_sets(f, "reels", &self.reels)?;
+        fmt_reel_sets(f, "reel dist", &self.reel_dists)?;
+        write!(f, "\npaytable:")?;
+        for p in self.paytable.iter() {
+            write!(f, "\n  {} x{} {}", p.symbol, p.count, p.factor)?;
+        }
+        write!(f, "\nlines:")?;
+        for l in self.lines.iter() {
+            write!(f, "\n  {:>3} {} {:?}", l.number, l.notation, l.rows)?;
+        }
+        for d in self.dists.iter() {
+            match d.category {
+                Some(c) => write!(f, "\n{} [{c}]:", d.name)?,
+                None => write!(f, "\n{}:", d.name)?,
+            }
+            for e in d.entries.iter() {
+                write!(f, "\n  {:<10} {:>10} {:.8}", e.value.to_string(), e.weight, e.probability)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use crate::bonus::BonusData;
+use crate::gamble::GambleData;
 use crate::math::IPlayResponse;
 use essential_async::channel::UnboundedSender;
 use essential_core::err_on;
@@ -5,7 +7,7 @@ use essential_core::error::message::ILLEGAL_ARGUMENT;
 use essential_core::error::ServerError;
 use fugaso_data::fugaso_action;
 use fugaso_data::fugaso_action::ActionKind;
-use fugaso_data::fugaso_action::ActionKind::RESPIN;
+use fugaso_data::fugaso_action::ActionKind::{BONUS, DROP, RESPIN};
 use fugaso_data::fugaso_round::RoundDetail;
 use serde::ser::SerializeSeq;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
@@ -34,6 +36,8 @@ pub mod id {
     pub const GAME_DATA: i32 = 6;
     pub const TOURNAMENT_INFO: i32 = 261;
     pub const HISTORY: i32 = 256;
+    pub const FAIR_SEED: i32 = 262;
+    pub const FAIR_VERIFY: i32 = 263;
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -49,6 +53,12 @@ pub enum GameData<S: DatabaseStore + Default, R: Default> {
     Collect(SpinData<S, R>),
     #[serde(rename = "FREE_SPIN")]
     FreeSpin(SpinData<S, R>),
+    #[serde(rename = "DROP")]
+    Drop(SpinData<S, R>),
+    #[serde(rename = "BONUS")]
+    Bonus(BonusData<S, R>),
+    #[serde(rename = "GAMBLE")]
+    Gamble(GambleData<S, R>),
 }
 
 impl<S: DatabaseStore + Default + 'static, R: Default + 'static> Deref for GameData<S, R> {
@@ -61,6 +71,9 @@ impl<S: DatabaseStore + Default + 'static, R: Default + 'static> Deref for GameD
             GameData::FreeSpin(v) => v,
             GameData::ReSpin(v) => v,
             GameData::Collect(v) => v,
+            GameData::Drop(v) => v,
+            GameData::Bonus(v) => v,
+            GameData::Gamble(v) => v,
         }
     }
 }
@@ -73,6 +86,9 @@ impl<S: DatabaseStore + Default + 'static, R: Default + 'static> DerefMut for Ga
             GameData::Spin(v) => v,
             GameData::FreeSpin(v) => v,
             GameData::Collect(v) => v,
+            GameData::Drop(v) => v,
+            GameData::Bonus(v) => v,
+            GameData::Gamble(v) => v,
         }
     }
 }
@@ -115,7 +131,7 @@ impl<S: DatabaseStore + Default, R: Default + 'static> IPlayResponse for SpinDat
     }
 
     fn has_bonus(&self) -> bool {
-        false
+        self.next_act == BONUS
     }
 
     fn has_respin(&self) -> bool {
@@ -123,7 +139,7 @@ impl<S: DatabaseStore + Default, R: Default + 'static> IPlayResponse for SpinDat
     }
 
     fn has_drop(&self) -> bool {
-        false
+        self.next_act == DROP
     }
 
     fn total(&self) -> i64 {
@@ -285,6 +301,20 @@ pub struct FreeGame {
     pub initial: i32,
     pub left: i32,
     pub done: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<FreeLevel>,
+    /// wilds which stay on the grid for the rest of the free spins
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sticky: Vec<BasePoint>,
+}
+
+/// level of the progressive multiplier of the free spins, `progress` goes to the next level
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FreeLevel {
+    pub level: usize,
+    pub progress: usize,
+    pub multi: i32,
 }
 
 impl Default for FreeGame {
@@ -296,6 +326,8 @@ impl Default for FreeGame {
             initial: 0,
             left: 0,
             done: 0,
+            level: None,
+            sticky: vec![],
         }
     }
 }
@@ -339,6 +371,30 @@ impl DatabaseStore for FreeGame {
                 } else if "category" == p[0] {
                     free_game.category =
                         p[1].parse::<usize>().map_err(|e| err_on!(e.to_string()))?;
+                } else if "level" == p[0] {
+                    let l = p[1]
+                        .split(":")
+                        .map(|v| v.parse::<i64>().map_err(|e| err_on!(e.to_string())))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if l.len() != 3 {
+                        return Err(err_on!("error parse free level!"));
+                    }
+                    free_game.level = Some(FreeLevel {
+                        level: l[0] as usize,
+                        progress: l[1] as usize,
+                        multi: l[2] as i32,
+                    });
+                } else if "sticky" == p[0] {
+                    free_game.sticky = p[1]
+                        .split(",")
+                        .map(|v| match v.split_once(":") {
+                            Some((x, y)) => Ok(BasePoint {
+                                x: x.parse::<usize>().map_err(|e| err_on!(e.to_string()))?,
+                                y: y.parse::<usize>().map_err(|e| err_on!(e.to_string()))?,
+                            }),
+                            None => Err(err_on!("error parse sticky!")),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
                 }
             } else {
                 return Err(err_on!("error parse free_game!"));
@@ -348,10 +404,18 @@ impl DatabaseStore for FreeGame {
     }
 
     fn to_db(&self) -> Result<String, ServerError> {
-        Ok(format!(
+        let mut value = format!(
             "left={}|done={}|initial={}|symbol={}|totalWin={}|category={}",
             self.left, self.done, self.initial, self.symbol, self.total_win, self.category
-        ))
+        );
+        if let Some(l) = &self.level {
+            value.push_str(&format!("|level={}:{}:{}", l.level, l.progress, l.multi));
+        }
+        if !self.sticky.is_empty() {
+            let sticky = self.sticky.iter().map(|p| format!("{}:{}", p.x, p.y)).collect::<Vec<_>>().join(",");
+            value.push_str(&format!("|sticky={sticky}"));
+        }
+        Ok(value)
     }
 }
 
@@ -598,7 +662,7 @@ impl DatabaseStore for MultiplierInfo {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct Card {
     pub symbol: String,
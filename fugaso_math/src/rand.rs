@@ -1,8 +1,11 @@
+use crate::cascade;
 use crate::config::{ BaseConfig, ReelDist,
 };
+use crate::rng::{DefaultSource, RngSource};
+use crate::seed::Seed;
 use essential_rand::error::RandError;
 use essential_rand::err_on;
-use essential_rand::random::RandomGenerator;
+use std::collections::BTreeSet;
 use std::sync::Arc;
 
 pub type Result<T> = std::result::Result<T, RandError>;
@@ -13,15 +16,35 @@ pub trait ReelRandom {
         category: usize,
         combos: Option<Vec<usize>>,
     ) -> (Vec<usize>, Vec<Vec<char>>);
+
+    /// stops and grid after the `cells` fall out and the strips of the category fill the columns,
+    /// only the games with the cascades implement it
+    #[allow(unused_variables)]
+    fn refill_cols(
+        &self,
+        category: usize,
+        stops: &Vec<usize>,
+        grid: &Vec<Vec<char>>,
+        cells: &BTreeSet<(usize, usize)>,
+    ) -> Result<(Vec<usize>, Vec<Vec<char>>)> {
+        Err(err_on!("refill is not supported!"))
+    }
 }
 
-pub struct BaseRandom<C: BaseConfig> {
-    pub rand: RandomGenerator,
+pub struct BaseRandom<C: BaseConfig, R: RngSource = DefaultSource> {
+    pub rand: R,
     pub rows: usize,
     pub config: Arc<C>,
 }
 
-impl<C: BaseConfig> ReelRandom for BaseRandom<C> {
+impl<C: BaseConfig, R: RngSource> BaseRandom<C, R> {
+    /// the next draws come from the seed of the action, see `seed::Seed`
+    pub fn reseed(&mut self, seed: Seed) {
+        self.rand.reseed(seed);
+    }
+}
+
+impl<C: BaseConfig, R: RngSource> ReelRandom for BaseRandom<C, R> {
     fn rand_cols(
         &mut self,
         category: usize,
@@ -50,14 +73,25 @@ impl<C: BaseConfig> ReelRandom for BaseRandom<C> {
             .collect::<Vec<_>>();
         (stops, grid)
     }
+
+    fn refill_cols(
+        &self,
+        category: usize,
+        stops: &Vec<usize>,
+        grid: &Vec<Vec<char>>,
+        cells: &BTreeSet<(usize, usize)>,
+    ) -> Result<(Vec<usize>, Vec<Vec<char>>)> {
+        let reels = self.config.reels().get(category).ok_or_else(|| err_on!("category is absent!"))?;
+        Ok(cascade::refill(reels, stops, grid, cells))
+    }
 }
 
-pub struct GroupRandom<C: BaseConfig> {
+pub struct GroupRandom<C: BaseConfig, R: RngSource = DefaultSource> {
     pub reels_cfg: Arc<ReelDist>,
-    pub base: BaseRandom<C>,
+    pub base: BaseRandom<C, R>,
 }
 
-impl<C: BaseConfig> GroupRandom<C> {
+impl<C: BaseConfig, R: RngSource> GroupRandom<C, R> {
     pub fn rand_cols_group(
         &mut self,
         category: usize,
@@ -106,6 +140,10 @@ impl<C: BaseConfig> GroupRandom<C> {
         (stops, grid)
     }
 
+    pub fn reseed(&mut self, seed: Seed) {
+        self.base.reseed(seed)
+    }
+
     pub fn config(&self)-> &C {
         &self.base.config
     }
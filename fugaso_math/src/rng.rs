@@ -0,0 +1,593 @@
+use crate::fair::key_seed;
+use crate::rand::Result;
+use crate::seed::Seed;
+use essential_rand::err_on;
+use essential_rand::random::RandomGenerator;
+use rand_chacha::ChaCha20Rng;
+use rand_core::{RngCore, SeedableRng};
+use std::collections::BTreeMap;
+
+/// seed of the stream `index` of the seed by splitmix64, the streams of a seed are independent,
+/// the actions of a round and the chunks of a simulation take their seeds by it
+pub fn split_seed(seed: u64, index: u64) -> u64 {
+    let mut z = seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// source of the numbers of the game rands, the labs certify the algorithm of the source,
+/// so the games take it by `DefaultSource` and the algorithm of the seeds is switched by the features of the crate.
+/// The sources make every draw from `below`, only `RandomGenerator` keeps its own draws
+pub trait RngSource: Sized {
+    fn from_seed(seed: u64) -> Self;
+
+    fn from_entropy() -> Self;
+
+    /// ChaCha20 of the key of a provably fair round on the stream of the action, see `fair::fair_key`,
+    /// the games draw from `GameSource` which is ChaCha20 for the keys, the other sources take the seed of the head of the key
+    fn from_key(key: [u8; 32], stream: u64) -> Self {
+        Self::from_seed(split_seed(key_seed(&key), stream))
+    }
+
+    fn next_u64(&mut self) -> u64;
+
+    /// the next draws come from the seed, see `seed::Seed`
+    fn reseed(&mut self, seed: Seed) {
+        *self = match seed {
+            Seed::Plain(s) => Self::from_seed(s),
+            Seed::Fair {
+                key,
+                stream,
+            } => Self::from_key(key, stream),
+        };
+    }
+
+    /// number of `[0, n)` without the modulo bias, 0 for the empty range
+    fn below(&mut self, n: u64) -> u64 {
+        if n == 0 {
+            return 0;
+        }
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let v = self.next_u64();
+            if v < zone {
+                return v % n;
+            }
+        }
+    }
+
+    fn range_usize(&mut self, min: usize, max: usize) -> usize {
+        min + self.below(max.saturating_sub(min) as u64) as usize
+    }
+
+    fn range_i32(&mut self, min: i32, max: i32) -> i32 {
+        (min as i64 + self.below((max as i64 - min as i64).max(0) as u64) as i64) as i32
+    }
+
+    /// number of `[min, max)`
+    fn random<T: RandInt>(&mut self, min: T, max: T) -> T {
+        T::draw(self, min, max)
+    }
+
+    fn random_usize(&mut self, n: usize) -> usize {
+        self.range_usize(0, n)
+    }
+
+    fn random_i64(&mut self) -> i64 {
+        self.next_u64() as i64
+    }
+
+    fn rand_value<V: Copy>(&mut self, dist: &BTreeMap<i32, V>) -> Result<V> {
+        self.rand_value_clone(dist).map(|p| p.1)
+    }
+
+    /// index and value of the distribution, the keys are cumulative bounds
+    fn rand_value_clone<V: Clone>(&mut self, dist: &BTreeMap<i32, V>) -> Result<(usize, V)> {
+        let total = dist.keys().last().cloned().filter(|t| *t > 0).ok_or_else(|| err_on!("distribution is empty!"))?;
+        let r = self.range_i32(0, total);
+        dist.iter().enumerate().find(|(_, (b, _))| r < **b).map(|(i, (_, v))| (i, v.clone())).ok_or_else(|| err_on!("illegal distribution!"))
+    }
+
+    fn rand_vec<T: Copy>(&mut self, values: &Vec<T>) -> Result<T> {
+        if values.is_empty() {
+            return Err(err_on!("vector is empty!"));
+        }
+        Ok(values[self.range_usize(0, values.len())])
+    }
+
+    fn rand_vec_remove<T>(&mut self, values: &mut Vec<T>) -> Result<T> {
+        if values.is_empty() {
+            return Err(err_on!("vector is empty!"));
+        }
+        let i = self.range_usize(0, values.len());
+        Ok(values.remove(i))
+    }
+}
+
+/// integers of `RngSource::random`
+pub trait RandInt: Copy {
+    fn draw<R: RngSource>(rand: &mut R, min: Self, max: Self) -> Self;
+}
+
+impl RandInt for usize {
+    fn draw<R: RngSource>(rand: &mut R, min: Self, max: Self) -> Self {
+        rand.range_usize(min, max)
+    }
+}
+
+impl RandInt for i32 {
+    fn draw<R: RngSource>(rand: &mut R, min: Self, max: Self) -> Self {
+        rand.range_i32(min, max)
+    }
+}
+
+#[cfg(not(feature = "chacha"))]
+pub type CertifiedSource = RandomGenerator;
+
+#[cfg(feature = "chacha")]
+pub type CertifiedSource = ChaChaSource;
+
+pub type DefaultSource = GameSource;
+
+/// source of the games, the seeds give the certified source of the features and the keys of the provably fair rounds give ChaCha20,
+/// so a fair round is drawn by ChaCha20 whatever the features of the crate are
+pub enum GameSource {
+    Certified(CertifiedSource),
+    Fair(ChaChaSource),
+}
+
+impl RngSource for GameSource {
+    fn from_seed(seed: u64) -> Self {
+        GameSource::Certified(<CertifiedSource as RngSource>::from_seed(seed))
+    }
+
+    fn from_entropy() -> Self {
+        GameSource::Certified(<CertifiedSource as RngSource>::from_entropy())
+    }
+
+    fn from_key(key: [u8; 32], stream: u64) -> Self {
+        GameSource::Fair(ChaChaSource::from_key(key, stream))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            GameSource::Certified(r) => r.next_u64(),
+            GameSource::Fair(r) => r.next_u64(),
+        }
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        match self {
+            GameSource::Certified(r) => r.below(n),
+            GameSource::Fair(r) => r.below(n),
+        }
+    }
+
+    fn range_usize(&mut self, min: usize, max: usize) -> usize {
+        match self {
+            GameSource::Certified(r) => r.range_usize(min, max),
+            GameSource::Fair(r) => r.range_usize(min, max),
+        }
+    }
+
+    fn range_i32(&mut self, min: i32, max: i32) -> i32 {
+        match self {
+            GameSource::Certified(r) => r.range_i32(min, max),
+            GameSource::Fair(r) => r.range_i32(min, max),
+        }
+    }
+
+    fn random_usize(&mut self, n: usize) -> usize {
+        match self {
+            GameSource::Certified(r) => r.random_usize(n),
+            GameSource::Fair(r) => r.random_usize(n),
+        }
+    }
+
+    fn random_i64(&mut self) -> i64 {
+        match self {
+            GameSource::Certified(r) => r.random_i64(),
+            GameSource::Fair(r) => r.random_i64(),
+        }
+    }
+
+    fn rand_value<V: Copy>(&mut self, dist: &BTreeMap<i32, V>) -> Result<V> {
+        match self {
+            GameSource::Certified(r) => r.rand_value(dist),
+            GameSource::Fair(r) => r.rand_value(dist),
+        }
+    }
+
+    fn rand_value_clone<V: Clone>(&mut self, dist: &BTreeMap<i32, V>) -> Result<(usize, V)> {
+        match self {
+            GameSource::Certified(r) => r.rand_value_clone(dist),
+            GameSource::Fair(r) => r.rand_value_clone(dist),
+        }
+    }
+
+    fn rand_vec<T: Copy>(&mut self, values: &Vec<T>) -> Result<T> {
+        match self {
+            GameSource::Certified(r) => r.rand_vec(values),
+            GameSource::Fair(r) => r.rand_vec(values),
+        }
+    }
+
+    fn rand_vec_remove<T>(&mut self, values: &mut Vec<T>) -> Result<T> {
+        match self {
+            GameSource::Certified(r) => r.rand_vec_remove(values),
+            GameSource::Fair(r) => r.rand_vec_remove(values),
+        }
+    }
+}
+
+/// the current generator keeps its own draws, so the certified games give the same numbers
+impl RngSource for RandomGenerator {
+    fn from_seed(seed: u64) -> Self {
+        RandomGenerator::from_seed(seed)
+    }
+
+    fn from_entropy() -> Self {
+        RandomGenerator::new()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RandomGenerator::random_i64(self) as u64
+    }
+
+    fn range_usize(&mut self, min: usize, max: usize) -> usize {
+        RandomGenerator::random(self, min, max)
+    }
+
+    fn range_i32(&mut self, min: i32, max: i32) -> i32 {
+        RandomGenerator::random(self, min, max)
+    }
+
+    fn random_usize(&mut self, n: usize) -> usize {
+        RandomGenerator::random_usize(self, n)
+    }
+
+    fn random_i64(&mut self) -> i64 {
+        RandomGenerator::random_i64(self)
+    }
+
+    fn rand_value<V: Copy>(&mut self, dist: &BTreeMap<i32, V>) -> Result<V> {
+        RandomGenerator::rand_value(self, dist)
+    }
+
+    fn rand_value_clone<V: Clone>(&mut self, dist: &BTreeMap<i32, V>) -> Result<(usize, V)> {
+        RandomGenerator::rand_value_clone(self, dist)
+    }
+
+    fn rand_vec<T: Copy>(&mut self, values: &Vec<T>) -> Result<T> {
+        RandomGenerator::rand_vec(self, values)
+    }
+
+    fn rand_vec_remove<T>(&mut self, values: &mut Vec<T>) -> Result<T> {
+        RandomGenerator::rand_vec_remove(self, values)
+    }
+}
+
+/// ChaCha20 stream cipher as a cryptographically secure generator
+pub struct ChaChaSource(ChaCha20Rng);
+
+impl RngSource for ChaChaSource {
+    fn from_seed(seed: u64) -> Self {
+        Self(ChaCha20Rng::seed_from_u64(seed))
+    }
+
+    fn from_entropy() -> Self {
+        Self(ChaCha20Rng::from_entropy())
+    }
+
+    /// the key is the 32 bytes of the ChaCha20 key and the stream is the 64-bit nonce of ChaCha20, the counter starts from 0
+    fn from_key(key: [u8; 32], stream: u64) -> Self {
+        let mut rng = ChaCha20Rng::from_seed(key);
+        rng.set_stream(stream);
+        Self(rng)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+}
+
+/// the number `i` of the stream is `split_seed(key, i)`, so any number of a test is known without the draws before it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CounterSource {
+    pub key: u64,
+    pub counter: u64,
+}
+
+impl RngSource for CounterSource {
+    fn from_seed(seed: u64) -> Self {
+        Self {
+            key: seed,
+            counter: 0,
+        }
+    }
+
+    fn from_entropy() -> Self {
+        Self::from_seed(RandomGenerator::new().random_i64() as u64)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let v = split_seed(self.key, self.counter);
+        self.counter += 1;
+        v
+    }
+}
+
+/// draws of the script for the QA, `below(n)` gives the next number of the script modulo `n`, so the script `[3, 0]` stops a reel at 3 and the next at 0.
+/// The script is repeated when it ends and is kept by `reseed`, a source without the script gives zeros
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptedSource {
+    pub script: Vec<u64>,
+    pub position: usize,
+}
+
+impl ScriptedSource {
+    pub fn new(script: Vec<u64>) -> Self {
+        Self {
+            script,
+            position: 0,
+        }
+    }
+}
+
+impl RngSource for ScriptedSource {
+    fn from_seed(_seed: u64) -> Self {
+        Self::default()
+    }
+
+    fn from_entropy() -> Self {
+        Self::default()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        if self.script.is_empty() {
+            return 0;
+        }
+        let v = self.script[self.position % self.script.len()];
+        self.position += 1;
+        v
+    }
+
+    fn reseed(&mut self, _seed: Seed) {}
+
+    fn below(&mut self, n: u64) -> u64 {
+        if n == 0 {
+            0
+        } else {
+            self.next_u64() % n
+        }
+    }
+}
+
+/// records the draws of the inner source, every draw is made by the inner source like without the record
+/// and is kept as the number of `below` which gives it again, so the script of `into_script` plays them again
+pub struct RecordedSource<R: RngSource> {
+    pub inner: R,
+    pub draws: Vec<u64>,
+}
+
+impl<R: RngSource> RecordedSource<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            draws: vec![],
+        }
+    }
+
+    pub fn into_script(self) -> ScriptedSource {
+        ScriptedSource::new(self.draws)
+    }
+
+    /// index of the distribution drawn by the inner source, the values do not change the draw, so the indexes take their place
+    fn dist_index<V>(&mut self, dist: &BTreeMap<i32, V>, clone: bool) -> Result<usize> {
+        let indexes = dist.keys().enumerate().map(|(i, b)| (*b, i)).collect::<BTreeMap<_, _>>();
+        let i = if clone {
+            self.inner.rand_value_clone(&indexes)?.1
+        } else {
+            self.inner.rand_value(&indexes)?
+        };
+        let bound = if i == 0 {
+            0
+        } else {
+            dist.keys().nth(i - 1).map(|b| *b as u64).unwrap_or(0)
+        };
+        self.draws.push(bound);
+        Ok(i)
+    }
+
+    /// index of the vector drawn by the inner source
+    fn vec_index<T>(&mut self, values: &Vec<T>, remove: bool) -> Result<usize> {
+        let mut indexes = (0..values.len()).collect::<Vec<_>>();
+        let i = if remove {
+            self.inner.rand_vec_remove(&mut indexes)?
+        } else {
+            self.inner.rand_vec(&indexes)?
+        };
+        self.draws.push(i as u64);
+        Ok(i)
+    }
+}
+
+impl<R: RngSource> RngSource for RecordedSource<R> {
+    fn from_seed(seed: u64) -> Self {
+        Self::new(R::from_seed(seed))
+    }
+
+    fn from_entropy() -> Self {
+        Self::new(R::from_entropy())
+    }
+
+    fn from_key(key: [u8; 32], stream: u64) -> Self {
+        Self::new(R::from_key(key, stream))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let v = self.inner.next_u64();
+        self.draws.push(v);
+        v
+    }
+
+    /// the draws are kept over the seeds of the actions
+    fn reseed(&mut self, seed: Seed) {
+        self.inner.reseed(seed)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        let v = self.inner.below(n);
+        self.draws.push(v);
+        v
+    }
+
+    fn range_usize(&mut self, min: usize, max: usize) -> usize {
+        let v = self.inner.range_usize(min, max);
+        self.draws.push(v.saturating_sub(min) as u64);
+        v
+    }
+
+    fn range_i32(&mut self, min: i32, max: i32) -> i32 {
+        let v = self.inner.range_i32(min, max);
+        self.draws.push((v as i64 - min as i64).max(0) as u64);
+        v
+    }
+
+    fn random_usize(&mut self, n: usize) -> usize {
+        let v = self.inner.random_usize(n);
+        self.draws.push(v as u64);
+        v
+    }
+
+    fn random_i64(&mut self) -> i64 {
+        let v = self.inner.random_i64();
+        self.draws.push(v as u64);
+        v
+    }
+
+    fn rand_value<V: Copy>(&mut self, dist: &BTreeMap<i32, V>) -> Result<V> {
+        let i = self.dist_index(dist, false)?;
+        dist.values().nth(i).copied().ok_or_else(|| err_on!("illegal distribution!"))
+    }
+
+    fn rand_value_clone<V: Clone>(&mut self, dist: &BTreeMap<i32, V>) -> Result<(usize, V)> {
+        let i = self.dist_index(dist, true)?;
+        dist.values().nth(i).map(|v| (i, v.clone())).ok_or_else(|| err_on!("illegal distribution!"))
+    }
+
+    fn rand_vec<T: Copy>(&mut self, values: &Vec<T>) -> Result<T> {
+        let i = self.vec_index(values, false)?;
+        Ok(values[i])
+    }
+
+    fn rand_vec_remove<T>(&mut self, values: &mut Vec<T>) -> Result<T> {
+        let i = self.vec_index(values, true)?;
+        Ok(values.remove(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::configurable::{ConfigurableRandom, GameDefinition};
+    use crate::rng::{CertifiedSource, ChaChaSource, CounterSource, GameSource, RecordedSource, RngSource, ScriptedSource};
+    use crate::seed::Seed;
+    use essential_rand::random::RandomGenerator;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    fn draws<R: RngSource>(rand: &mut R, dist: &BTreeMap<i32, char>) -> Vec<(usize, usize, i32, char, (usize, char), char, usize, i64)> {
+        (0..100)
+            .map(|_| {
+                let mut values = vec![1, 2, 3, 4];
+                (
+                    rand.random_usize(7),
+                    rand.range_usize(3, 9),
+                    rand.range_i32(-3, 3),
+                    rand.rand_value(dist).unwrap(),
+                    rand.rand_value_clone(dist).unwrap(),
+                    rand.rand_vec(&vec!['x', 'y', 'z']).unwrap(),
+                    rand.rand_vec_remove(&mut values).unwrap(),
+                    rand.random_i64(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rng_sources() {
+        // the counter source gives any number of the stream without the draws before it
+        let mut counter = CounterSource::from_seed(9);
+        let stream = (0..4).map(|_| counter.next_u64()).collect::<Vec<_>>();
+        let mut jump = CounterSource {
+            key: 9,
+            counter: 2,
+        };
+        assert_eq!(jump.next_u64(), stream[2]);
+        counter.reseed(9.into());
+        assert_eq!(counter.next_u64(), stream[0]);
+
+        let dist = BTreeMap::from([(10, 'A'), (30, 'B'), (60, 'C')]);
+        let mut chacha = ChaChaSource::from_seed(5);
+        let mut other = ChaChaSource::from_seed(5);
+        for _ in 0..1_000 {
+            let (i, v) = chacha.rand_value_clone(&dist).unwrap();
+            assert_eq!(other.rand_value_clone(&dist).unwrap(), (i, v));
+            assert_eq!(dist.values().nth(i), Some(&v));
+            assert!((-5..5).contains(&counter.random(-5, 5)));
+        }
+        assert!(chacha.rand_value(&BTreeMap::<i32, char>::new()).is_err());
+        assert!(chacha.rand_vec_remove(&mut Vec::<usize>::new()).is_err());
+
+        // the script stops the reels, the number 25 of the script falls under the bound 30
+        let mut script = ScriptedSource::new(vec![1, 2, 3, 25]);
+        assert_eq!(script.rand_value(&dist).unwrap(), 'A');
+        assert_eq!(script.random(0, 2_usize), 0);
+        assert_eq!(script.random(0, 10_usize), 3);
+        assert_eq!(script.rand_value(&dist).unwrap(), 'B');
+        script.reseed(1.into());
+        assert_eq!(script.random(0, 10_usize), 1);
+
+        let json = r#"{
+            "cols": 3,
+            "rows": 3,
+            "reels": [["AABBS", "AABBS", "AWBBS"]],
+            "evaluator": "LINES",
+            "lines": ["---", "^^^", "___"],
+            "wins": {"A": {"3": 5}, "B": {"3": 10}},
+            "wilds": ["W"]
+        }"#;
+        let config = Arc::new(serde_json::from_str::<GameDefinition>(json).unwrap());
+        let mut scripted = ConfigurableRandom::with_rand(Arc::clone(&config), ScriptedSource::new(vec![1, 7, 4]));
+        let (stops, grid) = scripted.rand_grid(0, None).unwrap();
+        assert_eq!(stops, vec![1, 2, 4]);
+        assert_eq!(grid[1], vec!['B', 'B', 'S']);
+
+        // the recorded draws of a game are played again by the script
+        let mut recorded = ConfigurableRandom::with_rand(Arc::clone(&config), RecordedSource::new(ChaChaSource::from_seed(3)));
+        let spins = (0..10).map(|_| recorded.rand_grid(0, None).unwrap()).collect::<Vec<_>>();
+        assert_eq!(recorded.p.base.rand.draws.len(), 30);
+        let mut replay = ConfigurableRandom::with_rand(Arc::clone(&config), ScriptedSource::new(recorded.p.base.rand.draws.clone()));
+        assert_eq!((0..10).map(|_| replay.rand_grid(0, None).unwrap()).collect::<Vec<_>>(), spins);
+
+        // the recorded draws of the current generator are its own draws
+        let mut plain = RandomGenerator::from_seed(4);
+        let mut recorded = RecordedSource::new(RandomGenerator::from_seed(4));
+        let drawn = draws(&mut recorded, &dist);
+        assert_eq!(drawn, draws(&mut plain, &dist));
+        assert_eq!(draws(&mut recorded.into_script(), &dist), drawn);
+
+        // the keys of the fair rounds give ChaCha20 whatever the certified source is, every action has its own stream
+        let key = [7; 32];
+        let mut game = GameSource::from_seed(4);
+        assert_eq!(draws(&mut game, &dist), draws(&mut <CertifiedSource as RngSource>::from_seed(4), &dist));
+        game.reseed(Seed::Fair {
+            key,
+            stream: 2,
+        });
+        let drawn = draws(&mut game, &dist);
+        assert_eq!(drawn, draws(&mut ChaChaSource::from_key(key, 2), &dist));
+        assert_ne!(drawn, draws(&mut ChaChaSource::from_key(key, 3), &dist));
+    }
+}
@@ -0,0 +1,333 @@
+use crate::rand::Result as RandResult;
+use crate::rng::RngSource;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// gaps of the gap test from 0 to the last one which takes the longer gaps too
+pub const GAP_CATEGORIES: usize = 10;
+/// birthdays of a year of the birthday spacings test, `lambda = m^3 / 4n` is 2 for them
+pub const BIRTHDAYS: u64 = 512;
+pub const BIRTHDAY_DAYS: u64 = 1 << 24;
+/// duplicate spacings from 0 to the last bin which takes the greater counts too
+const BIRTHDAY_BINS: usize = 6;
+const MIN_YEARS: usize = 100;
+/// bins of the chi-square test with less expected counts are pooled
+const MIN_EXPECTED: f64 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TestKind {
+    ChiSquare,
+    SerialCorrelation,
+    Runs,
+    Gap,
+    BirthdaySpacing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestResult {
+    pub kind: TestKind,
+    /// `range 10` for the numbers of `random(0, 10)` or the name of a distribution
+    pub target: String,
+    pub samples: usize,
+    pub statistic: f64,
+    pub p_value: f64,
+    pub passed: bool,
+}
+
+impl Display for TestResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let verdict = if self.passed {
+            "pass"
+        } else {
+            "FAIL"
+        };
+        write!(f, "{:<18} {:<28} {:>9} samples statistic {:>12.4} p {:.6} {verdict}", format!("{:?}", self.kind), self.target, self.samples, self.statistic, self.p_value)
+    }
+}
+
+/// statistical tests of a source like the labs run them, a test passes when its p-value is not below `alpha`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RngChecker {
+    pub samples: usize,
+    pub alpha: f64,
+    pub results: Vec<TestResult>,
+}
+
+impl RngChecker {
+    pub fn new(samples: usize, alpha: f64) -> Self {
+        Self {
+            samples,
+            alpha,
+            results: vec![],
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.results.iter().all(|r| r.passed)
+    }
+
+    fn add(&mut self, kind: TestKind, target: &str, samples: usize, (statistic, p_value): (f64, f64)) {
+        self.results.push(TestResult {
+            kind,
+            target: target.to_string(),
+            samples,
+            statistic,
+            p_value,
+            passed: p_value >= self.alpha,
+        });
+    }
+
+    /// uniformity, independence and gaps of the numbers of `random(0, n)`
+    pub fn check_range<R: RngSource>(&mut self, rand: &mut R, n: usize) {
+        let draws = (0..self.samples).map(|_| rand.random(0, n) as u64).collect::<Vec<_>>();
+        let target = format!("range {n}");
+        let expected = vec![self.samples as f64 / n as f64; n];
+        self.add(TestKind::ChiSquare, &target, draws.len(), chi_square(&counts(&draws, n), &expected));
+        self.add(TestKind::SerialCorrelation, &target, draws.len(), serial_correlation(&draws));
+        self.add(TestKind::Runs, &target, draws.len(), runs(&draws, (n as f64 - 1.0) / 2.0));
+        self.add(TestKind::Gap, &target, draws.len(), gap(&draws, (n / 2) as u64, n as u64));
+    }
+
+    /// the indexes of `rand_value_clone` against the weights of the cumulative bounds of the distribution
+    pub fn check_dist<R: RngSource, V: Clone>(&mut self, rand: &mut R, name: &str, dist: &BTreeMap<i32, V>) -> RandResult<()> {
+        let draws = (0..self.samples).map(|_| rand.rand_value_clone(dist).map(|p| p.0 as u64)).collect::<RandResult<Vec<_>>>()?;
+        let total = dist.keys().last().cloned().unwrap_or(0) as f64;
+        let mut prev = 0;
+        let expected = dist
+            .keys()
+            .map(|b| {
+                let weight = (*b - prev) as f64;
+                prev = *b;
+                weight / total * self.samples as f64
+            })
+            .collect::<Vec<_>>();
+        self.add(TestKind::ChiSquare, name, draws.len(), chi_square(&counts(&draws, dist.len()), &expected));
+        self.add(TestKind::SerialCorrelation, name, draws.len(), serial_correlation(&draws));
+        Ok(())
+    }
+
+    /// the years of `BIRTHDAYS` draws of `random(0, BIRTHDAY_DAYS)` take as many numbers as the other tests
+    pub fn check_birthday<R: RngSource>(&mut self, rand: &mut R) {
+        let years = (self.samples / BIRTHDAYS as usize).max(MIN_YEARS);
+        let mut observed = vec![0; BIRTHDAY_BINS];
+        for _ in 0..years {
+            let mut days = (0..BIRTHDAYS).map(|_| rand.random(0, BIRTHDAY_DAYS as usize) as u64).collect::<Vec<_>>();
+            days.sort_unstable();
+            let mut spacings = days.iter().scan(0, |prev, d| Some(d - std::mem::replace(prev, *d))).collect::<Vec<_>>();
+            spacings.sort_unstable();
+            let duplicates = spacings.windows(2).filter(|w| w[0] == w[1]).count();
+            observed[duplicates.min(BIRTHDAY_BINS - 1)] += 1;
+        }
+        let lambda = (BIRTHDAYS as f64).powi(3) / (4.0 * BIRTHDAY_DAYS as f64);
+        let expected = poisson_bins(lambda, BIRTHDAY_BINS).into_iter().map(|p| p * years as f64).collect::<Vec<_>>();
+        let target = format!("{BIRTHDAYS} of {BIRTHDAY_DAYS}");
+        self.add(TestKind::BirthdaySpacing, &target, years * BIRTHDAYS as usize, chi_square(&observed, &expected));
+    }
+}
+
+fn counts(draws: &[u64], bins: usize) -> Vec<u64> {
+    let mut observed = vec![0; bins];
+    draws.iter().for_each(|d| observed[*d as usize] += 1);
+    observed
+}
+
+/// probabilities of `0..bins - 1` of the poisson distribution, the last bin takes the rest
+fn poisson_bins(lambda: f64, bins: usize) -> Vec<f64> {
+    let mut p = (-lambda).exp();
+    let mut probs = vec![];
+    for k in 0..bins - 1 {
+        probs.push(p);
+        p *= lambda / (k + 1) as f64;
+    }
+    probs.push(1.0 - probs.iter().sum::<f64>());
+    probs
+}
+
+/// statistic and p-value of the observed counts, the bins with less than `MIN_EXPECTED` are pooled into one bin
+pub fn chi_square(observed: &[u64], expected: &[f64]) -> (f64, f64) {
+    let mut bins = vec![];
+    let mut pooled = (0.0, 0.0);
+    for (o, e) in observed.iter().zip(expected.iter()) {
+        if *e >= MIN_EXPECTED {
+            bins.push((*o as f64, *e));
+        } else {
+            pooled = (pooled.0 + *o as f64, pooled.1 + *e);
+        }
+    }
+    if pooled.1 > 0.0 {
+        bins.push(pooled);
+    } else if pooled.0 > 0.0 {
+        return (f64::INFINITY, 0.0);
+    }
+    if bins.len() < 2 {
+        return (0.0, 1.0);
+    }
+    let statistic = bins.iter().map(|(o, e)| (o - e) * (o - e) / e).sum::<f64>();
+    (statistic, chi_square_p(statistic, (bins.len() - 1) as f64))
+}
+
+/// lag 1 correlation of the numbers, it is normal with the variance `1 / n` for the independent numbers
+pub fn serial_correlation(draws: &[u64]) -> (f64, f64) {
+    let n = draws.len();
+    if n < 3 {
+        return (0.0, 1.0);
+    }
+    let mean = draws.iter().sum::<u64>() as f64 / n as f64;
+    let dev = draws.iter().map(|d| *d as f64 - mean).collect::<Vec<_>>();
+    let variance = dev.iter().map(|d| d * d).sum::<f64>();
+    if variance == 0.0 {
+        return (0.0, 1.0);
+    }
+    let r = dev.windows(2).map(|w| w[0] * w[1]).sum::<f64>() / variance;
+    (r, normal_p(r * (n as f64).sqrt()))
+}
+
+/// runs above and below the median of the Wald-Wolfowitz test, the numbers equal to the median are skipped
+pub fn runs(draws: &[u64], median: f64) -> (f64, f64) {
+    let signs = draws.iter().map(|d| *d as f64).filter(|d| *d != median).map(|d| d > median).collect::<Vec<_>>();
+    let above = signs.iter().filter(|s| **s).count() as f64;
+    let below = signs.len() as f64 - above;
+    if above == 0.0 || below == 0.0 {
+        return (f64::INFINITY, 0.0);
+    }
+    let total = above + below;
+    let runs = 1.0 + signs.windows(2).filter(|w| w[0] != w[1]).count() as f64;
+    let mean = 2.0 * above * below / total + 1.0;
+    let variance = 2.0 * above * below * (2.0 * above * below - total) / (total * total * (total - 1.0));
+    let z = (runs - mean) / variance.sqrt();
+    (z, normal_p(z))
+}
+
+/// gaps between the numbers below `hit` of the range `n`, the gap `k` has the probability `p (1 - p)^k`
+pub fn gap(draws: &[u64], hit: u64, n: u64) -> (f64, f64) {
+    let p = hit as f64 / n as f64;
+    let mut observed = vec![0; GAP_CATEGORIES + 1];
+    let mut current: Option<usize> = None;
+    for d in draws.iter() {
+        if *d < hit {
+            if let Some(g) = current {
+                observed[g.min(GAP_CATEGORIES)] += 1;
+            }
+            current = Some(0);
+        } else if let Some(g) = current.as_mut() {
+            *g += 1;
+        }
+    }
+    let gaps = observed.iter().sum::<u64>() as f64;
+    let mut expected = (0..GAP_CATEGORIES).map(|k| gaps * p * (1.0 - p).powi(k as i32)).collect::<Vec<_>>();
+    expected.push(gaps * (1.0 - p).powi(GAP_CATEGORIES as i32));
+    chi_square(&observed, &expected)
+}
+
+pub fn chi_square_p(statistic: f64, df: f64) -> f64 {
+    gamma_q(df / 2.0, statistic / 2.0)
+}
+
+/// two-sided p-value of the standard normal `z`, `erfc(|z| / sqrt(2))`
+pub fn normal_p(z: f64) -> f64 {
+    gamma_q(0.5, z * z / 2.0)
+}
+
+/// regularized upper incomplete gamma function by the series or the continued fraction
+pub fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    if x.is_infinite() {
+        return 0.0;
+    }
+    let ln_front = a * x.ln() - x - ln_gamma(a);
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut ap = a;
+        for _ in 0..1_000 {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        (1.0 - sum * ln_front.exp()).max(0.0)
+    } else {
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1_000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        (ln_front.exp() * h).min(1.0)
+    }
+}
+
+/// Lanczos approximation with `g = 7`
+pub fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEFFS.iter().enumerate().skip(1).fold(COEFFS[0], |s, (i, c)| s + c / (x + i as f64));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rng::{CounterSource, RngSource, ScriptedSource};
+    use crate::rng_check::{chi_square_p, normal_p, RngChecker, TestKind};
+
+    #[test]
+    fn test_rng_checker() {
+        assert!((chi_square_p(3.841459, 1.0) - 0.05).abs() < 1e-6);
+        assert!((chi_square_p(18.307038, 10.0) - 0.05).abs() < 1e-5);
+        assert!((normal_p(1.959964) - 0.05).abs() < 1e-6);
+
+        let mut checker = RngChecker::new(20_000, 1e-6);
+        let mut counter = CounterSource::from_seed(7);
+        checker.check_range(&mut counter, 10);
+        checker.check_birthday(&mut counter);
+        assert!(checker.is_valid(), "{:?}", checker.results);
+
+        // the script of the cycle 0..10 is uniform but every number is followed by the next one
+        let mut checker = RngChecker::new(20_000, 0.001);
+        checker.check_range(&mut ScriptedSource::new((0..10).collect()), 10);
+        let passed = |kind: TestKind| checker.results.iter().find(|r| r.kind == kind).map(|r| r.passed);
+        assert_eq!(passed(TestKind::ChiSquare), Some(true));
+        assert_eq!(passed(TestKind::SerialCorrelation), Some(false));
+        assert_eq!(passed(TestKind::Runs), Some(false));
+        assert_eq!(passed(TestKind::Gap), Some(false));
+    }
+}
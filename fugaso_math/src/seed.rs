@@ -0,0 +1,640 @@
+use crate::bonus::PickBoard;
+use crate::fair::{fair_key, key_seed, server_hash, FairRound};
+use crate::fsm::FSM;
+use crate::gamble::{GambleLimit, GambleState};
+use crate::math::{BetCalculator, GameInitArg, IRequest, ProxyMath, SlotMath, SpinArg, Step};
+use crate::protocol::{DatabaseStore, Promo};
+use crate::rng::{split_seed, ChaChaSource, RngSource};
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use fugaso_data::fugaso_action::{self, ActionKind};
+use fugaso_data::fugaso_fair_seed;
+use fugaso_data::fugaso_round::{self, RoundDetail};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// actions of a round which draw from the seed of the round, the index of an action is the count of them before it
+pub const SEEDED_ACTS: [ActionKind; 7] =
+    [ActionKind::BET, ActionKind::RESPIN, ActionKind::FREE_SPIN, ActionKind::DROP, ActionKind::BONUS, ActionKind::GAMBLE_PLAY, ActionKind::HALF_COLLECT];
+
+/// seed of the action `index` of the round, every action has its own stream, so a restored round draws the same numbers
+pub fn action_seed(round_seed: u64, index: u64) -> u64 {
+    split_seed(round_seed, index)
+}
+
+/// seed of the draws of an action, the actions of a provably fair round draw from ChaCha20 keyed by the round, each on its own stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seed {
+    Plain(u64),
+    Fair {
+        key: [u8; 32],
+        stream: u64,
+    },
+}
+
+impl From<u64> for Seed {
+    fn from(seed: u64) -> Self {
+        Seed::Plain(seed)
+    }
+}
+
+impl Seed {
+    /// seed which is stored with the round, it is the head of the key of a fair round, see `fair::key_seed`
+    pub fn head(&self) -> u64 {
+        match self {
+            Seed::Plain(s) => *s,
+            Seed::Fair {
+                key,
+                ..
+            } => key_seed(key),
+        }
+    }
+
+    /// seed of the action `index` of the round of the seed
+    pub fn action(&self, index: u64) -> Seed {
+        match self {
+            Seed::Plain(s) => Seed::Plain(action_seed(*s, index)),
+            Seed::Fair {
+                key,
+                ..
+            } => Seed::Fair {
+                key: *key,
+                stream: index,
+            },
+        }
+    }
+}
+
+/// seed of the stored round, a round of the provably fair mode is keyed by its server seed `seed`, none for a round without the seed
+pub fn stored_seed(round: &fugaso_round::Model, seed: Option<&fugaso_fair_seed::Model>) -> Result<Option<Seed>, ServerError> {
+    match round.seed_id {
+        Some(id) => {
+            let fair = seed.and_then(|s| FairRound::stored(round, s)).ok_or_else(|| err_on!(format!("server seed {id} of round {} is absent!", round.id)))?;
+            Ok(Some(Seed::Fair {
+                key: fair.key()?,
+                stream: 0,
+            }))
+        }
+        None => Ok(round.seed.map(|s| Seed::Plain(s as u64))),
+    }
+}
+
+pub fn is_seeded(kind: &ActionKind) -> bool {
+    SEEDED_ACTS.contains(kind)
+}
+
+/// index of the next seeded action of the round
+pub fn seeded_count(actions: &[fugaso_action::Model]) -> u64 {
+    actions.iter().filter(|a| a.act_descr.as_ref().map(is_seeded).unwrap_or(false)).count() as u64
+}
+
+/// master generator of the seeds, a fresh seed is drawn for every round, it is ChaCha20 keyed by the OS entropy
+/// whatever the `DefaultSource` of the games is, because a seed of a round gives every number of the round
+pub struct RoundSeeder {
+    rand: ChaChaSource,
+}
+
+impl Default for RoundSeeder {
+    fn default() -> Self {
+        Self {
+            rand: ChaChaSource::from_entropy(),
+        }
+    }
+}
+
+impl RoundSeeder {
+    pub fn next_seed(&mut self) -> u64 {
+        self.rand.next_u64()
+    }
+}
+
+/// fields of a stored action which are made by the math, the admin sets the others
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionRecord {
+    pub act_descr: ActionKind,
+    pub next_act: ActionKind,
+    pub reel_combo: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reel_stops: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub holds: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lines: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub special: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub free_games: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cards: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub choice: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom: Option<String>,
+}
+
+impl ActionRecord {
+    pub fn new(action: &fugaso_action::Model) -> Self {
+        Self {
+            act_descr: action.act_descr.clone().unwrap_or_default(),
+            next_act: action.next_act.clone().unwrap_or_default(),
+            reel_combo: action.reel_combo,
+            reel_stops: action.reel_stops.clone(),
+            grid: action.grid.clone(),
+            holds: action.holds.clone(),
+            lines: action.lines.clone(),
+            special: action.special.clone(),
+            free_games: action.free_games.clone(),
+            cards: action.cards.clone(),
+            choice: action.choice.clone(),
+            custom: action.custom.clone(),
+        }
+    }
+
+    pub fn to_model(&self, id: i64) -> fugaso_action::Model {
+        fugaso_action::Model {
+            id,
+            act_descr: Some(self.act_descr.clone()),
+            next_act: Some(self.next_act.clone()),
+            reel_combo: self.reel_combo,
+            reel_stops: self.reel_stops.clone(),
+            grid: self.grid.clone(),
+            holds: self.holds.clone(),
+            lines: self.lines.clone(),
+            special: self.special.clone(),
+            free_games: self.free_games.clone(),
+            cards: self.cards.clone(),
+            choice: self.choice.clone(),
+            custom: self.custom.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// names of the fields which differ from `other`, `next_act` is set by the fsm of the admin and is not compared
+    pub fn diff(&self, other: &Self) -> Vec<String> {
+        let reel_combo = (self.reel_combo != other.reel_combo).then(|| "reelCombo".to_string());
+        let fields = [
+            ("reelStops", &self.reel_stops, &other.reel_stops),
+            ("grid", &self.grid, &other.grid),
+            ("holds", &self.holds, &other.holds),
+            ("lines", &self.lines, &other.lines),
+            ("special", &self.special, &other.special),
+            ("freeGames", &self.free_games, &other.free_games),
+            ("cards", &self.cards, &other.cards),
+            ("choice", &self.choice, &other.choice),
+            ("custom", &self.custom, &other.custom),
+        ];
+        reel_combo.into_iter().chain(fields.iter().filter(|(_, a, b)| a != b).map(|(n, _, _)| n.to_string())).collect()
+    }
+}
+
+/// stored round with its seed and config hash, it is everything the re-simulation of the round needs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoundRecord {
+    pub id: i64,
+    pub common_id: i64,
+    pub config_hash: Option<String>,
+    pub seed: i64,
+    pub bet: i32,
+    pub line: i32,
+    pub denom: i32,
+    pub bet_counter: i32,
+    #[serde(default)]
+    pub reels: Option<i32>,
+    pub multi: i32,
+    #[serde(default)]
+    pub detail: RoundDetail,
+    pub stake: i64,
+    #[serde(default)]
+    pub bought: bool,
+    /// commitment, client seed and nonce of a round of the provably fair mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fair_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_seed: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<i64>,
+    /// server seed of a round of the provably fair mode, the round does not keep it, it is given when the seed is revealed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_seed: Option<String>,
+    /// step of the percent of the player which limited the win of the round
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_win: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_take: Option<i32>,
+    pub actions: Vec<ActionRecord>,
+}
+
+impl RoundRecord {
+    pub fn new(round: &fugaso_round::Model, actions: &[fugaso_action::Model]) -> Result<Self, ServerError> {
+        let mut actions = actions.iter().collect::<Vec<_>>();
+        actions.sort_by_key(|a| a.id);
+        Ok(Self {
+            id: round.id,
+            common_id: round.common_id.ok_or_else(|| err_on!("common id is none!"))?,
+            config_hash: round.config_hash.clone(),
+            seed: round.seed.ok_or_else(|| err_on!(format!("seed of round {} is none!", round.id)))?,
+            bet: round.bet,
+            line: round.line,
+            denom: round.denom,
+            bet_counter: round.bet_counter,
+            reels: round.reels,
+            multi: round.multi,
+            detail: round.detail.clone(),
+            stake: round.stake.unwrap_or(0),
+            bought: round.bought.unwrap_or(false),
+            fair_hash: round.fair_hash.clone(),
+            client_seed: round.client_seed.clone(),
+            nonce: round.nonce,
+            server_seed: None,
+            step_win: round.step_win,
+            step_take: round.step_take,
+            actions: actions.into_iter().map(ActionRecord::new).collect(),
+        })
+    }
+
+    pub fn step(&self) -> Result<Step, ServerError> {
+        match (self.step_win, self.step_take) {
+            (Some(win), Some(take)) => Ok(Step {
+                win,
+                take: take as usize,
+            }),
+            _ => Err(err_on!(format!("step of round {} is none!", self.id))),
+        }
+    }
+
+    /// the revealed server seed is the committed one of the round and gives the key of the round
+    pub fn verify_fair(&self, server_seed: &str) -> Result<[u8; 32], ServerError> {
+        let (hash, client_seed, nonce) = match (&self.fair_hash, &self.client_seed, self.nonce) {
+            (Some(h), Some(c), Some(n)) => (h, c, n),
+            _ => return Err(err_on!(format!("round {} is not provably fair!", self.id))),
+        };
+        if &server_hash(server_seed) != hash {
+            return Err(err_on!(format!("server seed is not the commitment {hash}!")));
+        }
+        let key = fair_key(server_seed, client_seed, nonce as u64)?;
+        if key_seed(&key) != self.seed as u64 {
+            return Err(err_on!(format!("seed of round {} is not the seed of nonce {nonce}!", self.id)));
+        }
+        Ok(key)
+    }
+
+    /// seed of the round, a provably fair round is keyed by its revealed server seed
+    pub fn round_seed(&self) -> Result<Seed, ServerError> {
+        if self.fair_hash.is_none() {
+            return Ok(Seed::Plain(self.seed as u64));
+        }
+        let server_seed = self.server_seed.as_deref().ok_or_else(|| err_on!(format!("server seed of round {} is not revealed!", self.id)))?;
+        Ok(Seed::Fair {
+            key: self.verify_fair(server_seed)?,
+            stream: 0,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionCheck {
+    pub index: u64,
+    pub act_descr: ActionKind,
+    /// fields of the stored action which the seed does not give
+    pub fields: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResimReport {
+    pub round_id: i64,
+    pub seed: i64,
+    pub checks: Vec<ActionCheck>,
+}
+
+impl ResimReport {
+    pub fn is_exact(&self) -> bool {
+        self.checks.iter().all(|c| c.fields.is_empty() && c.error.is_none())
+    }
+}
+
+impl Display for ResimReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "round {} seed {}: {}",
+            self.round_id,
+            self.seed,
+            if self.is_exact() {
+                "exact"
+            } else {
+                "differs"
+            }
+        )?;
+        for c in self.checks.iter() {
+            match (&c.error, c.fields.is_empty()) {
+                (Some(e), _) => write!(f, "\n  {} {:?}: {e}", c.index, c.act_descr)?,
+                (None, true) => write!(f, "\n  {} {:?}: exact", c.index, c.act_descr)?,
+                (None, false) => write!(f, "\n  {} {:?}: {}", c.index, c.act_descr, c.fields.join(", "))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// the seeded actions of the round are played again from their seeds by the math of the config of the round like `SlotAdmin` plays them
+/// with the step of the round, a provably fair round is played without the step, the state before an action is restored from the stored
+/// actions, the pick of a bonus is the last pick of its board and a gamble takes the last choice of its stored state
+pub fn resimulate<M: SlotMath>(math: M, record: &RoundRecord) -> Result<ResimReport, ServerError> {
+    let step = record.step()?;
+    let seed = record.round_seed()?;
+    let mut math = ProxyMath::new(math);
+    math.set_fair(record.fair_hash.is_some());
+    let mut request = M::Input::create_input(record.bet, record.line as usize, record.denom, record.bet_counter as usize);
+    if record.bought {
+        request = math.buy_request(&request)?;
+    }
+    let mut checks = vec![];
+    let mut index = 0;
+    for (i, stored) in record.actions.iter().enumerate().filter(|(_, a)| is_seeded(&a.act_descr)) {
+        let mut check = ActionCheck {
+            index,
+            act_descr: stored.act_descr.clone(),
+            fields: vec![],
+            error: None,
+        };
+        match play_action(&mut math, record, i, &request, &step, seed.action(index)) {
+            Ok(played) => check.fields = stored.diff(&played),
+            Err(e) => check.error = Some(e.to_string()),
+        }
+        checks.push(check);
+        index += 1;
+    }
+    Ok(ResimReport {
+        round_id: record.id,
+        seed: record.seed,
+        checks,
+    })
+}
+
+fn play_action<M: SlotMath>(math: &mut ProxyMath<M>, record: &RoundRecord, i: usize, request: &M::Input, step: &Step, seed: Seed) -> Result<ActionRecord, ServerError> {
+    let stored = &record.actions[i];
+    let mut fsm = math.create_fsm("");
+    if i == 0 {
+        fsm.client_act(ActionKind::BET)?;
+        fsm.client_act(ActionKind::SPIN)?;
+    } else {
+        let prev = record.actions[..i].iter().enumerate().map(|(id, a)| a.to_model(id as i64)).collect::<Vec<_>>();
+        math.init(
+            GameInitArg {
+                curr_lines: record.line as usize,
+                curr_bet: record.bet,
+                curr_denom: record.denom,
+                curr_reels: record.reels.map(|r| r as usize).unwrap_or(request.reels()),
+                round_id: record.common_id,
+                round_type: record.detail.clone(),
+                round_multiplier: record.multi,
+                bet_counter: record.bet_counter as usize,
+                promo: Promo::default(),
+            },
+            &prev,
+        )?;
+        fsm.init(record.actions[i - 1].next_act.clone());
+        fsm.client_act(stored.act_descr.clone())?;
+    }
+    let arg = SpinArg {
+        balance: i64::MAX / 2,
+        round_id: record.common_id,
+        round_type: record.detail.clone(),
+        round_multiplier: record.multi,
+        next_act: fsm.current(),
+        promo: Promo::default(),
+        stake: if i == 0 {
+            record.stake
+        } else {
+            math.create_bet_calculator().calc_total_bet(request)
+        },
+    };
+    math.reseed(seed);
+    let data = match stored.act_descr {
+        ActionKind::BET if record.bought => math.buy_spin(request, arg, step, None)?,
+        ActionKind::BET => math.spin(request, arg, step, None)?,
+        ActionKind::RESPIN => math.respin(request, arg, step, None)?,
+        ActionKind::FREE_SPIN => math.free_spin(request, arg, step, None)?,
+        ActionKind::DROP => math.drop(request, arg, step, None)?,
+        ActionKind::BONUS => {
+            let board = stored.choice.as_ref().map_or_else(|| Ok(PickBoard::default()), |c| PickBoard::from_choice(c))?;
+            let pick = board.picks.last().cloned().ok_or_else(|| err_on!("pick of the bonus is absent!"))?;
+            math.bonus(request, arg, step, pick)?
+        }
+        ActionKind::GAMBLE_PLAY => {
+            let state = stored.cards.as_ref().map(|c| GambleState::from_db(c)).transpose()?;
+            let choice = state.and_then(|s| s.choices.last().copied()).ok_or_else(|| err_on!("choice of the gamble is absent!"))?;
+            math.gamble(request, arg, choice, &GambleLimit::default())?
+        }
+        ActionKind::HALF_COLLECT => math.half_collect(request, arg)?,
+        _ => return Err(err_on!(format!("action {:?} is not seeded!", stored.act_descr))),
+    };
+    Ok(ActionRecord {
+        act_descr: stored.act_descr.clone(),
+        next_act: stored.next_act.clone(),
+        ..ActionRecord::new(&data.create_action_default()?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::config_hash;
+    use crate::configurable::ConfigurableSlotMath;
+    use crate::fair::FairSeeds;
+    use crate::math::{ProxyMath, Request, SlotMath, SpinArg, Step};
+    use crate::protocol::Promo;
+    use crate::seed::{action_seed, resimulate, seeded_count, RoundRecord, Seed};
+    use fugaso_data::fugaso_action::{self, ActionKind};
+    use fugaso_data::fugaso_round::{self, RoundDetail};
+
+    #[test]
+    fn test_resimulate_round() {
+        let json = r#"{
+            "cols": 3,
+            "rows": 3,
+            "reels": [["AABSS", "ABBSS", "AWBSS"], ["AABBS", "AABBS", "AWBBS"]],
+            "evaluator": "LINES",
+            "lines": ["---", "^^^", "___"],
+            "wins": {"A": {"3": 5}, "B": {"3": 10}},
+            "wilds": ["W"],
+            "scatter": "S",
+            "scatterWins": {"3": 2},
+            "free": {"scatter": "S", "triggers": {"3": 3}, "categories": [1], "multipliers": [2]},
+            "maxWin": 1000
+        }"#;
+        let mut math = ProxyMath::new(ConfigurableSlotMath::new(json).unwrap());
+        let request = Request {
+            bet: 1,
+            line: 3,
+            denom: 1,
+            bet_counter: 1,
+            ..Default::default()
+        };
+        let arg = SpinArg {
+            balance: 1_000,
+            round_id: 1,
+            round_type: RoundDetail::SIMPLE,
+            round_multiplier: 1,
+            next_act: ActionKind::BET,
+            promo: Promo::default(),
+            stake: 3,
+        };
+
+        // the round is played like the admin plays it, every action draws from its own stream of the seed
+        let seed = 42;
+        let mut actions: Vec<fugaso_action::Model> = vec![];
+        let mut kind = ActionKind::BET;
+        while actions.len() < 10 {
+            math.reseed(action_seed(seed, seeded_count(&actions)).into());
+            let result = if kind == ActionKind::BET {
+                math.spin(&request, arg.clone(), &Step::default(), None)
+            } else {
+                math.free_spin(&request, arg.clone(), &Step::default(), None)
+            }
+            .unwrap();
+            let left = result.free().map(|f| f.left).unwrap_or(0);
+            let next = if left > 0 {
+                ActionKind::FREE_SPIN
+            } else {
+                ActionKind::CLOSE
+            };
+            actions.push(fugaso_action::Model {
+                id: actions.len() as i64 + 1,
+                act_descr: Some(kind),
+                next_act: Some(next.clone()),
+                ..result.create_action_default().unwrap()
+            });
+            math.post_process(next.clone(), result).unwrap();
+            if next == ActionKind::CLOSE {
+                break;
+            }
+            kind = ActionKind::FREE_SPIN;
+        }
+        let round = fugaso_round::Model {
+            id: 1,
+            common_id: Some(1),
+            bet: 1,
+            line: 3,
+            denom: 1,
+            bet_counter: 1,
+            multi: 1,
+            stake: Some(3),
+            config_hash: Some(config_hash(json, None)),
+            seed: Some(seed as i64),
+            step_win: Some(Step::default().win),
+            step_take: Some(Step::default().take as i32),
+            ..Default::default()
+        };
+        let unseeded = fugaso_round::Model {
+            seed: None,
+            ..round.clone()
+        };
+        assert!(RoundRecord::new(&unseeded, &actions).is_err());
+        let record = RoundRecord::new(&round, &actions).unwrap();
+        assert_eq!(record.actions.len(), actions.len());
+        let resim = |record: &RoundRecord| resimulate(ConfigurableSlotMath::new(json).unwrap(), record);
+
+        let report = resim(&record).unwrap();
+        assert!(report.is_exact(), "{report}");
+        assert_eq!(report.checks.len(), actions.len());
+
+        // the stored grid which the seed does not give is found
+        let mut tampered = record.clone();
+        tampered.actions[0].grid = Some("[]".to_string());
+        let report = resim(&tampered).unwrap();
+        assert!(!report.is_exact());
+        assert_eq!(report.checks[0].fields, vec!["grid".to_string()]);
+
+        // the step of the round is stored with it
+        let stepless = RoundRecord {
+            step_take: None,
+            ..record.clone()
+        };
+        assert!(resim(&stepless).is_err());
+    }
+
+    #[test]
+    fn test_resimulate_fair_round() {
+        let json = r#"{
+            "cols": 3,
+            "rows": 3,
+            "reels": [["AABK", "ABBK", "AKBK"]],
+            "evaluator": "LINES",
+            "lines": ["---", "^^^", "___"],
+            "wins": {"A": {"3": 5}, "B": {"3": 10}, "K": {"3": 2}},
+            "maxWin": 1000
+        }"#;
+        let request = Request {
+            bet: 1,
+            line: 3,
+            denom: 1,
+            bet_counter: 1,
+            ..Default::default()
+        };
+        let arg = SpinArg {
+            balance: 1_000,
+            round_id: 1,
+            round_type: RoundDetail::SIMPLE,
+            round_multiplier: 1,
+            next_act: ActionKind::BET,
+            promo: Promo::default(),
+            stake: 3,
+        };
+        // no win is allowed by the step, the fair round is not played again for it
+        let step = Step {
+            win: 0,
+            take: 100,
+        };
+
+        // a fair round is played again from its revealed server seed
+        let seeds = FairSeeds::new(None).unwrap().next_round();
+        let seed = Seed::Fair {
+            key: seeds.key().unwrap(),
+            stream: 0,
+        };
+        let mut math = ProxyMath::new(ConfigurableSlotMath::new(json).unwrap());
+        math.set_fair(true);
+        math.reseed(seed.action(0));
+        let result = math.spin(&request, arg.clone(), &step, None).unwrap();
+        let action = fugaso_action::Model {
+            id: 1,
+            act_descr: Some(ActionKind::BET),
+            next_act: Some(ActionKind::CLOSE),
+            ..result.create_action_default().unwrap()
+        };
+        let round = fugaso_round::Model {
+            id: 1,
+            common_id: Some(1),
+            bet: 1,
+            line: 3,
+            denom: 1,
+            bet_counter: 1,
+            multi: 1,
+            stake: Some(3),
+            config_hash: Some(config_hash(json, None)),
+            seed: Some(seed.head() as i64),
+            fair_hash: Some(seeds.server_hash.clone()),
+            client_seed: Some(seeds.client_seed.clone()),
+            nonce: Some(seeds.nonce as i64),
+            step_win: Some(step.win),
+            step_take: Some(step.take as i32),
+            ..Default::default()
+        };
+        let record = RoundRecord::new(&round, &[action]).unwrap();
+        assert!(resimulate(ConfigurableSlotMath::new(json).unwrap(), &record).is_err());
+        let revealed = RoundRecord {
+            server_seed: Some(seeds.server_seed.clone()),
+            ..record
+        };
+        let report = resimulate(ConfigurableSlotMath::new(json).unwrap(), &revealed).unwrap();
+        assert!(report.is_exact(), "{report}");
+    }
+}
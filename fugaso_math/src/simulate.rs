@@ -0,0 +1,654 @@
+use crate::fsm::FSM;
+use crate::math::{BetCalculator, IRequest, SlotMath, SpinArg, Step};
+use crate::protocol::{DatabaseStore, GameData, Promo, SpinData};
+use crate::rng::split_seed;
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use fugaso_data::fugaso_action::ActionKind;
+use fugaso_data::fugaso_round::RoundDetail;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Feature {
+    Line,
+    Collect,
+    Respin,
+    Jackpot,
+    Free,
+    Bonus,
+    Other,
+}
+
+pub type FeatureWins = BTreeMap<Feature, i64>;
+
+pub trait FeatureSplit<S: DatabaseStore + Default, R: Default> {
+    /// adds the gains of a single spin, respin, free spin or cascade to the features of the round
+    fn split(&mut self, kind: &ActionKind, data: &SpinData<S, R>, features: &mut FeatureWins);
+}
+
+/// splits gains only by the action which produced them
+#[derive(Default)]
+pub struct ActionSplit;
+
+impl<S: DatabaseStore + Default, R: Default> FeatureSplit<S, R> for ActionSplit {
+    fn split(&mut self, kind: &ActionKind, data: &SpinData<S, R>, features: &mut FeatureWins) {
+        let feature = match kind {
+            ActionKind::RESPIN => Feature::Respin,
+            ActionKind::FREE_SPIN => Feature::Free,
+            _ => Feature::Line,
+        };
+        let amount = data.result.gains.iter().map(|g| g.amount).sum::<i64>();
+        *features.entry(feature).or_insert(0) += amount;
+    }
+}
+
+/// lower bounds of the win buckets in stakes, the first bucket is for the rounds without a win
+pub const WIN_BUCKETS: [f64; 11] = [0.0, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0];
+
+/// confidence levels of the volatility index with their normal quantiles
+pub const CONFIDENCE_LEVELS: [(f64, f64); 3] = [(0.90, 1.645), (0.95, 1.960), (0.99, 2.576)];
+
+fn bucket_of(ratio: f64) -> usize {
+    if ratio <= 0.0 {
+        0
+    } else {
+        WIN_BUCKETS.iter().rposition(|b| ratio >= *b).unwrap_or(0) + 1
+    }
+}
+
+fn bucket_name(bucket: usize) -> String {
+    match bucket {
+        0 => "0".to_string(),
+        b if b == WIN_BUCKETS.len() => format!("{}x+", WIN_BUCKETS[b - 1]),
+        b => format!("{}-{}x", WIN_BUCKETS[b - 1], WIN_BUCKETS[b]),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulationArg {
+    pub game_name: String,
+    pub rounds: u64,
+    pub bet: i32,
+    pub line: usize,
+    pub denom: i32,
+    pub bet_counter: usize,
+    /// `stop_factor` of the game config, 0 when the wins are not capped
+    pub stop_factor: i32,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationStats {
+    pub rounds: u64,
+    pub total_bet: i64,
+    pub total_win: i64,
+    pub hits: u64,
+    pub max_win: i64,
+    pub respin_rounds: u64,
+    pub free_rounds: u64,
+    pub actions: u64,
+    pub sum_ratio: f64,
+    pub sum_ratio_sq: f64,
+    #[serde(default)]
+    pub features: BTreeMap<Feature, f64>,
+    #[serde(default)]
+    pub buckets: Vec<u64>,
+    #[serde(default)]
+    pub bucket_wins: Vec<i64>,
+    #[serde(default)]
+    pub cap_hits: u64,
+    /// gains of the features above the round wins, they were cut by the caps
+    #[serde(default)]
+    pub cap_loss: i64,
+}
+
+impl SimulationStats {
+    pub fn add_round(&mut self, stake: i64, win: i64, round: &RoundTrace) {
+        self.rounds += 1;
+        self.total_bet += stake;
+        self.total_win += win;
+        self.actions += round.actions;
+        if win > 0 {
+            self.hits += 1;
+        }
+        if round.respins > 0 {
+            self.respin_rounds += 1;
+        }
+        if round.free_spins > 0 {
+            self.free_rounds += 1;
+        }
+        self.max_win = std::cmp::max(self.max_win, win);
+        let ratio = if stake > 0 {
+            win as f64 / stake as f64
+        } else {
+            0.0
+        };
+        self.sum_ratio += ratio;
+        self.sum_ratio_sq += ratio * ratio;
+        self.add_bucket(bucket_of(ratio), 1, win);
+        if round.capped {
+            self.cap_hits += 1;
+        }
+        self.add_features(win, &round.features);
+    }
+
+    fn add_bucket(&mut self, bucket: usize, count: u64, win: i64) {
+        if self.buckets.len() <= WIN_BUCKETS.len() {
+            self.buckets.resize(WIN_BUCKETS.len() + 1, 0);
+            self.bucket_wins.resize(WIN_BUCKETS.len() + 1, 0);
+        }
+        self.buckets[bucket] += count;
+        self.bucket_wins[bucket] += win;
+    }
+
+    /// gains of the features as they were won, the part above the round win cut by the cap is the cap loss
+    /// and the part of the round win without a feature is `Other`
+    fn add_features(&mut self, win: i64, features: &FeatureWins) {
+        for (feature, amount) in features.iter().filter(|f| *f.1 != 0) {
+            *self.features.entry(*feature).or_insert(0.0) += *amount as f64;
+        }
+        let sum = features.values().sum::<i64>();
+        if sum > win {
+            self.cap_loss += sum - win;
+        } else if sum < win {
+            *self.features.entry(Feature::Other).or_insert(0.0) += (win - sum) as f64;
+        }
+    }
+
+    pub fn merge(&mut self, other: &SimulationStats) {
+        self.rounds += other.rounds;
+        self.total_bet += other.total_bet;
+        self.total_win += other.total_win;
+        self.hits += other.hits;
+        self.max_win = std::cmp::max(self.max_win, other.max_win);
+        self.respin_rounds += other.respin_rounds;
+        self.free_rounds += other.free_rounds;
+        self.actions += other.actions;
+        self.sum_ratio += other.sum_ratio;
+        self.sum_ratio_sq += other.sum_ratio_sq;
+        for (feature, amount) in other.features.iter() {
+            *self.features.entry(*feature).or_insert(0.0) += *amount;
+        }
+        for (bucket, count) in other.buckets.iter().enumerate() {
+            self.add_bucket(bucket, *count, other.bucket_wins.get(bucket).cloned().unwrap_or(0));
+        }
+        self.cap_hits += other.cap_hits;
+        self.cap_loss += other.cap_loss;
+    }
+
+    pub fn report(&self, stake: i64) -> SimulationReport {
+        let rounds = std::cmp::max(self.rounds, 1) as f64;
+        let mean = self.sum_ratio / rounds;
+        let variance = (self.sum_ratio_sq / rounds - mean * mean).max(0.0);
+        let std_dev = variance.sqrt();
+        let rtp = if self.total_bet > 0 {
+            self.total_win as f64 / self.total_bet as f64
+        } else {
+            0.0
+        };
+        SimulationReport {
+            seed: 0,
+            rounds: self.rounds,
+            stake,
+            total_bet: self.total_bet,
+            total_win: self.total_win,
+            rtp,
+            hit_frequency: self.hits as f64 / rounds,
+            max_win: self.max_win,
+            max_win_x: if stake > 0 {
+                self.max_win as f64 / stake as f64
+            } else {
+                0.0
+            },
+            std_dev,
+            respin_frequency: self.respin_rounds as f64 / rounds,
+            free_frequency: self.free_rounds as f64 / rounds,
+            features: self
+                .features
+                .iter()
+                .map(|(feature, amount)| {
+                    let rtp = if self.total_bet > 0 {
+                        *amount / self.total_bet as f64
+                    } else {
+                        0.0
+                    };
+                    (*feature, rtp)
+                })
+                .collect(),
+            distribution: self
+                .buckets
+                .iter()
+                .enumerate()
+                .map(|(bucket, count)| WinBucket {
+                    name: bucket_name(bucket),
+                    from: if bucket == 0 {
+                        0.0
+                    } else {
+                        WIN_BUCKETS[bucket - 1]
+                    },
+                    rounds: *count,
+                    probability: *count as f64 / rounds,
+                    rtp: if self.total_bet > 0 {
+                        self.bucket_wins[bucket] as f64 / self.total_bet as f64
+                    } else {
+                        0.0
+                    },
+                })
+                .collect(),
+            cap_hits: self.cap_hits,
+            cap_probability: self.cap_hits as f64 / rounds,
+            cap_loss: if self.total_bet > 0 {
+                self.cap_loss as f64 / self.total_bet as f64
+            } else {
+                0.0
+            },
+            volatility: CONFIDENCE_LEVELS
+                .iter()
+                .map(|(confidence, z)| {
+                    let margin = z * std_dev / rounds.sqrt();
+                    VolatilityIndex {
+                        confidence: *confidence,
+                        index: z * std_dev,
+                        rtp_low: rtp - margin,
+                        rtp_high: rtp + margin,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationReport {
+    #[serde(default)]
+    pub seed: u64,
+    pub rounds: u64,
+    pub stake: i64,
+    pub total_bet: i64,
+    pub total_win: i64,
+    pub rtp: f64,
+    pub hit_frequency: f64,
+    pub max_win: i64,
+    pub max_win_x: f64,
+    pub std_dev: f64,
+    pub respin_frequency: f64,
+    pub free_frequency: f64,
+    pub features: BTreeMap<Feature, f64>,
+    #[serde(default)]
+    pub distribution: Vec<WinBucket>,
+    /// rounds which reached `calc_max_win` of the game and were stopped by `stop_factor`
+    #[serde(default)]
+    pub cap_hits: u64,
+    #[serde(default)]
+    pub cap_probability: f64,
+    /// rtp of the feature gains cut by the caps, the features without it sum to the rtp
+    #[serde(default)]
+    pub cap_loss: f64,
+    #[serde(default)]
+    pub volatility: Vec<VolatilityIndex>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WinBucket {
+    pub name: String,
+    /// lower bound of the round win in stakes
+    pub from: f64,
+    pub rounds: u64,
+    pub probability: f64,
+    pub rtp: f64,
+}
+
+/// volatility index is the standard deviation of the round win in stakes scaled by the quantile of the confidence,
+/// the rtp bounds are the confidence interval of the simulated rtp
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VolatilityIndex {
+    pub confidence: f64,
+    pub index: f64,
+    pub rtp_low: f64,
+    pub rtp_high: f64,
+}
+
+impl Display for SimulationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "seed:             {}", self.seed)?;
+        writeln!(f, "rounds:           {}", self.rounds)?;
+        writeln!(f, "stake:            {}", self.stake)?;
+        writeln!(f, "total bet:        {}", self.total_bet)?;
+        writeln!(f, "total win:        {}", self.total_win)?;
+        writeln!(f, "rtp:              {:.4}%", self.rtp * 100.0)?;
+        writeln!(f, "hit frequency:    {:.4}%", self.hit_frequency * 100.0)?;
+        writeln!(f, "max win:          {} ({:.2}x)", self.max_win, self.max_win_x)?;
+        writeln!(f, "std dev:          {:.4}", self.std_dev)?;
+        writeln!(f, "respin frequency: {:.6}", self.respin_frequency)?;
+        write!(f, "free frequency:   {:.6}", self.free_frequency)?;
+        for (feature, rtp) in self.features.iter() {
+            write!(f, "\n  rtp {:<12} {:.4}%", format!("{feature:?}:"), rtp * 100.0)?;
+        }
+        write!(f, "\n  cap loss         -{:.4}%", self.cap_loss * 100.0)?;
+        write!(f, "\ncap hits:         {} ({:.8})", self.cap_hits, self.cap_probability)?;
+        for v in self.volatility.iter() {
+            write!(
+                f,
+                "\nvolatility {:.0}%:   {:.4} rtp {:.4}%..{:.4}%",
+                v.confidence * 100.0,
+                v.index,
+                v.rtp_low * 100.0,
+                v.rtp_high * 100.0
+            )?;
+        }
+        if !self.distribution.is_empty() {
+            write!(f, "\n{:<12} {:>14} {:>14} {:>10}", "win", "rounds", "probability", "rtp")?;
+        }
+        for b in self.distribution.iter() {
+            write!(f, "\n{:<12} {:>14} {:>14.8} {:>9.4}%", b.name, b.rounds, b.probability, b.rtp * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RoundTrace {
+    pub actions: u64,
+    pub respins: u64,
+    pub free_spins: u64,
+    pub features: FeatureWins,
+    /// the round win reached the max win of the stop factor
+    pub capped: bool,
+}
+
+pub struct Simulator<M: SlotMath> {
+    pub math: M,
+    pub fsm: M::PlayFSM,
+    pub request: M::Input,
+    pub stake: i64,
+    /// max win of a round like `calc_max_win` of the games, 0 without a cap
+    pub max_win: i64,
+    step: Step,
+    split: Box<dyn FeatureSplit<M::Special, M::Restore>>,
+}
+
+impl<M: SlotMath> Simulator<M> {
+    pub fn new(math: M, arg: &SimulationArg) -> Self {
+        let request = M::Input::create_input(arg.bet, arg.line, arg.denom, arg.bet_counter);
+        let calculator = math.create_bet_calculator();
+        let stake = calculator.calc_total_bet(&request);
+        let max_win = calculator.calc_playing_bet(&request) * arg.stop_factor as i64;
+        let fsm = math.create_fsm(&arg.game_name);
+        Self {
+            math,
+            fsm,
+            request,
+            stake,
+            max_win,
+            step: Step::default(),
+            split: Box::new(ActionSplit),
+        }
+    }
+
+    pub fn with_split(mut self, split: Box<dyn FeatureSplit<M::Special, M::Restore>>) -> Self {
+        self.split = split;
+        self
+    }
+
+    pub fn run(&mut self, rounds: u64) -> Result<SimulationStats, ServerError> {
+        let mut stats = SimulationStats::default();
+        for round_id in 0..rounds {
+            let mut trace = RoundTrace::default();
+            let win = self.play_round(round_id as i64, &mut trace)?;
+            trace.capped = self.max_win > 0 && win >= self.max_win;
+            stats.add_round(self.stake, win, &trace);
+        }
+        Ok(stats)
+    }
+
+    fn spin_arg(&self, round_id: i64, stake: i64) -> SpinArg {
+        SpinArg {
+            balance: i64::MAX / 2,
+            round_id,
+            round_type: RoundDetail::SIMPLE,
+            round_multiplier: 1,
+            next_act: self.fsm.current(),
+            promo: Promo::default(),
+            stake,
+        }
+    }
+
+    /// plays a single round the way `SlotAdmin` does and returns the round win
+    pub fn play_round(&mut self, round_id: i64, trace: &mut RoundTrace) -> Result<i64, ServerError> {
+        self.fsm.reset(ActionKind::BET);
+        self.fsm.client_act(ActionKind::BET)?;
+        self.fsm.client_act(ActionKind::SPIN)?;
+        let result = self.math.spin(&self.request, self.spin_arg(round_id, self.stake), &self.step, None)?;
+        trace.actions += 1;
+        self.on_result(ActionKind::BET, result, trace)?;
+
+        let mut pick = 0;
+        loop {
+            match self.fsm.current() {
+                ActionKind::RESPIN => {
+                    self.fsm.client_act(ActionKind::RESPIN)?;
+                    let result = self.math.respin(&self.request, self.spin_arg(round_id, self.stake), &self.step, None)?;
+                    trace.actions += 1;
+                    trace.respins += 1;
+                    self.on_result(ActionKind::RESPIN, result, trace)?;
+                }
+                ActionKind::DROP => {
+                    self.fsm.client_act(ActionKind::DROP)?;
+                    let result = self.math.drop(&self.request, self.spin_arg(round_id, self.stake), &self.step, None)?;
+                    trace.actions += 1;
+                    self.on_result(ActionKind::DROP, result, trace)?;
+                }
+                ActionKind::BONUS => {
+                    self.fsm.client_act(ActionKind::BONUS)?;
+                    let result = self.math.bonus(&self.request, self.spin_arg(round_id, self.stake), &self.step, pick)?;
+                    pick += 1;
+                    trace.actions += 1;
+                    self.on_result(ActionKind::BONUS, result, trace)?;
+                }
+                ActionKind::FREE_SPIN => {
+                    self.fsm.client_act(ActionKind::FREE_SPIN)?;
+                    let result = self.math.free_spin(&self.request, self.spin_arg(round_id, self.stake), &self.step, None)?;
+                    trace.actions += 1;
+                    trace.free_spins += 1;
+                    self.on_result(ActionKind::FREE_SPIN, result, trace)?;
+                }
+                ActionKind::COLLECT | ActionKind::GAMBLE_END => {
+                    self.fsm.server_act(ActionKind::COLLECT)?;
+                    let win = self.math.collect(&self.request, self.spin_arg(round_id, 0))?.total();
+                    trace.actions += 1;
+                    return Ok(win);
+                }
+                ActionKind::CLOSE => {
+                    self.fsm.server_act(ActionKind::CLOSE)?;
+                    let win = self.math.close(self.fsm.current())?.total();
+                    return Ok(win);
+                }
+                a => return Err(err_on!(format!("simulation of {a:?} is not supported!"))),
+            }
+        }
+    }
+
+    fn on_result(&mut self, kind: ActionKind, result: GameData<M::Special, M::Restore>, trace: &mut RoundTrace) -> Result<(), ServerError> {
+        match &result {
+            GameData::Spin(v) | GameData::ReSpin(v) | GameData::FreeSpin(v) | GameData::Drop(v) => self.split.split(&kind, v, &mut trace.features),
+            GameData::Bonus(v) if v.bonus.finished => *trace.features.entry(Feature::Bonus).or_insert(0) += v.bonus.total,
+            _ => {}
+        }
+        let is_spin = kind == ActionKind::BET;
+        let left = result.free().map(|f| f.left).unwrap_or(0);
+        if kind == ActionKind::BONUS && !result.has_bonus() {
+            self.fsm.server_act(ActionKind::CLOSE)?;
+        }
+        if result.has_bonus() {
+            if is_spin {
+                self.fsm.server_act(ActionKind::BONUS_START)?;
+            }
+        } else if result.has_respin() {
+            self.fsm.server_act(ActionKind::RESPIN_START)?;
+        } else if (is_spin || kind == ActionKind::DROP) && result.has_drop() {
+            if is_spin && left > 0 {
+                self.fsm.server_act(ActionKind::FREESPIN_START)?;
+            } else {
+                self.fsm.server_act(ActionKind::DROP_START)?;
+            }
+        } else if left > 0 {
+            self.fsm.server_act(ActionKind::FREESPIN_START)?;
+        } else if result.total() > 0 {
+            if result.is_gamble_end(self.stake) {
+                self.fsm.server_act(ActionKind::GAMBLE_END)?;
+            } else {
+                self.fsm.server_act(ActionKind::COLLECT_START)?;
+            }
+        }
+        self.math.post_process(self.fsm.current(), result)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParallelArg {
+    pub threads: usize,
+    pub seed: u64,
+    /// rounds of a chunk, every chunk has its own seed derived from the master seed
+    pub chunk: u64,
+    pub checkpoint: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    pub game_name: String,
+    pub seed: u64,
+    pub chunk: u64,
+    pub rounds: u64,
+    pub stake: i64,
+    pub chunks: BTreeMap<u64, SimulationStats>,
+}
+
+impl Checkpoint {
+    pub fn new(arg: &SimulationArg, parallel: &ParallelArg) -> Self {
+        Self {
+            game_name: arg.game_name.clone(),
+            seed: parallel.seed,
+            chunk: std::cmp::max(parallel.chunk, 1),
+            rounds: arg.rounds,
+            stake: 0,
+            chunks: BTreeMap::new(),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Option<Self>, ServerError> {
+        if !Path::new(path).exists() {
+            return Ok(None);
+        }
+        let json = std::fs::read_to_string(path).map_err(|e| err_on!(e))?;
+        serde_json::from_str(&json).map(Some).map_err(|e| err_on!(e))
+    }
+
+    /// the file is replaced at once, so an interrupted save keeps the previous checkpoint
+    pub fn save(&self, path: &str) -> Result<(), ServerError> {
+        let json = serde_json::to_string(self).map_err(|e| err_on!(e))?;
+        let tmp = format!("{path}.tmp");
+        std::fs::write(&tmp, json).map_err(|e| err_on!(e))?;
+        std::fs::rename(&tmp, path).map_err(|e| err_on!(e))
+    }
+
+    pub fn chunks_total(&self) -> u64 {
+        self.rounds.div_ceil(self.chunk)
+    }
+
+    pub fn chunk_rounds(&self, chunk: u64) -> u64 {
+        std::cmp::min(self.chunk, self.rounds - chunk * self.chunk)
+    }
+
+    pub fn is_done(&self) -> bool {
+        (0..self.chunks_total()).all(|c| self.chunks.contains_key(&c))
+    }
+
+    fn check_same(&self, other: &Checkpoint) -> Result<(), ServerError> {
+        if self.game_name != other.game_name || self.seed != other.seed || self.chunk != other.chunk || self.rounds != other.rounds {
+            return Err(err_on!(format!(
+                "checkpoints do not match - game:{} seed:{} chunk:{} rounds:{}!",
+                other.game_name, other.seed, other.chunk, other.rounds
+            )));
+        }
+        Ok(())
+    }
+
+    /// joins chunks of the same run done by other processes
+    pub fn merge(&mut self, other: Checkpoint) -> Result<(), ServerError> {
+        self.check_same(&other)?;
+        if self.stake == 0 {
+            self.stake = other.stake;
+        }
+        self.chunks.extend(other.chunks);
+        Ok(())
+    }
+
+    /// chunks are merged in their order, so the float sums do not depend on the threads
+    pub fn stats(&self) -> SimulationStats {
+        let mut stats = SimulationStats::default();
+        for s in self.chunks.values() {
+            stats.merge(s);
+        }
+        stats
+    }
+
+    pub fn report(&self) -> SimulationReport {
+        let mut report = self.stats().report(self.stake);
+        report.seed = self.seed;
+        report
+    }
+}
+
+/// runs the chunks of the rounds over the threads, `factory` creates a simulator with a seeded rand for a chunk
+pub fn run_parallel<M, F>(arg: &SimulationArg, parallel: &ParallelArg, factory: F) -> Result<Checkpoint, ServerError>
+where
+    M: SlotMath,
+    F: Fn(u64) -> Result<Simulator<M>, ServerError> + Sync,
+{
+    let mut checkpoint = Checkpoint::new(arg, parallel);
+    if let Some(path) = parallel.checkpoint.as_ref() {
+        if let Some(prev) = Checkpoint::load(path)? {
+            checkpoint.merge(prev)?;
+        }
+    }
+    let pending = (0..checkpoint.chunks_total()).filter(|c| !checkpoint.chunks.contains_key(c)).collect::<Vec<_>>();
+    let threads = std::cmp::max(parallel.threads, 1);
+    let shared = Mutex::new(checkpoint);
+
+    let results = std::thread::scope(|scope| {
+        let handles = (0..threads)
+            .map(|t| {
+                let (shared, factory, pending) = (&shared, &factory, &pending);
+                scope.spawn(move || -> Result<(), ServerError> {
+                    for chunk in pending.iter().skip(t).step_by(threads) {
+                        let (seed, rounds) = {
+                            let c = shared.lock().map_err(|e| err_on!(e))?;
+                            (split_seed(c.seed, *chunk), c.chunk_rounds(*chunk))
+                        };
+                        let mut simulator = factory(seed)?;
+                        let stats = simulator.run(rounds)?;
+                        let mut c = shared.lock().map_err(|e| err_on!(e))?;
+                        c.stake = simulator.stake;
+                        c.chunks.insert(*chunk, stats);
+                        if let Some(path) = parallel.checkpoint.as_ref() {
+                            c.save(path)?;
+                        }
+                    }
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().map(|h| h.join().map_err(|_| err_on!("simulation thread panicked!"))?).collect::<Vec<_>>()
+    });
+    results.into_iter().collect::<Result<Vec<_>, _>>()?;
+    shared.into_inner().map_err(|e| err_on!(e))
+}
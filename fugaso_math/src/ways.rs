@@ -0,0 +1,128 @@
+use crate::math::Request;
+use crate::protocol::{BasePoint, Gain};
+use std::collections::HashMap;
+
+/// number of ways of a grid by the rows of the columns, like 243 for `[3; 5]` or a megaways grid with rows changing by spin
+pub fn ways_count(rows: &[usize]) -> u64 {
+    rows.iter().map(|r| *r as u64).product()
+}
+
+/// pays of the symbols on the adjacent columns from the left, the columns can have any count of rows,
+/// a win is the factor of `wins` for the count of columns multiplied by the ways
+#[derive(Debug, Clone)]
+pub struct WaysEvaluator<'a> {
+    pub wins: &'a HashMap<char, HashMap<usize, i32>>,
+    pub wilds: Vec<char>,
+    /// a wild with the multiplier counts as so many ways
+    pub wild_mults: HashMap<char, i32>,
+}
+
+impl<'a> WaysEvaluator<'a> {
+    pub fn new(wins: &'a HashMap<char, HashMap<usize, i32>>) -> Self {
+        Self {
+            wins,
+            wilds: vec![],
+            wild_mults: HashMap::new(),
+        }
+    }
+
+    pub fn with_wilds(mut self, wilds: &[char]) -> Self {
+        self.wilds = wilds.to_vec();
+        self
+    }
+
+    pub fn with_wild_mult(mut self, wild: char, mult: i32) -> Self {
+        if !self.wilds.contains(&wild) {
+            self.wilds.push(wild);
+        }
+        self.wild_mults.insert(wild, mult);
+        self
+    }
+
+    pub fn is_wild(&self, c: char) -> bool {
+        self.wilds.contains(&c)
+    }
+
+    fn weight(&self, c: char) -> i64 {
+        *self.wild_mults.get(&c).unwrap_or(&1) as i64
+    }
+
+    /// a wild symbol of `wins` is paid only for the columns of wilds
+    fn matches(&self, symbol: char, c: char) -> bool {
+        c == symbol || (!self.is_wild(symbol) && self.is_wild(c))
+    }
+
+    /// the adjacent columns with the symbol from the first one, the ways are weighted by the wild multipliers
+    fn hit(&self, grid: &Vec<Vec<char>>, symbol: char) -> (usize, i64, Vec<(usize, usize)>) {
+        let mut ways = 1;
+        let mut positions = vec![];
+        let mut count = 0;
+        for (c, column) in grid.iter().enumerate() {
+            let cells = column.iter().enumerate().filter(|(_, s)| self.matches(symbol, **s)).collect::<Vec<_>>();
+            if cells.is_empty() {
+                break;
+            }
+            ways *= cells.iter().map(|(_, s)| self.weight(**s)).sum::<i64>();
+            positions.extend(cells.iter().map(|(r, _)| (c, *r)));
+            count += 1;
+        }
+        (count, ways, positions)
+    }
+
+    /// wins of the symbols ordered by the symbol, the `multi` of a gain is the number of its ways
+    pub fn eval(&self, grid: &Vec<Vec<char>>, bet: i64) -> Vec<Gain> {
+        let columns = grid.len();
+        let mut symbols = self.wins.keys().cloned().collect::<Vec<_>>();
+        symbols.sort();
+        symbols
+            .into_iter()
+            .filter_map(|symbol| {
+                let (count, ways, positions) = self.hit(grid, symbol);
+                let factor = *self.wins.get(&symbol).and_then(|m| m.get(&count)).unwrap_or(&0);
+                if count > 0 && factor > 0 {
+                    Some(Gain {
+                        symbol,
+                        count,
+                        amount: factor as i64 * ways * bet,
+                        multi: ways as i32,
+                        columns: Some(columns),
+                        indexes: positions.iter().map(|(c, r)| r * columns + c).collect(),
+                        points: positions.iter().map(|(c, r)| BasePoint { x: *c, y: *r }).collect(),
+                        ..Default::default()
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// wins with the bet of a way of `req.bet`
+    pub fn eval_request(&self, req: &Request, grid: &Vec<Vec<char>>, multiplier: i32) -> Vec<Gain> {
+        self.eval(grid, req.bet as i64 * multiplier as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::BasePoint;
+    use crate::ways::{ways_count, WaysEvaluator};
+    use std::collections::HashMap;
+
+    #[test]
+    pub fn test_ways_evaluator() {
+        assert_eq!(ways_count(&[3; 5]), 243);
+        assert_eq!(ways_count(&[4; 5]), 1024);
+        let wins = HashMap::from([('A', HashMap::from([(3, 10)])), ('B', HashMap::from([(3, 4)]))]);
+        // megaways columns with 2, 3 and 4 rows
+        let grid = vec![vec!['A', 'B'], vec!['A', 'W', 'C'], vec!['A', 'C', 'W', 'D']];
+        let gains = WaysEvaluator::new(&wins).with_wild_mult('W', 3).eval(&grid, 1);
+        assert_eq!(gains.len(), 2);
+        // A: 1 * (1 + 3) * (1 + 3) ways
+        assert_eq!((gains[0].symbol, gains[0].count, gains[0].multi, gains[0].amount), ('A', 3, 16, 160));
+        assert_eq!(gains[0].points.len(), 5);
+        assert!(gains[0].points.contains(&BasePoint { x: 2, y: 2 }));
+        // B: 1 * 3 * 3 ways of the wilds
+        assert_eq!((gains[1].symbol, gains[1].count, gains[1].amount), ('B', 3, 36));
+    }
+}
@@ -1,7 +1,9 @@
 use std::collections::{BTreeMap, HashMap};
 
 use fugaso_math::{
-    config::BaseConfig,
+    config::{BaseConfig, ReelDist},
+    config_validator::ConfigValidator,
+    math::MathSettings,
     protocol::{deserialize_lines, deserialize_vec_reels, serialize_vec_reels},
 };
 use serde::{Deserialize, Serialize};
@@ -36,13 +38,45 @@ impl BaseConfig for ThunderExpressConfig {
     }
 }
 
+impl ThunderExpressConfig {
+    pub fn settings(&self) -> MathSettings {
+        MathSettings {
+            lines: vec![self.lines.len()],
+            reels: vec![self.reels[0].len()],
+            bet_counters: self.bet_counters.clone(),
+        }
+    }
+
+    /// `settings` are the ones of the game which the bets of the operators are made for
+    pub fn validate(&self, reels_cfg: &ReelDist, settings: &MathSettings) -> ConfigValidator {
+        let mut v = ConfigValidator::new(stringify!(ThunderExpressConfig));
+        let columns = self.reels.first().map(|r| r.len()).unwrap_or(0);
+        v.check_reels(&self.reels, thunder_express::BONUS_OFFSET + 1);
+        v.check_reel_dist(reels_cfg, thunder_express::NUM_CATEGORIES, columns, thunder_express::ROWS);
+        v.check_paytable(&self.wins, &self.reels, Some(reels_cfg), &[], columns);
+        v.check_lines(&self.lines, thunder_express::ROWS, columns);
+        if !self.reels.is_empty() {
+            v.check_bet_counters(&self.bet_counters, settings, &[("distCoin", self.dist_coin.len(), thunder_express::NUM_CATEGORIES), ("distOver", self.dist_over.len(), 1)]);
+        }
+        v.check_dists("distCoin", &self.dist_coin);
+        v.check_dists("distOver", &self.dist_over);
+        if !self.dist_base_category.is_empty() {
+            v.check_dist("distBaseCategory", &self.dist_base_category);
+        }
+        v.check_jackpots(&self.map_jack, &self.dist_coin);
+        v
+    }
+}
+
 pub mod thunder_express {
+    use std::collections::HashMap;
     use std::sync::Arc;
 
     use lazy_static::lazy_static;
 
     use super::ThunderExpressConfig;
-    use fugaso_math::config::ReelDist;
+    use fugaso_math::config::{ConfigVariant, ReelDist};
+    use fugaso_math::config_validator::ConfigValidator;
 
     pub const BASE_CATEGORY: usize = 0;
     pub const BONUS_OFFSET: usize = 2;
@@ -54,15 +88,64 @@ pub mod thunder_express {
     pub const SYM_COINS: [char; 5] = ['K', 'L', 'M', 'N', 'O']; //coin & jackpots
     pub const ROWS: usize = 3;
 
+    pub const CONFIG_JSON: &str = include_str!("resources/thunder_express.json");
+    pub const REELS_JSON: &str = include_str!("resources/thunder_express_reels.json");
+
+    /// the variants of the percents 92, 94 and 96 pay the line wins of the embedded config cut to their percent, the coins and the reels are kept
+    pub const VARIANTS: [ConfigVariant; 4] = [
+        ConfigVariant {
+            id: "thunder_express",
+            percent: 100,
+            config: None,
+            reels: None,
+            active: true,
+        },
+        ConfigVariant {
+            id: "thunder_express_92",
+            percent: 92,
+            config: Some(include_str!("resources/thunder_express_92.json")),
+            reels: None,
+            active: true,
+        },
+        ConfigVariant {
+            id: "thunder_express_94",
+            percent: 94,
+            config: Some(include_str!("resources/thunder_express_94.json")),
+            reels: None,
+            active: true,
+        },
+        ConfigVariant {
+            id: "thunder_express_96",
+            percent: 96,
+            config: Some(include_str!("resources/thunder_express_96.json")),
+            reels: None,
+            active: true,
+        },
+    ];
+
+    /// the hashes of `VARIANTS` are computed once
+    pub fn variant_hash(variant: &ConfigVariant) -> String {
+        HASHES.get(variant.id).cloned().unwrap_or_else(|| variant.hash(CONFIG_JSON, Some(REELS_JSON)))
+    }
+
+    pub fn validate(variant: &ConfigVariant) -> ConfigValidator {
+        let mut v = ConfigValidator::new(variant.id);
+        let config = v.parse::<ThunderExpressConfig>("config", variant.config.unwrap_or(CONFIG_JSON));
+        let reels_cfg = v.parse::<ReelDist>("reels", variant.reels.unwrap_or(REELS_JSON));
+        if let (Some(config), Some(reels_cfg)) = (config, reels_cfg) {
+            v.merge(config.validate(&reels_cfg, &CFG.settings()));
+        }
+        v
+    }
+
     lazy_static! {
+        static ref HASHES: HashMap<&'static str, String> = VARIANTS.iter().map(|v| (v.id, v.hash(CONFIG_JSON, Some(REELS_JSON)))).collect();
         pub static ref CFG: Arc<ThunderExpressConfig> = {
-            let json = include_str!("resources/thunder_express.json");
-            let r = serde_json::from_str(json).expect("error parse config");
+            let r = serde_json::from_str(CONFIG_JSON).expect("error parse config");
             Arc::new(r)
         };
         pub static ref REELS_CFG: Arc<ReelDist> = {
-            let json = include_str!("resources/thunder_express_reels.json");
-            let r = serde_json::from_str(json).expect("error parse config");
+            let r = serde_json::from_str(REELS_JSON).expect("error parse config");
             Arc::new(r)
         };
     }
@@ -96,12 +179,46 @@ impl BaseConfig for BonanzaLinkCashConfig {
     }
 }
 
+impl BonanzaLinkCashConfig {
+    pub fn settings(&self) -> MathSettings {
+        MathSettings {
+            lines: vec![self.lines.len()],
+            reels: vec![self.reels[0].len()],
+            bet_counters: self.bet_counters.clone(),
+        }
+    }
+
+    /// `settings` are the ones of the game which the bets of the operators are made for
+    pub fn validate(&self, settings: &MathSettings) -> ConfigValidator {
+        let mut v = ConfigValidator::new(stringify!(BonanzaLinkCashConfig));
+        let columns = self.reels.first().map(|r| r.len()).unwrap_or(0);
+        v.check_reels(&self.reels, bonanza_1000::FREE_CATEGORY + 1);
+        v.check_paytable(&self.wins, &self.reels, None, &[], columns);
+        v.check_lines(&self.lines, bonanza_1000::ROWS, columns);
+        if !self.reels.is_empty() {
+            v.check_bet_counters(&self.bet_counters, settings, &[]);
+        }
+        v.check_dist("distCoin", &self.dist_coin);
+        v.check_dist("distOver", &self.dist_over);
+        v.check_dist("distBang", &self.dist_bang);
+        v.check_chance("distShift", self.dist_shift);
+        v.check_chance("distPull", self.dist_pull);
+        for (i, chance) in self.dist_wilds.iter().enumerate() {
+            v.check_chance(&format!("distWilds[{i}]"), *chance);
+        }
+        v
+    }
+}
+
 pub mod bonanza_1000 {
+    use std::collections::HashMap;
     use std::sync::Arc;
 
     use lazy_static::lazy_static;
 
     use super::BonanzaLinkCashConfig;
+    use fugaso_math::config::ConfigVariant;
+    use fugaso_math::config_validator::ConfigValidator;
 
     pub const BASE_CATEGORY: usize = 0;
     pub const X5_CATEGORY: usize = 1;
@@ -116,11 +233,59 @@ pub mod bonanza_1000 {
     pub const MULT_LEVELS: [i32; 4] = [1, 2, 3, 10];
     pub const BET_LEVELS: [i32; 2] = [10, 15];
     pub const SCATTERS_FOR_FREE: usize = 3;
+    pub const ROWS: usize = 3;
+
+    pub const CONFIG_JSON: &str = include_str!("resources/bonanza_link_1000.json");
+
+    /// the variants of the percents 92, 94 and 96 pay the line wins of the embedded config cut to their percent, the coins and the reels are kept
+    pub const VARIANTS: [ConfigVariant; 4] = [
+        ConfigVariant {
+            id: "bonanza_link_1000",
+            percent: 100,
+            config: None,
+            reels: None,
+            active: true,
+        },
+        ConfigVariant {
+            id: "bonanza_link_1000_92",
+            percent: 92,
+            config: Some(include_str!("resources/bonanza_link_1000_92.json")),
+            reels: None,
+            active: true,
+        },
+        ConfigVariant {
+            id: "bonanza_link_1000_94",
+            percent: 94,
+            config: Some(include_str!("resources/bonanza_link_1000_94.json")),
+            reels: None,
+            active: true,
+        },
+        ConfigVariant {
+            id: "bonanza_link_1000_96",
+            percent: 96,
+            config: Some(include_str!("resources/bonanza_link_1000_96.json")),
+            reels: None,
+            active: true,
+        },
+    ];
+
+    /// the hashes of `VARIANTS` are computed once
+    pub fn variant_hash(variant: &ConfigVariant) -> String {
+        HASHES.get(variant.id).cloned().unwrap_or_else(|| variant.hash(CONFIG_JSON, None))
+    }
+
+    pub fn validate(variant: &ConfigVariant) -> ConfigValidator {
+        let mut v = ConfigValidator::new(variant.id);
+        if let Some(config) = v.parse::<BonanzaLinkCashConfig>("config", variant.config.unwrap_or(CONFIG_JSON)) {
+            v.merge(config.validate(&CFG.settings()));
+        }
+        v
+    }
 
     lazy_static! {
+        static ref HASHES: HashMap<&'static str, String> = VARIANTS.iter().map(|v| (v.id, v.hash(CONFIG_JSON, None))).collect();
         pub static ref CFG: Arc<BonanzaLinkCashConfig> = {
-            let json = include_str!("resources/bonanza_link_1000.json");
-            let r = serde_json::from_str(json).expect("error parse config");
+            let r = serde_json::from_str(CONFIG_JSON).expect("error parse config");
             Arc::new(r)
         };
     }
@@ -1,4 +1,84 @@
 pub mod math;
 pub mod rand;
 pub mod config;
-pub mod protocol;
\ No newline at end of file
+pub mod protocol;
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{bonanza_1000, thunder_express};
+    use crate::math::{BonanzaLink1000Math, ThunderExpressMath};
+    use fugaso_data::fugaso_action::ActionKind;
+    use fugaso_data::fugaso_round::RoundDetail;
+    use fugaso_math::config::select_variant;
+    use fugaso_math::math::{BetCalculator, BetDenomCounterCalculator, Request, SlotMath, SpinArg, Step};
+    use fugaso_math::protocol::Promo;
+
+    #[test]
+    pub fn test_buy_request() {
+        let request = Request {
+            bet: 5,
+            line: 1,
+            denom: 10,
+            bet_counter: 1,
+            ..Default::default()
+        };
+        assert_eq!(BetDenomCounterCalculator.calc_buy_bet(&request, 100), 5_000);
+
+        let mut thunder = ThunderExpressMath::new(None, None).expect("error math");
+        let buy = thunder.buy_request(&request).expect("error buy");
+        assert_eq!(Some(&buy.bet_counter), thunder_express::CFG.bet_counters.last());
+        assert_eq!((buy.bet, buy.denom), (request.bet, request.denom));
+        // the price of the buy is the total bet of its bet counter
+        assert_eq!(BetDenomCounterCalculator.calc_total_bet(&buy), 50 * buy.bet_counter as i64);
+
+        // the grid of the buy is not played by a spin, so the ban of the buy holds
+        let arg = SpinArg {
+            balance: 100_000,
+            round_id: 1,
+            round_type: RoundDetail::SIMPLE,
+            round_multiplier: 1,
+            next_act: ActionKind::BET,
+            promo: Promo::default(),
+            stake: 50 * buy.bet_counter as i64,
+        };
+        assert!(thunder.spin(&buy, arg.clone(), &Step::default(), None).is_err());
+        assert!(thunder.buy_spin(&request, arg.clone(), &Step::default(), None).is_err());
+        assert!(thunder.buy_spin(&buy, arg, &Step::default(), None).is_ok());
+
+        let bonanza = BonanzaLink1000Math::new(None).expect("error math");
+        let buy = bonanza.buy_request(&request).expect("error buy");
+        assert_eq!(Some(&buy.bet_counter), bonanza_1000::CFG.bet_counters.last());
+    }
+
+    #[test]
+    pub fn test_variants() {
+        // the hashes of the certified configs are stored on the rounds, a change of a config makes a new variant
+        let hashes = [
+            ("thunder_express", "6c6456d863e10549c2e06bb27bc519289c5db9f4964561a81c4775c3266613f0"),
+            ("thunder_express_92", "fcca07a55a4d55db37bb3f9b7d019b0a8b9cd3197b494322e0479ef9261c9e23"),
+            ("thunder_express_94", "71f65f3ab8c050b879b19a285e3d820b07688e3f1c56766f8b1410dc14d63520"),
+            ("thunder_express_96", "44a10b2461074996544f2323de5e8ffa1826527ae26ea293aef043f48af42cd0"),
+        ];
+        assert_eq!(thunder_express::VARIANTS.iter().map(|v| (v.id, thunder_express::variant_hash(v))).collect::<Vec<_>>(), hashes.map(|(id, h)| (id, h.to_string())));
+        for v in thunder_express::VARIANTS.iter() {
+            assert!(thunder_express::validate(v).is_valid(), "{}", thunder_express::validate(v));
+            assert!(ThunderExpressMath::new(v.config(), v.reels()).is_ok(), "{}", v.id);
+        }
+
+        let hashes = [
+            ("bonanza_link_1000", "7739e37e94221f965e00d0b38298c5a0e23ed9ae424eb3e4c7519ad878e0b45e"),
+            ("bonanza_link_1000_92", "5adccd27635faf19474eff732564e6ecb4006daa5557f3312970bfef0b95e635"),
+            ("bonanza_link_1000_94", "1f1b9d24f2f4a1d019a5631fde77d577ef2d66d5437e3428ad8b4e676a20b5b2"),
+            ("bonanza_link_1000_96", "b2fea298a13e8ef20adbdef4e62f2943b80fa7009a5728d7b2c7d5b2d28a5ff5"),
+        ];
+        assert_eq!(bonanza_1000::VARIANTS.iter().map(|v| (v.id, bonanza_1000::variant_hash(v))).collect::<Vec<_>>(), hashes.map(|(id, h)| (id, h.to_string())));
+        for v in bonanza_1000::VARIANTS.iter() {
+            assert!(bonanza_1000::validate(v).is_valid(), "{}", bonanza_1000::validate(v));
+            assert!(BonanzaLink1000Math::new(v.config()).is_ok(), "{}", v.id);
+        }
+
+        assert_eq!(select_variant(&thunder_express::VARIANTS, Some(94)).map(|v| v.id), Some("thunder_express_94"));
+        assert_eq!(select_variant(&bonanza_1000::VARIANTS, Some(92)).map(|v| v.id), Some("bonanza_link_1000_92"));
+        assert_eq!(select_variant(&thunder_express::VARIANTS, None).map(|v| v.id), Some("thunder_express"));
+    }
+}
@@ -6,9 +6,11 @@ use essential_core::error::ServerError;
 use fugaso_data::fugaso_action;
 use fugaso_data::fugaso_action::ActionKind;
 use fugaso_math::fsm::SlotFSM;
+use fugaso_math::lines::LineEvaluator;
 use fugaso_math::math::{BetCalculator, BetDenomCounterCalculator, GameInitArg, JoinArg, MathSettings, Request, SlotMath, SpinArg, Step};
 use fugaso_math::protocol::{id, DatabaseStore, FreeGame, GameData, GameResult, InitialData, StartInfo};
 use fugaso_math::protocol::{Gain, SpinData, Win};
+use fugaso_math::seed::Seed;
 use fugaso_math::validator::RequestValidator;
 use log::{debug, info};
 use std::sync::Arc;
@@ -21,9 +23,14 @@ pub struct ThunderExpressMath<R: ThunderExpressRand> {
 }
 
 impl ThunderExpressMath<ThunderExpressRandom> {
+    /// the embedded configs are validated at the startup, the custom ones are validated here
     pub fn new(config: Option<String>, reels_cfg: Option<String>) -> Result<Self, ServerError> {
+        let custom = config.is_some() || reels_cfg.is_some();
         let cfg = config.map(|j| serde_json::from_str(&j).map(|v| Arc::new(v)).map_err(|e| err_on!(e))).unwrap_or(Ok(Arc::clone(&thunder_express::CFG)))?;
         let reels_cfg_on = reels_cfg.map(|j| serde_json::from_str(&j).map(|v| Arc::new(v)).map_err(|e| err_on!(e))).unwrap_or(Ok(Arc::clone(&thunder_express::REELS_CFG)))?;
+        if custom {
+            cfg.validate(&reels_cfg_on, &thunder_express::CFG.settings()).into_result()?;
+        }
         let rand = ThunderExpressRandom::new(Arc::clone(&cfg), reels_cfg_on);
         Self::custom(rand, cfg)
     }
@@ -83,43 +90,7 @@ impl<R: ThunderExpressRand> ThunderExpressMath<R> {
     }
 
     pub fn check_lines(&mut self, req: &Request, counter_idx: usize, round_mul: i32, grid: &Vec<Vec<char>>) -> Result<(Vec<Gain>, Vec<i32>, ThunderExpressInfo), ServerError> {
-        let lines = &self.config.lines;
-        let combs = &self.config.wins;
-        let mut gains = lines
-            .iter()
-            .enumerate()
-            .filter_map(|(line_num, l)| {
-                let mut w = grid[0][l[0]];
-                let mut symbols = 0;
-
-                for j in 0..l.len() {
-                    let ch = grid[j][l[j]];
-                    if w == thunder_express::SYM_WILD {
-                        w = ch
-                    }
-                    if w == ch || ch == thunder_express::SYM_WILD {
-                        symbols += 1;
-                    } else {
-                        break;
-                    }
-                }
-
-                let factor = *combs.get(&w).and_then(|m| m.get(&symbols)).unwrap_or(&0);
-                if factor > 0 {
-                    let amount = factor as i64 * req.bet as i64 * round_mul as i64;
-                    Some(Gain {
-                        symbol: w,
-                        count: symbols,
-                        amount,
-                        line_num,
-                        multi: 1,
-                        ..Default::default()
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
+        let mut gains = LineEvaluator::new(&self.config.lines, &self.config.wins).with_wilds(&[thunder_express::SYM_WILD]).eval_request(req, grid, round_mul);
 
         let scatters = grid.iter().flat_map(|c| c.iter().filter(|v| **v == thunder_express::SYM_COLLECT)).count();
         let coins = grid.iter().flat_map(|c| c.iter().filter(|v| thunder_express::is_coin(**v))).count();
@@ -294,6 +265,67 @@ impl<R: ThunderExpressRand> ThunderExpressMath<R> {
         max
     }
 
+    /// the last bet counter is the one of the buy
+    fn buy_counter_idx(&self) -> Result<usize, ServerError> {
+        self.config.bet_counters.len().checked_sub(1).ok_or_else(|| err_on!("bet counters are empty!"))
+    }
+
+    fn spin_grid(
+        &mut self,
+        request: &Request,
+        arg: SpinArg,
+        count_idx: usize,
+        category: usize,
+        stops: Vec<usize>,
+        grid: Vec<Vec<char>>,
+    ) -> Result<GameData<ThunderExpressInfo, StartInfo>, ServerError> {
+        let (gains, holds, special) = self.check_lines(request, count_idx, arg.round_multiplier, &grid)?;
+        let total = gains.iter().map(|g| g.amount).sum();
+        let (next_act, restore) = if special.respins > 0 {
+            let grid_on = match special.overlay.as_ref() {
+                None => grid.clone(),
+                Some(o) => o.clone(),
+            };
+            (
+                ActionKind::RESPIN,
+                Some(StartInfo {
+                    grid: Some(grid_on),
+                    mults: special.mults.clone(),
+                    ..Default::default()
+                }),
+            )
+        } else {
+            (ActionKind::CLOSE, None)
+        };
+        let result = GameData::Spin(SpinData {
+            id: id::GAME_DATA,
+            balance: arg.balance - arg.stake,
+            credit_type: 100,
+            result: GameResult {
+                total,
+                stops,
+                holds,
+                grid,
+                special: Some(special),
+                gains,
+                restore,
+                ..Default::default()
+            },
+            curr_lines: request.line,
+            curr_bet: request.bet,
+            curr_denom: request.denom,
+            curr_reels: request.reels,
+            next_act,
+            category,
+            round_id: arg.round_id,
+            round_type: arg.round_type,
+            round_multiplier: arg.round_multiplier,
+            promo: arg.promo,
+            ..Default::default()
+        });
+        Ok(result)
+    }
+
     fn apply_prev(&self, current: &mut Vec<Vec<char>>, prev: &Vec<Vec<char>>) {
         for c in 0..prev.len() {
             for r in 0..prev[c].len() {
@@ -432,67 +464,39 @@ impl<R: ThunderExpressRand> SlotMath for ThunderExpressMath<R> {
 
     fn spin(&mut self, request: &Request, arg: SpinArg, _step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
         let count_idx = self.config.bet_counters.iter().position(|c| *c == request.bet_counter).ok_or_else(|| err_on!("illegal bet counter!"))?;
-        let (category, stops, grid) = if request.bet_counter < self.config.bet_counters[2] {
+        if count_idx == self.buy_counter_idx()? {
+            return Err(err_on!("bet counter of the buy is played by the bonus buy only!"));
+        }
+        let (category, stops, grid) = if count_idx < 2 {
             let category = thunder_express::BASE_CATEGORY + count_idx;
             let (stops, grid) = self.rand.rand_cols_group(category, combo)?;
             (category, stops, grid)
-        } else if request.bet_counter == self.config.bet_counters[2] {
+        } else {
             let category = thunder_express::BASE_CATEGORY;
             let (stops, grid) = self.rand.rand_cols_group(category, None)?;
             (category, stops, grid)
-        } else if request.bet_counter == self.config.bet_counters[3] {
-            let category = thunder_express::BASE_CATEGORY;
-            let (stops, grid) = self.rand.rand_buy_cols(category)?;
-            (category, stops, grid)
-        } else {
-            return Err(err_on!("illegal bet_counter!"));
         };
+        self.spin_grid(request, arg, count_idx, category, stops, grid)
+    }
 
-        let (gains, holds, special) = self.check_lines(request, count_idx, arg.round_multiplier, &grid)?;
-        let total = gains.iter().map(|g| g.amount).sum();
-        let (next_act, restore) = if special.respins > 0 {
-            let grid_on = match special.overlay.as_ref() {
-                None => grid.clone(),
-                Some(o) => o.clone(),
-            };
-            (
-                ActionKind::RESPIN,
-                Some(StartInfo {
-                    grid: Some(grid_on),
-                    mults: special.mults.clone(),
-                    ..Default::default()
-                }),
-            )
-        } else {
-            (ActionKind::CLOSE, None)
-        };
-        let result = GameData::Spin(SpinData {
-            id: id::GAME_DATA,
-            balance: arg.balance - arg.stake,
-            credit_type: 100,
-            result: GameResult {
-                total,
-                stops,
-                holds,
-                grid,
-                special: Some(special),
-                gains,
-                restore,
-                ..Default::default()
-            },
-            curr_lines: request.line,
-            curr_bet: request.bet,
-            curr_denom: request.denom,
-            curr_reels: request.reels,
-            next_act,
-            category,
-            round_id: arg.round_id,
-            round_type: arg.round_type,
-            round_multiplier: arg.round_multiplier,
-            promo: arg.promo,
-            ..Default::default()
-        });
-        Ok(result)
+    /// the buy spins the grid of the last bet counter with the collect symbols on the center column,
+    /// so the price of the buy is the total bet of this counter
+    fn buy_request(&self, request: &Request) -> Result<Request, ServerError> {
+        let bet_counter = *self.config.bet_counters.last().ok_or_else(|| err_on!("bet counters are empty!"))?;
+        Ok(Request {
+            bet_counter,
+            ..request.clone()
+        })
+    }
+
+    fn buy_spin(&mut self, request: &Request, arg: SpinArg, _step: &Step, _combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        let count_idx = self.config.bet_counters.iter().position(|c| *c == request.bet_counter).ok_or_else(|| err_on!("illegal bet counter!"))?;
+        if count_idx != self.buy_counter_idx()? {
+            return Err(err_on!("illegal bet counter of the buy!"));
+        }
+        let category = thunder_express::BASE_CATEGORY;
+        let (stops, grid) = self.rand.rand_buy_cols(category)?;
+        self.spin_grid(request, arg, count_idx, category, stops, grid)
     }
 
     fn respin(&mut self, request: &Request, arg: SpinArg, _step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
@@ -610,16 +614,16 @@ impl<R: ThunderExpressRand> SlotMath for ThunderExpressMath<R> {
     }
 
     fn settings(&self) -> MathSettings {
-        MathSettings {
-            lines: vec![self.config.lines.len()],
-            reels: vec![self.config.reels[0].len()],
-            bet_counters: self.config.bet_counters.clone(),
-        }
+        self.config.settings()
     }
 
     fn set_rand(&mut self, rand: Self::Rand) {
         self.rand = rand;
     }
+
+    fn reseed(&mut self, seed: Seed) {
+        self.rand.reseed(seed)
+    }
 }
 
 pub struct BonanzaLink1000Math<R: BonanzaLink1000Rand> {
@@ -629,8 +633,13 @@ pub struct BonanzaLink1000Math<R: BonanzaLink1000Rand> {
 }
 
 impl BonanzaLink1000Math<BonanzaLink1000Random> {
+    /// the embedded config is validated at the startup, the custom one is validated here
     pub fn new(config: Option<String>) -> Result<Self, ServerError> {
+        let custom = config.is_some();
         let cfg = config.map(|j| serde_json::from_str(&j).map(|v| Arc::new(v)).map_err(|e| err_on!(e))).unwrap_or(Ok(Arc::clone(&bonanza_1000::CFG)))?;
+        if custom {
+            cfg.validate(&bonanza_1000::CFG.settings()).into_result()?;
+        }
         let rand = BonanzaLink1000Random::new(Arc::clone(&cfg));
         Self::custom(rand, cfg)
     }
@@ -677,9 +686,6 @@ impl<R: BonanzaLink1000Rand> BonanzaLink1000Math<R> {
         prev_total: i64,
         respin: bool,
     ) -> Result<(Vec<Gain>, BonanzaLink1000Info, i32), ServerError> {
-        let lines = &self.config.lines;
-        let combs = &self.config.wins;
-
         let scatters = grid
             .iter()
             .flat_map(|c| {
@@ -719,46 +725,10 @@ impl<R: BonanzaLink1000Rand> BonanzaLink1000Math<R> {
             (0, None)
         };
 
-        let wins = lines
-            .iter()
-            .enumerate()
-            .filter_map(|(line_num, l)| {
-                let mut w = grid_on[0][l[0]];
-                let mut symbols = 0;
-
-                for j in 0..l.len() {
-                    let ch = grid_on[j][l[j]];
-                    if ch == bonanza_1000::SYM_SCAT {
-                        break;
-                    }
-
-                    if bonanza_1000::SYM_WILD == w {
-                        w = ch
-                    }
-
-                    if w == ch || bonanza_1000::SYM_WILD == ch {
-                        symbols += 1;
-                    } else {
-                        break;
-                    }
-                }
-                let factor = *combs.get(&w).and_then(|m| m.get(&symbols)).unwrap_or(&0);
-
-                if factor > 0 {
-                    let amount = factor as i64 * req.bet as i64 * multiplier as i64;
-                    Some(Gain {
-                        symbol: w,
-                        count: symbols,
-                        amount,
-                        line_num,
-                        multi: 1,
-                        ..Default::default()
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
+        let wins = LineEvaluator::new(&self.config.lines, &self.config.wins)
+            .with_wilds(&[bonanza_1000::SYM_WILD])
+            .with_breakers(&[bonanza_1000::SYM_SCAT])
+            .eval_request(req, grid_on, multiplier);
 
         let max = self.calc_max_win(req);
         let sum_current = wins.iter().map(|w| w.amount).sum::<i64>();
@@ -823,48 +793,10 @@ impl<R: BonanzaLink1000Rand> BonanzaLink1000Math<R> {
             },
         }];
 
-        let lines = &self.config.lines;
-        let combs = &self.config.wins;
-        let mut wins = lines
-            .iter()
-            .enumerate()
-            .filter_map(|(line_num, l)| {
-                let mut w = grid_on[0][l[0]];
-                let mut symbols = 0;
-
-                for j in 0..l.len() {
-                    let ch = grid_on[j][l[j]];
-                    if ch == bonanza_1000::SYM_SCAT {
-                        break;
-                    }
-
-                    if bonanza_1000::SYM_WILD == w {
-                        w = ch
-                    }
-
-                    if w == ch || bonanza_1000::SYM_WILD == ch {
-                        symbols += 1;
-                    } else {
-                        break;
-                    }
-                }
-                let factor = *combs.get(&w).and_then(|m| m.get(&symbols)).unwrap_or(&0);
-
-                if factor > 0 {
-                    let amount = factor as i64 * req.bet as i64 * multiplier as i64;
-                    Some(Gain {
-                        symbol: w,
-                        count: symbols,
-                        amount,
-                        line_num,
-                        multi: 1,
-                        ..Default::default()
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
+        let mut wins = LineEvaluator::new(&self.config.lines, &self.config.wins)
+            .with_wilds(&[bonanza_1000::SYM_WILD])
+            .with_breakers(&[bonanza_1000::SYM_SCAT])
+            .eval_request(req, grid_on, multiplier);
 
         let mults = self.rand.rand_mults(grid_on)?;
         let mult_sum = mults.iter().flat_map(|m| m).sum::<i32>();
@@ -938,7 +870,9 @@ impl<R: BonanzaLink1000Rand> SlotMath for BonanzaLink1000Math<R> {
     fn join(&self, arg: JoinArg) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
         let (result, free) = match self.result.as_ref() {
             GameData::Initial(v) => (v.result.clone(), v.free.clone()),
-            GameData::Spin(v) | GameData::ReSpin(v) | GameData::FreeSpin(v) => (v.result.clone(), v.free.clone()),
+            GameData::Spin(v) | GameData::ReSpin(v) | GameData::FreeSpin(v) | GameData::Drop(v) => (v.result.clone(), v.free.clone()),
+            GameData::Bonus(v) => (v.spin.result.clone(), v.spin.free.clone()),
+            GameData::Gamble(v) => (v.spin.result.clone(), v.spin.free.clone()),
             GameData::Collect(v) => (v.result.clone(), v.free.clone()),
         };
 
@@ -1097,6 +1031,7 @@ impl<R: BonanzaLink1000Rand> SlotMath for BonanzaLink1000Math<R> {
                     initial: free_games,
                     left: free_games,
                     done: 0,
+                    ..Default::default()
                 },
             )
         } else {
@@ -1132,6 +1067,15 @@ impl<R: BonanzaLink1000Rand> SlotMath for BonanzaLink1000Math<R> {
         Ok(result)
     }
 
+    /// the buy spins the grid of the last bet counter with the scatters of the free spins
+    fn buy_request(&self, request: &Request) -> Result<Request, ServerError> {
+        let bet_counter = *self.config.bet_counters.last().ok_or_else(|| err_on!("bet counters are empty!"))?;
+        Ok(Request {
+            bet_counter,
+            ..request.clone()
+        })
+    }
+
     fn free_spin(&mut self, request: &Request, arg: SpinArg, _step: &Step, combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
         let prev = Arc::clone(&self.result);
         let (_prev_grid, prev_info) = match prev.as_ref() {
@@ -1233,6 +1177,7 @@ impl<R: BonanzaLink1000Rand> SlotMath for BonanzaLink1000Math<R> {
                     initial: free_games,
                     left: free_games,
                     done: 0,
+                    ..Default::default()
                 },
             )
         } else {
@@ -1296,7 +1241,9 @@ impl<R: BonanzaLink1000Rand> SlotMath for BonanzaLink1000Math<R> {
     fn collect(&self, request: &Request, arg: SpinArg) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
         let (category, result, free_game) = match self.result.as_ref() {
             GameData::Initial(v) => (v.category, v.result.clone(), v.free.clone()),
-            GameData::Spin(v) | GameData::ReSpin(v) | GameData::FreeSpin(v) | GameData::Collect(v) => (v.category, v.result.clone(), v.free.clone()),
+            GameData::Spin(v) | GameData::ReSpin(v) | GameData::FreeSpin(v) | GameData::Drop(v) | GameData::Collect(v) => (v.category, v.result.clone(), v.free.clone()),
+            GameData::Bonus(v) => (v.spin.category, v.spin.result.clone(), v.spin.free.clone()),
+            GameData::Gamble(v) => (v.spin.category, v.spin.result.clone(), v.spin.free.clone()),
         };
         info!("collect: {}", result.total);
         Ok(GameData::Collect(SpinData {
@@ -1319,14 +1266,14 @@ impl<R: BonanzaLink1000Rand> SlotMath for BonanzaLink1000Math<R> {
     }
 
     fn settings(&self) -> MathSettings {
-        MathSettings {
-            lines: vec![self.config.lines.len()],
-            reels: vec![self.config.reels[0].len()],
-            bet_counters: self.config.bet_counters.clone(),
-        }
+        self.config.settings()
     }
 
     fn set_rand(&mut self, rand: Self::Rand) {
         self.rand = rand;
     }
+
+    fn reseed(&mut self, seed: Seed) {
+        self.rand.reseed(seed)
+    }
 }
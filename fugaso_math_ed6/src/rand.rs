@@ -1,9 +1,11 @@
 use std::sync::Arc;
 
-use essential_rand::{err_on, error::RandError, random::RandomGenerator};
+use essential_rand::{err_on, error::RandError};
 use fugaso_math::{
     config::{BaseConfig, ReelDist},
     rand::{BaseRandom, GroupRandom, ReelRandom, Result},
+    rng::{DefaultSource, RngSource},
+    seed::Seed,
 };
 use log::{debug, error};
 use mockall::*;
@@ -24,19 +26,27 @@ pub trait ThunderExpressRand {
     fn rand_cols(&mut self, category: usize, combos: Option<Vec<usize>>) -> (Vec<usize>, Vec<Vec<char>>);
 
     fn rand_over(&mut self, grid: &Vec<Vec<char>>, counter_idx: usize) -> Result<Option<Vec<Vec<char>>>>;
+
+    fn reseed(&mut self, seed: Seed);
 }
 
-pub struct ThunderExpressRandom {
-    pub p: GroupRandom<ThunderExpressConfig>,
+pub struct ThunderExpressRandom<R: RngSource = DefaultSource> {
+    pub p: GroupRandom<ThunderExpressConfig, R>,
 }
 
 impl ThunderExpressRandom {
     pub fn new(config: Arc<ThunderExpressConfig>, reels_cfg: Arc<ReelDist>) -> Self {
+        Self::with_source(config, reels_cfg, DefaultSource::from_entropy())
+    }
+}
+
+impl<R: RngSource> ThunderExpressRandom<R> {
+    pub fn with_source(config: Arc<ThunderExpressConfig>, reels_cfg: Arc<ReelDist>, rand: R) -> Self {
         Self {
             p: GroupRandom {
                 reels_cfg,
                 base: BaseRandom {
-                    rand: RandomGenerator::new(),
+                    rand,
                     rows: thunder_express::ROWS,
                     config,
                 },
@@ -45,7 +55,11 @@ impl ThunderExpressRandom {
     }
 }
 
-impl ThunderExpressRand for ThunderExpressRandom {
+impl<R: RngSource> ThunderExpressRand for ThunderExpressRandom<R> {
+    fn reseed(&mut self, seed: Seed) {
+        self.p.reseed(seed)
+    }
+
     fn rand_buy_cols(&mut self, category: usize) -> Result<(Vec<usize>, Vec<Vec<char>>)> {
         let (stops, mut grid) = self.p.rand_cols_group(category, None)?;
         const CENTER_COL: usize = 2;
@@ -142,18 +156,26 @@ pub trait BonanzaLink1000Rand {
     fn rand_over_coins(&mut self, category: usize, grid: &Vec<Vec<char>>) -> Result<Option<OverBonus>>;
 
     fn rand_pull(&mut self, category: usize, grid: &Vec<Vec<char>>, code_on: Vec<char>, code_off: char) -> Result<Option<OverBonus>>;
+
+    fn reseed(&mut self, seed: Seed);
 }
 
-pub struct BonanzaLink1000Random {
-    pub p: BaseRandom<BonanzaLinkCashConfig>,
+pub struct BonanzaLink1000Random<R: RngSource = DefaultSource> {
+    pub p: BaseRandom<BonanzaLinkCashConfig, R>,
 }
 
 impl BonanzaLink1000Random {
     pub fn new(config: Arc<BonanzaLinkCashConfig>) -> Self {
+        Self::with_source(config, DefaultSource::from_entropy())
+    }
+}
+
+impl<R: RngSource> BonanzaLink1000Random<R> {
+    pub fn with_source(config: Arc<BonanzaLinkCashConfig>, rand: R) -> Self {
         Self {
             p: BaseRandom {
-                rand: RandomGenerator::new(),
-                rows: 3,
+                rand,
+                rows: bonanza_1000::ROWS,
                 config,
             },
         }
@@ -209,7 +231,11 @@ impl BonanzaLink1000Random {
     }
 }
 
-impl BonanzaLink1000Rand for BonanzaLink1000Random {
+impl<R: RngSource> BonanzaLink1000Rand for BonanzaLink1000Random<R> {
+    fn reseed(&mut self, seed: Seed) {
+        self.p.reseed(seed)
+    }
+
     fn rand_mults(&mut self, grid: &Vec<Vec<char>>) -> Result<Vec<Vec<i32>>> {
         grid.iter()
             .map(|c| {
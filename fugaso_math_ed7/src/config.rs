@@ -1,7 +1,9 @@
 use std::collections::{BTreeMap, HashMap};
 
 use fugaso_math::{
-    config::BaseConfig,
+    config::{BaseConfig, ReelDist},
+    config_validator::ConfigValidator,
+    math::MathSettings,
     protocol::{deserialize_lines, deserialize_vec_reels, serialize_vec_reels},
 };
 use serde::{Deserialize, Serialize};
@@ -44,13 +46,60 @@ impl BaseConfig for MegaThunderConfig {
     }
 }
 
+impl MegaThunderConfig {
+    pub fn settings(&self) -> MathSettings {
+        MathSettings {
+            lines: vec![self.lines.len()],
+            reels: vec![self.reels[0].len()],
+            bet_counters: self.bet_counters.clone(),
+        }
+    }
+
+    /// the spins take the grid from `reels_cfg` and the respins take it from `reels` of the same category,
+    /// `settings` are the ones of the game which the bets of the operators are made for
+    pub fn validate(&self, reels_cfg: &ReelDist, settings: &MathSettings) -> ConfigValidator {
+        let mut v = ConfigValidator::new(stringify!(MegaThunderConfig));
+        v.check_reels(&self.reels, mega_thunder::BONUS_OFFSET + 1);
+        v.check_reel_dist(reels_cfg, self.reels.len(), mega_thunder::COLS, mega_thunder::ROWS);
+        v.check_paytable(&self.wins, &self.reels, Some(reels_cfg), &[], mega_thunder::COLS);
+        v.check_lines(&self.lines, mega_thunder::ROWS, mega_thunder::COLS);
+        if !self.reels.is_empty() {
+            v.check_bet_counters(
+                &self.bet_counters,
+                settings,
+                &[
+                    ("distCoinValue", self.dist_coin_value.len(), 1),
+                    ("distJackpotValue", self.dist_jackpot_value.len(), 1),
+                    ("distLiftMult", self.dist_lift_mult.len(), 1),
+                    ("distLiftSymbol", self.dist_lift_symbol.len(), 1),
+                ],
+            );
+        }
+        v.check_chance("distCoin", self.dist_coin);
+        v.check_chance("distJackpot", self.dist_jackpot);
+        v.check_chance("distLift", self.dist_lift);
+        v.check_dists("distCoinValue", &self.dist_coin_value);
+        v.check_dists("distJackpotValue", &self.dist_jackpot_value);
+        v.check_dists("distLiftMult", &self.dist_lift_mult);
+        v.check_dists("distLiftSymbol", &self.dist_lift_symbol);
+        v.check_dist("distOver", &self.dist_over);
+        v.check_dist("distOverSymbol", &self.dist_over_symbol);
+        if !self.dist_base_category.is_empty() {
+            v.check_dist("distBaseCategory", &self.dist_base_category);
+        }
+        v
+    }
+}
+
 pub mod mega_thunder {
+    use std::collections::HashMap;
     use std::sync::Arc;
 
     use lazy_static::lazy_static;
 
     use super::MegaThunderConfig;
-    use fugaso_math::config::ReelDist;
+    use fugaso_math::config::{ConfigVariant, ReelDist};
+    use fugaso_math::config_validator::ConfigValidator;
 
     pub const BASE_CATEGORY: usize = 0;
     pub const BONUS_OFFSET: usize = 1;
@@ -64,15 +113,64 @@ pub mod mega_thunder {
     pub const SYM_COIN_COLUMN: char = 'Y';
     pub const SYM_GRAND_JACKPOT: char = 'Z';
 
+    pub const CONFIG_JSON: &str = include_str!("resources/mega_thunder.json");
+    pub const REELS_JSON: &str = include_str!("resources/mega_thunder_reels.json");
+
+    /// the variants of the percents 92, 94 and 96 pay the line wins of the embedded config cut to their percent, the coins and the reels are kept
+    pub const VARIANTS: [ConfigVariant; 4] = [
+        ConfigVariant {
+            id: "mega_thunder",
+            percent: 100,
+            config: None,
+            reels: None,
+            active: true,
+        },
+        ConfigVariant {
+            id: "mega_thunder_92",
+            percent: 92,
+            config: Some(include_str!("resources/mega_thunder_92.json")),
+            reels: None,
+            active: true,
+        },
+        ConfigVariant {
+            id: "mega_thunder_94",
+            percent: 94,
+            config: Some(include_str!("resources/mega_thunder_94.json")),
+            reels: None,
+            active: true,
+        },
+        ConfigVariant {
+            id: "mega_thunder_96",
+            percent: 96,
+            config: Some(include_str!("resources/mega_thunder_96.json")),
+            reels: None,
+            active: true,
+        },
+    ];
+
+    /// the hashes of `VARIANTS` are computed once
+    pub fn variant_hash(variant: &ConfigVariant) -> String {
+        HASHES.get(variant.id).cloned().unwrap_or_else(|| variant.hash(CONFIG_JSON, Some(REELS_JSON)))
+    }
+
+    pub fn validate(variant: &ConfigVariant) -> ConfigValidator {
+        let mut v = ConfigValidator::new(variant.id);
+        let config = v.parse::<MegaThunderConfig>("config", variant.config.unwrap_or(CONFIG_JSON));
+        let reels_cfg = v.parse::<ReelDist>("reels", variant.reels.unwrap_or(REELS_JSON));
+        if let (Some(config), Some(reels_cfg)) = (config, reels_cfg) {
+            v.merge(config.validate(&reels_cfg, &CFG.settings()));
+        }
+        v
+    }
+
     lazy_static! {
+        static ref HASHES: HashMap<&'static str, String> = VARIANTS.iter().map(|v| (v.id, v.hash(CONFIG_JSON, Some(REELS_JSON)))).collect();
         pub static ref CFG: Arc<MegaThunderConfig> = {
-            let json = include_str!("resources/mega_thunder.json");
-            let r = serde_json::from_str(json).expect("error parse config");
+            let r = serde_json::from_str(CONFIG_JSON).expect("error parse config");
             Arc::new(r)
         };
         pub static ref REELS_CFG: Arc<ReelDist> = {
-            let json = include_str!("resources/mega_thunder_reels.json");
-            let r = serde_json::from_str(json).expect("error parse config");
+            let r = serde_json::from_str(REELS_JSON).expect("error parse config");
             Arc::new(r)
         };
     }
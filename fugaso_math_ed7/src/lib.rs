@@ -5,10 +5,29 @@ pub mod rand;
 
 #[cfg(test)]
 mod tests {
+    use crate::config::mega_thunder;
     use crate::math::MegaThunderMath;
+    use fugaso_math::config::select_variant;
     #[test]
     pub fn test_create() {
         let math = MegaThunderMath::new(None, None);
         assert!(math.is_ok());
     }
+
+    #[test]
+    pub fn test_variants() {
+        for v in mega_thunder::VARIANTS.iter() {
+            assert!(MegaThunderMath::new(v.config(), v.reels()).is_ok(), "{}", v.id);
+        }
+        assert_eq!(select_variant(&mega_thunder::VARIANTS, Some(mega_thunder::VARIANTS[0].percent)).map(|v| v.id), Some(mega_thunder::VARIANTS[0].id));
+        assert_eq!(select_variant(&mega_thunder::VARIANTS, Some(1)).map(|v| v.id), Some(mega_thunder::VARIANTS[0].id));
+        assert_eq!(select_variant(&mega_thunder::VARIANTS, Some(96)).map(|v| v.id), Some("mega_thunder_96"));
+        let hashes = [
+            ("mega_thunder", "678c8e3a29d355c6a1fe0d41ff63b8e88961877c73316fd9cd0d357f6f4b6d55"),
+            ("mega_thunder_92", "ad382d53a25ee67de502afecf143d424e255a7bad0eced5bfd77696a69668180"),
+            ("mega_thunder_94", "c098c2ff16dffa617d3e189c7c15918dec0865fe1c44beeed4058785679a31e0"),
+            ("mega_thunder_96", "9ca3d5c3208b6129586c4aab79c2f33c27ca30bced958129ec741217aba98fa0"),
+        ];
+        assert_eq!(mega_thunder::VARIANTS.iter().map(|v| (v.id, mega_thunder::variant_hash(v))).collect::<Vec<_>>(), hashes.map(|(id, h)| (id, h.to_string())));
+    }
 }
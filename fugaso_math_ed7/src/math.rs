@@ -6,12 +6,14 @@ use essential_core::error::ServerError;
 use fugaso_data::fugaso_action;
 use fugaso_data::fugaso_action::ActionKind;
 use fugaso_math::fsm::SlotFSM;
+use fugaso_math::lines::LineEvaluator;
 use fugaso_math::math::{
     BetCalculator, BetDenomCounterCalculator, GameInitArg, JoinArg, MathSettings, Request,
     SlotMath, SpinArg, Step,
 };
 use fugaso_math::protocol::{id, GameData, GameResult, InitialData, StartInfo};
 use fugaso_math::protocol::{Gain, SpinData, Win};
+use fugaso_math::seed::Seed;
 use fugaso_math::validator::RequestValidator;
 use log::{debug, info};
 use std::sync::Arc;
@@ -24,7 +26,9 @@ pub struct MegaThunderMath<R: MegaThunderRand> {
 }
 
 impl MegaThunderMath<MegaThunderRandom> {
+    /// the embedded configs are validated at the startup, the custom ones are validated here
     pub fn new(config: Option<String>, reels_cfg: Option<String>) -> Result<Self, ServerError> {
+        let custom = config.is_some() || reels_cfg.is_some();
         let cfg = config
             .map(|j| {
                 serde_json::from_str(&j)
@@ -39,6 +43,9 @@ impl MegaThunderMath<MegaThunderRandom> {
                     .map_err(|e| err_on!(e))
             })
             .unwrap_or(Ok(Arc::clone(&mega_thunder::REELS_CFG)))?;
+        if custom {
+            cfg.validate(&reels_cfg_on, &mega_thunder::CFG.settings()).into_result()?;
+        }
         let rand = MegaThunderRandom::new(Arc::clone(&cfg), reels_cfg_on);
         Self::custom(rand, cfg)
     }
@@ -88,39 +95,7 @@ impl<R: MegaThunderRand> MegaThunderMath<R> {
         debug!("over: {overlay:?}");
         let grid_on = overlay.as_ref().unwrap_or(grid);
 
-        let lines = &self.config.lines;
-        let combs = &self.config.wins;
-        let mut gains = lines.iter().enumerate().filter_map(|(line_num, l)| {
-            let mut w = grid[0][l[0]];
-            let mut symbols = 0;
-
-            for j in 0..l.len() {
-                let ch = grid[j][l[j]];
-                if w == mega_thunder::SYM_WILD {
-                    w = ch
-                }
-                if w == ch || ch == mega_thunder::SYM_WILD {
-                    symbols += 1;
-                } else {
-                    break;
-                }
-            }
-
-            let factor = *combs.get(&w).and_then(|m| m.get(&symbols)).unwrap_or(&0);
-            if factor > 0 {
-                let amount = factor as i64 * req.bet as i64 * round_mul as i64;
-                Some(Gain {
-                    symbol: w,
-                    count: symbols,
-                    amount,
-                    line_num,
-                    multi: 1,
-                    ..Default::default()
-                })
-            } else {
-                None
-            }
-        }).collect::<Vec<_>>();
+        let mut gains = LineEvaluator::new(&self.config.lines, &self.config.wins).with_wilds(&[mega_thunder::SYM_WILD]).eval_request(req, grid, round_mul);
         
         let coins = grid_on.iter().flat_map(|c| c.iter().filter(|v| {**v == mega_thunder::SYM_COIN || **v == mega_thunder::SYM_JACKPOT})).count();
         let mutipliers = grid_on.iter().flat_map(|c| c.iter().filter(|v| {**v == mega_thunder::SYM_MULTI})).count();
@@ -438,6 +413,59 @@ impl<R: MegaThunderRand> MegaThunderMath<R> {
         max
     }
 
+    fn spin_grid(&mut self, request: &Request, arg: SpinArg, counter_idx: usize, stops: Vec<usize>, mut grid: Vec<Vec<char>>) -> Result<GameData<MegaThunderInfo, StartInfo>, ServerError> {
+        let category = counter_idx;
+        if let Some(g) = self.rand.rand_grid_coins(&grid) {grid = g};
+        if let Some(g) = self.rand.rand_grid_jackpots(&grid) {grid = g};
+        if let Some(g) = self.rand.rand_grid_lifts(&grid) {grid = g};
+            
+        let (gains, holds, special) = self.check_lines(request, counter_idx, arg.round_multiplier, &grid)?;
+        let total = special.total;
+        let (next_act, restore) = if special.respins > 0 {
+            let grid_on = match special.overlay.as_ref() {
+                None => grid.clone(),
+                Some(o) => o.clone(),
+            };
+            (
+                ActionKind::RESPIN,
+                Some(StartInfo {
+                    grid: Some(grid_on),
+                    mults: special.mults.clone(),
+                    ..Default::default()
+                }),
+            )
+        } else {
+            (ActionKind::CLOSE, None)
+        };
+        let result = GameData::Spin(SpinData {
+            id: id::GAME_DATA,
+            balance: arg.balance - arg.stake,
+            credit_type: 100,
+            result: GameResult {
+                total,
+                stops,
+                holds,
+                grid,
+                special: Some(special),
+                gains,
+                restore,
+                ..Default::default()
+            },
+            curr_lines: request.line,
+            curr_bet: request.bet,
+            curr_denom: request.denom,
+            curr_reels: request.reels,
+            next_act,
+            category,
+            round_id: arg.round_id,
+            round_type: arg.round_type,
+            round_multiplier: arg.round_multiplier,
+            promo: arg.promo,
+            ..Default::default()
+        });
+        Ok(result)
+    }
+
     fn apply_prev(&self, current: &mut Vec<Vec<char>>, prev: &Vec<Vec<char>>) {
         for c in 0..prev.len() {
             for r in 0..prev[c].len() {
@@ -579,58 +607,20 @@ impl<R: MegaThunderRand> SlotMath for MegaThunderMath<R> {
 
     fn spin(&mut self, request: &Request, arg: SpinArg, _step: &Step, combo: Option<Vec<usize>>, ) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
         let counter_idx = self.config.bet_counters.iter().position(|c| *c == request.bet_counter).ok_or_else(|| err_on!("illegal bet counter!"))?;
-        
-        let category = counter_idx;
-        let (stops, mut grid) = self.rand.rand_spin_grid(category, combo)?;
-        if let Some(g) = self.rand.rand_grid_coins(&grid) {grid = g};
-        if let Some(g) = self.rand.rand_grid_jackpots(&grid) {grid = g};
-        if let Some(g) = self.rand.rand_grid_lifts(&grid) {grid = g};
-            
-        let (gains, holds, special) = self.check_lines(request, counter_idx, arg.round_multiplier, &grid)?;
-        let total = special.total;
-        let (next_act, restore) = if special.respins > 0 {
-            let grid_on = match special.overlay.as_ref() {
-                None => grid.clone(),
-                Some(o) => o.clone(),
-            };
-            (
-                ActionKind::RESPIN,
-                Some(StartInfo {
-                    grid: Some(grid_on),
-                    mults: special.mults.clone(),
-                    ..Default::default()
-                }),
-            )
-        } else {
-            (ActionKind::CLOSE, None)
-        };
-        let result = GameData::Spin(SpinData {
-            id: id::GAME_DATA,
-            balance: arg.balance - arg.stake,
-            credit_type: 100,
-            result: GameResult {
-                total,
-                stops,
-                holds,
-                grid,
-                special: Some(special),
-                gains,
-                restore,
-                ..Default::default()
-            },
-            curr_lines: request.line,
-            curr_bet: request.bet,
-            curr_denom: request.denom,
-            curr_reels: request.reels,
-            next_act,
-            category,
-            round_id: arg.round_id,
-            round_type: arg.round_type,
-            round_multiplier: arg.round_multiplier,
-            promo: arg.promo,
-            ..Default::default()
-        });
-        Ok(result)
+        let (stops, grid) = self.rand.rand_spin_grid(counter_idx, combo)?;
+        self.spin_grid(request, arg, counter_idx, stops, grid)
+    }
+
+    /// the buy keeps the bet counter of the player
+    fn buy_request(&self, request: &Request) -> Result<Request, ServerError> {
+        Ok(request.clone())
+    }
+
+    /// the grid of the buy has the specials on every column
+    fn buy_spin(&mut self, request: &Request, arg: SpinArg, _step: &Step, _combo: Option<Vec<usize>>) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
+        let counter_idx = self.config.bet_counters.iter().position(|c| *c == request.bet_counter).ok_or_else(|| err_on!("illegal bet counter!"))?;
+        let (stops, grid) = self.rand.rand_buy_spin_grid(counter_idx)?;
+        self.spin_grid(request, arg, counter_idx, stops, grid)
     }
 
     fn respin(&mut self, request: &Request, arg: SpinArg, _step: &Step, combo: Option<Vec<usize>>, ) -> Result<GameData<Self::Special, Self::Restore>, ServerError> {
@@ -767,14 +757,14 @@ impl<R: MegaThunderRand> SlotMath for MegaThunderMath<R> {
     }
 
     fn settings(&self) -> MathSettings {
-        MathSettings {
-            lines: vec![self.config.lines.len()],
-            reels: vec![self.config.reels[0].len()],
-            bet_counters: self.config.bet_counters.clone(),
-        }
+        self.config.settings()
     }
 
     fn set_rand(&mut self, rand: Self::Rand) {
         self.rand = rand;
     }
+
+    fn reseed(&mut self, seed: Seed) {
+        self.rand.reseed(seed)
+    }
 }
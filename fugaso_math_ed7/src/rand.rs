@@ -1,9 +1,11 @@
 use std::sync::Arc;
 
-use essential_rand::{err_on, error::RandError, random::RandomGenerator};
+use essential_rand::{err_on, error::RandError};
 use fugaso_math::{
     config::ReelDist,
     rand::{BaseRandom, GroupRandom, Result},
+    rng::{DefaultSource, RngSource},
+    seed::Seed,
 };
 use log::debug;
 use mockall::*;
@@ -27,19 +29,27 @@ pub trait MegaThunderRand {
     fn rand_jackpots_values(&mut self, grid: &Vec<Vec<char>>, mults: &Vec<Vec<i32>>, counter_idx: usize) -> Option<Vec<Vec<i32>>>;
     fn rand_lifts_values_mults(&mut self, grid: &Vec<Vec<char>>, counter_idx: usize) -> Result<Vec<LiftItem>>;
 
+    fn reseed(&mut self, seed: Seed);
+
 }
 
-pub struct MegaThunderRandom {
-    pub p: GroupRandom<MegaThunderConfig>,
+pub struct MegaThunderRandom<R: RngSource = DefaultSource> {
+    pub p: GroupRandom<MegaThunderConfig, R>,
 }
 
 impl MegaThunderRandom {
     pub fn new(config: Arc<MegaThunderConfig>, reels_cfg: Arc<ReelDist>) -> Self {
+        Self::with_source(config, reels_cfg, DefaultSource::from_entropy())
+    }
+}
+
+impl<R: RngSource> MegaThunderRandom<R> {
+    pub fn with_source(config: Arc<MegaThunderConfig>, reels_cfg: Arc<ReelDist>, rand: R) -> Self {
         Self {
             p: GroupRandom {
                 reels_cfg,
                 base: BaseRandom {
-                    rand: RandomGenerator::new(),
+                    rand,
                     rows: mega_thunder::ROWS,
                     config,
                 },
@@ -48,7 +58,10 @@ impl MegaThunderRandom {
     }
 }
 
-impl MegaThunderRand for MegaThunderRandom {
+impl<R: RngSource> MegaThunderRand for MegaThunderRandom<R> {
+    fn reseed(&mut self, seed: Seed) {
+        self.p.reseed(seed)
+    }
 
     fn rand_buy_spin_grid(&mut self, category: usize) -> Result<(Vec<usize>, Vec<Vec<char>>)> {
         let reels = &self.p.reels_cfg[category];
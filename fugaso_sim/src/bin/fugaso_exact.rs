@@ -0,0 +1,6 @@
+use fugaso_sim::cli::main_with;
+use fugaso_sim::exact::{run, ExactCommand};
+
+fn main() {
+    main_with(ExactCommand::parse, run)
+}
@@ -0,0 +1,6 @@
+use fugaso_sim::cli::main_with;
+use fugaso_sim::fsm::{run, FsmCommand};
+
+fn main() {
+    main_with(FsmCommand::parse, run)
+}
@@ -0,0 +1,6 @@
+use fugaso_sim::cli::main_with;
+use fugaso_sim::optimize::{run, OptimizeCommand};
+
+fn main() {
+    main_with(OptimizeCommand::parse, run)
+}
@@ -0,0 +1,6 @@
+use fugaso_sim::cli::main_with;
+use fugaso_sim::par::{run, ParCommand};
+
+fn main() {
+    main_with(ParCommand::parse, run)
+}
@@ -0,0 +1,6 @@
+use fugaso_sim::cli::main_with;
+use fugaso_sim::resim::{run, ResimCommand};
+
+fn main() {
+    main_with(ResimCommand::parse, run)
+}
@@ -0,0 +1,6 @@
+use fugaso_sim::cli::main_with;
+use fugaso_sim::rng_check::{run, RngCheckCommand};
+
+fn main() {
+    main_with(RngCheckCommand::parse, run)
+}
@@ -0,0 +1,6 @@
+use fugaso_sim::cli::main_with;
+use fugaso_sim::validate::{run, ValidateCommand};
+
+fn main() {
+    main_with(ValidateCommand::parse, run)
+}
@@ -0,0 +1,6 @@
+use fugaso_sim::cli::main_with;
+use fugaso_sim::variants::{run, VariantsCommand};
+
+fn main() {
+    main_with(VariantsCommand::parse, run)
+}
@@ -0,0 +1,40 @@
+use essential_core::error::ServerError;
+use serde::Serialize;
+use std::fmt::Display;
+
+/// command of a bin, the report goes as json with `--json`
+pub trait Command {
+    fn json(&self) -> bool;
+}
+
+/// report of a bin, the bin exits with 2 when the report does not pass
+pub trait Report: Display + Serialize {
+    fn passed(&self) -> bool {
+        true
+    }
+}
+
+/// main of the bins, the command is parsed from the args of the bin and its report is printed, the bin exits with 1 on an error
+pub fn main_with<C: Command, R: Report>(parse: fn(&[String]) -> Result<C, ServerError>, run: fn(C) -> Result<R, ServerError>) {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let result = parse(&args).and_then(|command| {
+        let json = command.json();
+        run(command).map(|report| (json, report))
+    });
+    match result {
+        Ok((json, report)) => {
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report).expect("error serialize report"));
+            } else {
+                println!("{report}");
+            }
+            if !report.passed() {
+                std::process::exit(2);
+            }
+        }
+        Err(e) => {
+            eprintln!("{e:?}");
+            std::process::exit(1);
+        }
+    }
+}
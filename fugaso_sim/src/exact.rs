@@ -0,0 +1,75 @@
+use crate::cli::{Command, Report};
+use crate::options::Options;
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use fugaso_math::exact::{dist_windows, strip_windows, ExactCalculator, ExactReport};
+use fugaso_math::lines::LineEvaluator;
+use fugaso_math::math::{BetCalculator, IRequest, Request, SlotMath};
+use fugaso_math_ed6::config::{bonanza_1000, thunder_express};
+use fugaso_math_ed6::math::{BonanzaLink1000Math, ThunderExpressMath};
+
+#[derive(Debug)]
+pub struct ExactCommand {
+    pub math_class: String,
+    pub config: Option<String>,
+    pub reels_cfg: Option<String>,
+    pub category: usize,
+    pub stake: Option<f64>,
+    pub json: bool,
+}
+
+impl ExactCommand {
+    pub fn parse(args: &[String]) -> Result<Self, ServerError> {
+        let options = Options::parse(args, &["--category", "--stake", "--config", "--reels"], &["--json"])?;
+        Ok(Self {
+            category: options.value_or("--category", 0)?,
+            stake: options.value("--stake")?,
+            config: options.file("--config")?,
+            reels_cfg: options.file("--reels")?,
+            json: options.flag("--json"),
+            math_class: options.math_class,
+        })
+    }
+}
+
+impl Command for ExactCommand {
+    fn json(&self) -> bool {
+        self.json
+    }
+}
+
+impl Report for ExactReport {}
+
+/// total bet of the category in line bets, when it is not passed explicitly
+fn calc_stake<M: SlotMath<Input = Request>>(math: &M, category: usize) -> f64 {
+    let settings = math.settings();
+    let bet_counter = settings.bet_counters.get(category).or(settings.bet_counters.get(0)).cloned().unwrap_or(1);
+    let line = settings.lines.get(0).cloned().unwrap_or(1);
+    let request = Request::create_input(1, line, 1, bet_counter);
+    math.create_bet_calculator().calc_total_bet(&request) as f64
+}
+
+/// base game line RTP of the games which spin their reels without overlays
+pub fn run(command: ExactCommand) -> Result<ExactReport, ServerError> {
+    let category = command.category;
+    if command.math_class == stringify!(ThunderExpressMath) {
+        let math = ThunderExpressMath::new(command.config, command.reels_cfg)?;
+        let reels = math.rand.p.reels_cfg.get(category).ok_or_else(|| err_on!(format!("category {category} is absent!")))?;
+        let columns = reels.iter().map(dist_windows).collect::<Result<Vec<_>, _>>()?;
+        let evaluator = LineEvaluator::new(&math.config.lines, &math.config.wins).with_wilds(&[thunder_express::SYM_WILD]);
+        let stake = command.stake.unwrap_or_else(|| calc_stake(&math, category));
+        ExactCalculator::new(evaluator).calc(&columns, stake)
+    } else if command.math_class == stringify!(BonanzaLink1000Math) {
+        let math = BonanzaLink1000Math::new(command.config)?;
+        let reels = math.config.reels.get(category).ok_or_else(|| err_on!(format!("category {category} is absent!")))?;
+        let columns = reels.iter().map(|r| strip_windows(r, math.rand.p.rows)).collect::<Vec<_>>();
+        // the scatters stop a line like `BonanzaLink1000Math::check_lines`
+        let evaluator = LineEvaluator::new(&math.config.lines, &math.config.wins).with_wilds(&[bonanza_1000::SYM_WILD]).with_breakers(&[bonanza_1000::SYM_SCAT]);
+        let stake = command.stake.unwrap_or_else(|| calc_stake(&math, category));
+        ExactCalculator::new(evaluator).calc(&columns, stake)
+    } else if command.math_class == stringify!(MegaThunderMath) {
+        Err(err_on!("coins are placed over the reels of MegaThunderMath, the base game is not driven by reels only!"))
+    } else {
+        Err(err_on!("game is not supported!"))
+    }
+}
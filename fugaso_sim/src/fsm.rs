@@ -0,0 +1,90 @@
+use crate::cli::{Command, Report};
+use crate::options::Options;
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use fugaso_math::config_validator::ConfigValidator;
+use fugaso_math::configurable::GameDefinition;
+use fugaso_math::fsm::FsmDefinition;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// `<math class> [--fsm file] [--config file] [--mermaid] [--json]`, the games without the definition take the default transitions of `SlotFSM`
+#[derive(Debug)]
+pub struct FsmCommand {
+    pub math_class: String,
+    pub fsm: Option<String>,
+    pub config: Option<String>,
+    pub mermaid: bool,
+    pub json: bool,
+}
+
+impl FsmCommand {
+    pub fn parse(args: &[String]) -> Result<Self, ServerError> {
+        let options = Options::parse(args, &["--fsm", "--config"], &["--mermaid", "--json"])?;
+        Ok(Self {
+            fsm: options.file("--fsm")?,
+            config: options.file("--config")?,
+            mermaid: options.flag("--mermaid"),
+            json: options.flag("--json"),
+            math_class: options.math_class,
+        })
+    }
+}
+
+impl Command for FsmCommand {
+    fn json(&self) -> bool {
+        self.json
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FsmReport {
+    pub math_class: String,
+    pub issues: ConfigValidator,
+    /// graph in the dot language or the mermaid state diagram
+    pub graph: String,
+}
+
+impl FsmReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_valid()
+    }
+}
+
+impl Display for FsmReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:\n{}\n\n{}", self.math_class, self.issues, self.graph)
+    }
+}
+
+impl Report for FsmReport {
+    fn passed(&self) -> bool {
+        self.is_valid()
+    }
+}
+
+/// the file of `--fsm` goes before the transitions of the definition of `ConfigurableSlotMath`
+fn definition(command: &FsmCommand) -> Result<FsmDefinition, ServerError> {
+    if let Some(json) = command.fsm.as_ref() {
+        FsmDefinition::from_json(json)
+    } else if let Some(json) = command.config.as_ref().filter(|_| command.math_class == stringify!(ConfigurableSlotMath)) {
+        let config = serde_json::from_str::<GameDefinition>(json).map_err(|e| err_on!(e))?;
+        Ok(config.fsm.unwrap_or_default())
+    } else {
+        Ok(FsmDefinition::default())
+    }
+}
+
+pub fn run(command: FsmCommand) -> Result<FsmReport, ServerError> {
+    let fsm = definition(&command)?;
+    Ok(FsmReport {
+        issues: fsm.verify(),
+        graph: if command.mermaid {
+            fsm.to_mermaid()
+        } else {
+            fsm.to_dot(&command.math_class)
+        },
+        math_class: command.math_class,
+    })
+}
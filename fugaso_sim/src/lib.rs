@@ -0,0 +1,549 @@
+pub mod cli;
+pub mod exact;
+pub mod fsm;
+pub mod optimize;
+pub mod options;
+pub mod par;
+pub mod resim;
+pub mod rng_check;
+pub mod split;
+pub mod validate;
+pub mod variants;
+
+use crate::cli::{Command, Report};
+use crate::options::Options;
+use crate::split::{BonanzaLink1000Split, MegaThunderSplit, ThunderExpressSplit};
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use fugaso_math::configurable::ConfigurableSlotMath;
+use fugaso_math::math::SlotMath;
+use essential_rand::random::RandomGenerator;
+use fugaso_math::simulate::{run_parallel, ActionSplit, Checkpoint, FeatureSplit, ParallelArg, SimulationArg, SimulationReport, Simulator};
+use fugaso_math_ed6::math::{BonanzaLink1000Math, ThunderExpressMath};
+use fugaso_math_ed7::math::MegaThunderMath;
+use log::info;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct SimCommand {
+    pub math_class: String,
+    pub config: Option<String>,
+    pub reels_cfg: Option<String>,
+    pub json: bool,
+    pub arg: SimulationArg,
+    pub parallel: ParallelArg,
+}
+
+impl SimCommand {
+    pub fn parse(args: &[String]) -> Result<Self, ServerError> {
+        let options = Options::parse(
+            args,
+            &["--rounds", "--bet", "--line", "--denom", "--bet-counter", "--config", "--reels", "--threads", "--seed", "--chunk", "--checkpoint"],
+            &["--json"],
+        )?;
+        let seed = match options.value("--seed")? {
+            Some(s) => s,
+            None => RandomGenerator::new().random_i64() as u64,
+        };
+        Ok(Self {
+            parallel: ParallelArg {
+                threads: options.value_or("--threads", std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1))?,
+                seed,
+                chunk: options.value_or("--chunk", 100_000)?,
+                checkpoint: options.value("--checkpoint")?,
+            },
+            arg: SimulationArg {
+                game_name: options.math_class.clone(),
+                rounds: options.value_or("--rounds", 1_000_000)?,
+                bet: options.value_or("--bet", 1)?,
+                line: options.value_or("--line", 0)?,
+                denom: options.value_or("--denom", 1)?,
+                bet_counter: options.value_or("--bet-counter", 0)?,
+                stop_factor: 0,
+            },
+            config: options.file("--config")?,
+            reels_cfg: options.file("--reels")?,
+            json: options.flag("--json"),
+            math_class: options.math_class,
+        })
+    }
+}
+
+impl Command for SimCommand {
+    fn json(&self) -> bool {
+        self.json
+    }
+}
+
+impl Report for SimulationReport {}
+
+/// command of `fugaso_sim`, a simulation of a game or the merge of the checkpoints of a run
+#[derive(Debug)]
+pub enum MainCommand {
+    Sim(SimCommand),
+    Merge {
+        out: String,
+        inputs: Vec<String>,
+        json: bool,
+    },
+}
+
+impl MainCommand {
+    /// `<math class> [options]` or `merge <out> <checkpoint>... [--json]`
+    pub fn parse(args: &[String]) -> Result<Self, ServerError> {
+        if args.first().map(|a| a == "merge").unwrap_or(false) {
+            let json = args.iter().any(|a| a == "--json");
+            let mut paths = args.iter().skip(1).filter(|a| *a != "--json").cloned();
+            let out = paths.next().ok_or_else(|| err_on!("output checkpoint is absent!"))?;
+            Ok(Self::Merge {
+                out,
+                inputs: paths.collect(),
+                json,
+            })
+        } else {
+            SimCommand::parse(args).map(Self::Sim)
+        }
+    }
+}
+
+impl Command for MainCommand {
+    fn json(&self) -> bool {
+        match self {
+            Self::Sim(c) => c.json,
+            Self::Merge {
+                json,
+                ..
+            } => *json,
+        }
+    }
+}
+
+pub fn run_main(command: MainCommand) -> Result<SimulationReport, ServerError> {
+    match command {
+        MainCommand::Sim(c) => run(c),
+        MainCommand::Merge {
+            out,
+            inputs,
+            ..
+        } => merge(&out, &inputs),
+    }
+}
+
+/// line and bet counter of the arguments, the first ones of the game when they are not passed
+fn resolve_arg<M: SlotMath>(math: &M, arg: &SimulationArg) -> SimulationArg {
+    let settings = math.settings();
+    SimulationArg {
+        line: if arg.line == 0 {
+            settings.lines.get(0).cloned().unwrap_or(1)
+        } else {
+            arg.line
+        },
+        bet_counter: if arg.bet_counter == 0 {
+            settings.bet_counters.get(0).cloned().unwrap_or(1)
+        } else {
+            arg.bet_counter
+        },
+        ..arg.clone()
+    }
+}
+
+pub fn simulate<M: SlotMath>(math: M, split: Box<dyn FeatureSplit<M::Special, M::Restore>>, arg: &SimulationArg) -> Result<SimulationReport, ServerError> {
+    let arg = resolve_arg(&math, arg);
+    info!("simulation: {arg:?}");
+    let mut simulator = Simulator::new(math, &arg).with_split(split);
+    let stats = simulator.run(arg.rounds)?;
+    Ok(stats.report(simulator.stake))
+}
+
+/// `create` builds the game which is reseeded by the seed of a chunk, the same seed and chunk size give the same result on any number of threads,
+/// `stop_factor` takes the cap of the wins from the config of the game
+pub fn simulate_parallel<M, C>(create: C, stop_factor: fn(&M) -> i32, arg: &SimulationArg, parallel: &ParallelArg) -> Result<Checkpoint, ServerError>
+where
+    M: SlotMath,
+    C: Fn() -> Result<(M, Box<dyn FeatureSplit<M::Special, M::Restore>>), ServerError> + Sync,
+{
+    let (probe, _) = create()?;
+    let arg = SimulationArg {
+        stop_factor: stop_factor(&probe),
+        ..resolve_arg(&probe, arg)
+    };
+    info!("simulation: {arg:?} {parallel:?}");
+    run_parallel(&arg, parallel, |seed| {
+        let (mut math, split) = create()?;
+        math.reseed(seed.into());
+        Ok(Simulator::new(math, &arg).with_split(split))
+    })
+}
+
+pub fn run(command: SimCommand) -> Result<SimulationReport, ServerError> {
+    let (config, reels_cfg) = (command.config, command.reels_cfg);
+    let (arg, parallel) = (&command.arg, &command.parallel);
+    let checkpoint = if command.math_class == stringify!(ThunderExpressMath) {
+        simulate_parallel(
+            || {
+                let math = ThunderExpressMath::new(config.clone(), reels_cfg.clone())?;
+                let split: Box<dyn FeatureSplit<_, _>> = Box::new(ThunderExpressSplit::new(Arc::clone(&math.config)));
+                Ok((math, split))
+            },
+            |m| m.config.stop_factor,
+            arg,
+            parallel,
+        )?
+    } else if command.math_class == stringify!(BonanzaLink1000Math) {
+        simulate_parallel(
+            || {
+                let math = BonanzaLink1000Math::new(config.clone())?;
+                let split: Box<dyn FeatureSplit<_, _>> = Box::new(BonanzaLink1000Split);
+                Ok((math, split))
+            },
+            |m| m.config.stop_factor,
+            arg,
+            parallel,
+        )?
+    } else if command.math_class == stringify!(MegaThunderMath) {
+        simulate_parallel(
+            || {
+                let math = MegaThunderMath::new(config.clone(), reels_cfg.clone())?;
+                let split: Box<dyn FeatureSplit<_, _>> = Box::new(MegaThunderSplit);
+                Ok((math, split))
+            },
+            |m| m.config.stop_factor,
+            arg,
+            parallel,
+        )?
+    } else if command.math_class == stringify!(ConfigurableSlotMath) {
+        let json = config.ok_or_else(|| err_on!("definition of ConfigurableSlotMath is absent!"))?;
+        simulate_parallel(
+            || {
+                let math = ConfigurableSlotMath::new(&json)?;
+                let split: Box<dyn FeatureSplit<_, _>> = Box::new(ActionSplit);
+                Ok((math, split))
+            },
+            |m| m.config.max_win,
+            arg,
+            parallel,
+        )?
+    } else {
+        return Err(err_on!("game is not supported!"));
+    };
+    Ok(checkpoint.report())
+}
+
+/// joins the checkpoints of a run made by several processes into `out`
+pub fn merge(out: &str, inputs: &[String]) -> Result<SimulationReport, ServerError> {
+    let mut merged: Option<Checkpoint> = None;
+    for path in inputs {
+        let checkpoint = Checkpoint::load(path)?.ok_or_else(|| err_on!(format!("checkpoint {path} is absent!")))?;
+        match merged.as_mut() {
+            Some(m) => m.merge(checkpoint)?,
+            None => merged = Some(checkpoint),
+        }
+    }
+    let merged = merged.ok_or_else(|| err_on!("checkpoints are absent!"))?;
+    merged.save(out)?;
+    Ok(merged.report())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::Command;
+    use crate::exact::ExactCommand;
+    use crate::fsm::FsmCommand;
+    use crate::optimize::tilt;
+    use crate::par::ParCommand;
+    use crate::resim::ResimCommand;
+    use crate::rng_check::{check, find_dists, RngCheckCommand};
+    use crate::split::ThunderExpressSplit;
+    use crate::validate::ValidateCommand;
+    use crate::variants::VariantsCommand;
+    use crate::{exact, fsm, merge, par, resim, rng_check, run, validate, variants, MainCommand, SimCommand};
+    use fugaso_math::config::{config_hash, sha256_hex};
+    use fugaso_math::config_validator::IssueKind;
+    use fugaso_math::configurable::ConfigurableSlotMath;
+    use fugaso_math::fair::FairSeeds;
+    use fugaso_math::fsm::FsmDefinition;
+    use fugaso_math::math::{SlotMath, Step};
+    use fugaso_math::rng::{CounterSource, RngSource};
+    use fugaso_math::seed::{RoundRecord, Seed};
+    use fugaso_math::simulate::{Feature, FeatureWins, RoundTrace, SimulationArg, SimulationStats, Simulator};
+    use fugaso_math_ed6::config::{thunder_express, ThunderExpressConfig};
+    use fugaso_math_ed6::math::{BonanzaLink1000Math, ThunderExpressMath};
+    use std::sync::Arc;
+
+    #[test]
+    pub fn test_simulate() {
+        for math_class in ["ThunderExpressMath", "BonanzaLink1000Math", "MegaThunderMath"] {
+            let args = vec![math_class.to_string(), "--rounds".to_string(), "1000".to_string()];
+            let command = SimCommand::parse(&args).expect("error parse command");
+            let report = run(command).expect("error simulate");
+            assert_eq!(report.rounds, 1000);
+            assert_eq!(report.total_bet, report.stake * 1000);
+            let features = report.features.values().sum::<f64>();
+            assert!((features - report.cap_loss - report.rtp).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    pub fn test_simulate_features() {
+        let arg = SimulationArg {
+            game_name: "ThunderExpressMath".to_string(),
+            rounds: 2000,
+            bet: 1,
+            line: 0,
+            denom: 1,
+            bet_counter: 0,
+            stop_factor: 0,
+        };
+        let create = || {
+            let mut math = ThunderExpressMath::new(None, None).expect("error math");
+            math.reseed(31.into());
+            let split = Box::new(ThunderExpressSplit::new(Arc::clone(&math.config)));
+            (Simulator::new(math, &arg).with_split(split), SimulationStats::default())
+        };
+        let (mut simulator, _) = create();
+        let stats = simulator.run(arg.rounds).expect("error simulate");
+
+        // the same seed played round by round gives the raw gains of the features
+        let (mut replay, mut expected) = create();
+        let mut raw = FeatureWins::new();
+        for round_id in 0..arg.rounds {
+            let mut trace = RoundTrace::default();
+            let win = replay.play_round(round_id as i64, &mut trace).expect("error round");
+            expected.total_win += win;
+            trace.features.iter().for_each(|(f, a)| *raw.entry(*f).or_insert(0) += a);
+        }
+        assert_eq!(stats.total_win, expected.total_win);
+        for (feature, amount) in raw.iter().filter(|f| *f.1 != 0) {
+            assert_eq!(stats.features.get(feature).cloned(), Some(*amount as f64), "{feature:?}");
+        }
+        assert!(raw.get(&Feature::Line).cloned().unwrap_or(0) > 0);
+        assert!(raw.get(&Feature::Respin).cloned().unwrap_or(0) + raw.get(&Feature::Collect).cloned().unwrap_or(0) > 0);
+        let features = stats.features.values().sum::<f64>() as i64;
+        assert_eq!(features - stats.cap_loss, stats.total_win);
+    }
+
+    #[test]
+    pub fn test_simulate_seed() {
+        let command = |threads: &str, checkpoint: &str| {
+            let args = ["ThunderExpressMath", "--rounds", "3000", "--chunk", "500", "--seed", "77", "--threads", threads, "--checkpoint", checkpoint];
+            SimCommand::parse(&args.map(|a| a.to_string())).expect("error parse command")
+        };
+        let dir = std::env::temp_dir();
+        let paths = ["seed_1.json", "seed_4.json"].map(|p| dir.join(p).to_string_lossy().to_string());
+        paths.iter().for_each(|p| {
+            let _ = std::fs::remove_file(p);
+        });
+        let report_1 = run(command("1", &paths[0])).expect("error simulate");
+        let report_4 = run(command("4", &paths[1])).expect("error simulate");
+        assert_eq!(report_1.total_win, report_4.total_win);
+        assert_eq!(report_1.hit_frequency, report_4.hit_frequency);
+        assert_eq!(report_1.seed, 77);
+        // a finished checkpoint is resumed without new rounds
+        let resumed = run(command("2", &paths[0])).expect("error resume");
+        assert_eq!(resumed.total_win, report_1.total_win);
+        let merged = merge(&paths[1], &paths).expect("error merge");
+        assert_eq!(merged.rounds, 3000);
+        assert_eq!(merged.total_win, report_1.total_win);
+    }
+
+    #[test]
+    pub fn test_simulate_distribution() {
+        let args = ["MegaThunderMath", "--rounds", "2000", "--seed", "5"].map(|a| a.to_string());
+        let report = run(SimCommand::parse(&args).expect("error parse command")).expect("error simulate");
+        assert_eq!(report.distribution.len(), 12);
+        assert_eq!(report.distribution.iter().map(|b| b.rounds).sum::<u64>(), 2000);
+        let rtp = report.distribution.iter().map(|b| b.rtp).sum::<f64>();
+        assert!((rtp - report.rtp).abs() < 1e-9);
+        assert!((report.distribution[0].probability - (1.0 - report.hit_frequency)).abs() < 1e-9);
+        let v = &report.volatility[1];
+        assert!(v.rtp_low <= report.rtp && report.rtp <= v.rtp_high);
+    }
+
+    #[test]
+    pub fn test_par_sheet() {
+        for config_class in ["ThunderExpressConfig", "MegaThunderConfig", "BonanzaLinkCashConfig"] {
+            let sheet = par::run(ParCommand::parse(&vec![config_class.to_string()]).expect("error parse command")).expect("error par sheet");
+            assert!(!sheet.reels.is_empty());
+            assert!(!sheet.paytable.is_empty());
+            assert!(sheet.lines.iter().all(|l| l.notation.chars().count() == l.rows.len()));
+            for d in sheet.dists.iter() {
+                let sum = d.entries.iter().map(|e| e.probability).sum::<f64>();
+                assert!((sum - 1.0).abs() < 1e-9, "{}", d.name);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_tilt() {
+        let mut dist = serde_json::json!({"50": 1, "80": 2, "100": 5});
+        tilt(&mut dist, None, 1.0).expect("error tilt");
+        let map = dist.as_object().expect("error object");
+        let bounds = map.keys().map(|k| k.parse::<i32>().expect("error bound")).collect::<Vec<_>>();
+        assert_eq!(bounds.iter().max(), Some(&100));
+        assert_eq!(map.get("100").and_then(|v| v.as_i64()), Some(5));
+        // the weight of the lowest value 1 is moved to the highest value 5
+        assert!(bounds.iter().min().cloned().unwrap_or(0) < 50);
+
+        // the weights raised to 1 are taken from the others, the total stays exact
+        let mut dist = serde_json::json!({"1": 1, "2": 2, "3": 3, "1000": 5});
+        tilt(&mut dist, None, 20.0).expect("error tilt");
+        let bounds = dist.as_object().expect("error object").keys().map(|k| k.parse::<i32>().expect("error bound")).collect::<Vec<_>>();
+        assert_eq!(bounds.iter().max(), Some(&1000));
+        assert_eq!(bounds.len(), 4);
+        let mut dist = serde_json::json!({"50": 1, "80": 2, "100": 5});
+        tilt(&mut dist, None, 0.0).expect("error tilt");
+        assert_eq!(dist, serde_json::json!({"50": 1, "80": 2, "100": 5}));
+    }
+
+    #[test]
+    pub fn test_variants() {
+        let report = variants::run(VariantsCommand::parse(&vec!["ThunderExpressMath".to_string()]).expect("error parse command")).expect("error variants");
+        let hash = report.variants[0].hash.clone();
+        let args = ["ThunderExpressMath", "--hash", &hash].map(|a| a.to_string());
+        let found = variants::run(VariantsCommand::parse(&args).expect("error parse command")).expect("error resolve");
+        assert_eq!(found.variants[0].id, report.variants[0].id);
+        let args = ["ThunderExpressMath", "--hash", "absent"].map(|a| a.to_string());
+        assert!(variants::run(VariantsCommand::parse(&args).expect("error parse command")).is_err());
+        assert_eq!(sha256_hex("abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(hash, sha256_hex(&format!("{}\n{}", thunder_express::CONFIG_JSON, thunder_express::REELS_JSON)));
+    }
+
+    #[test]
+    pub fn test_exact_games() {
+        for math_class in ["ThunderExpressMath", "BonanzaLink1000Math"] {
+            let command = ExactCommand::parse(&vec![math_class.to_string()]).expect("error parse command");
+            let report = exact::run(command).expect("error exact");
+            assert!(report.rtp > 0.0);
+        }
+    }
+
+    #[test]
+    pub fn test_validate() {
+        for math_class in ["ThunderExpressMath", "BonanzaLink1000Math", "MegaThunderMath"] {
+            let report = validate::run(ValidateCommand::parse(&vec![math_class.to_string()]).expect("error parse command")).expect("error validate");
+            assert!(report.is_valid(), "{report}");
+        }
+        let mut json: serde_json::Value = serde_json::from_str(thunder_express::CONFIG_JSON).expect("error parse config");
+        json["lines"][0] = serde_json::Value::from("VVVVV");
+        json["distOver"] = serde_json::Value::Array(vec![]);
+        json["mapJack"] = serde_json::json!({"2000": "O"});
+        let config: ThunderExpressConfig = serde_json::from_str(&json.to_string()).expect("error parse config");
+        let v = config.validate(&thunder_express::REELS_CFG, &thunder_express::CFG.settings());
+        let kinds = v.issues.iter().map(|i| i.kind).collect::<Vec<_>>();
+        for kind in [IssueKind::LineRow, IssueKind::DistCount, IssueKind::EmptyDist, IssueKind::JackpotCoin] {
+            assert!(kinds.contains(&kind), "{v}");
+        }
+        assert!(v.into_result().is_err());
+        let mut json: serde_json::Value = serde_json::from_str(thunder_express::CONFIG_JSON).expect("error parse config");
+        json["betCounters"] = serde_json::json!([1, 2, 70, 200]);
+        let config: ThunderExpressConfig = serde_json::from_str(&json.to_string()).expect("error parse config");
+        let v = config.validate(&thunder_express::REELS_CFG, &thunder_express::CFG.settings());
+        assert_eq!(v.issues.iter().map(|i| i.kind).collect::<Vec<_>>(), vec![IssueKind::BetCounters], "{v}");
+    }
+
+    #[test]
+    fn test_fsm_command() {
+        let command = FsmCommand::parse(&["LuckyLinesMath".to_string(), "--mermaid".to_string()]).unwrap();
+        let report = fsm::run(command).unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.graph, FsmDefinition::default().to_mermaid());
+    }
+
+    #[test]
+    fn test_main_command() {
+        let args = ["merge", "out.json", "a.json", "--json", "b.json"].map(|a| a.to_string());
+        match MainCommand::parse(&args).unwrap() {
+            MainCommand::Merge {
+                out,
+                inputs,
+                json,
+            } => {
+                assert_eq!(out, "out.json");
+                assert_eq!(inputs, vec!["a.json".to_string(), "b.json".to_string()]);
+                assert!(json);
+            }
+            c => panic!("merge is expected: {c:?}"),
+        }
+        assert!(MainCommand::parse(&["merge".to_string()]).is_err());
+        let command = MainCommand::parse(&["ThunderExpressMath".to_string(), "--rounds".to_string(), "10".to_string()]).unwrap();
+        assert!(matches!(command, MainCommand::Sim(SimCommand { ref math_class, .. }) if math_class == "ThunderExpressMath"));
+        assert!(!command.json());
+    }
+
+    #[test]
+    fn test_resim_command() {
+        let json = r#"{
+            "cols": 3,
+            "rows": 3,
+            "reels": [["AABK", "ABBK", "AKBK"]],
+            "evaluator": "LINES",
+            "lines": ["---", "^^^", "___"],
+            "wins": {"A": {"3": 5}, "B": {"3": 10}, "K": {"3": 2}},
+            "maxWin": 1000
+        }"#;
+        let seeds = FairSeeds::new(None).unwrap().next_round();
+        let seed = Seed::Fair {
+            key: seeds.key().unwrap(),
+            stream: 0,
+        };
+        let record = RoundRecord {
+            id: 1,
+            common_id: 1,
+            config_hash: Some(config_hash(json, None)),
+            seed: seed.head() as i64,
+            bet: 1,
+            line: 3,
+            denom: 1,
+            bet_counter: 1,
+            multi: 1,
+            stake: 3,
+            fair_hash: Some(seeds.server_hash.clone()),
+            client_seed: Some(seeds.client_seed.clone()),
+            nonce: Some(seeds.nonce as i64),
+            step_win: Some(Step::default().win),
+            step_take: Some(Step::default().take as i32),
+            ..Default::default()
+        };
+        let command = |config: &str, server_seed: Option<&str>| ResimCommand {
+            math_class: "ConfigurableSlotMath".to_string(),
+            round: record.clone(),
+            config: Some(config.to_string()),
+            server_seed: server_seed.map(|s| s.to_string()),
+            json: false,
+        };
+
+        // the round is keyed by the server seed of the command when it is the committed one
+        let report = resim::run(command(json, Some(&seeds.server_seed))).unwrap();
+        assert!(report.is_exact(), "{report}");
+        assert!(resim::run(command(json, None)).is_err());
+        assert!(resim::run(command(json, Some("forged"))).is_err());
+        // the definition of the command is the config of the round
+        let other = json.replace("\"maxWin\": 1000", "\"maxWin\": 500");
+        assert!(resim::run(command(&other, Some(&seeds.server_seed))).is_err());
+    }
+
+    #[test]
+    fn test_rng_check() {
+        // the distributions of the config and the windows of the reels, the wins are skipped
+        let dists = find_dists(thunder_express::CONFIG_JSON, Some(thunder_express::REELS_JSON)).unwrap();
+        let names = dists.iter().map(|d| d.0.as_str()).collect::<Vec<_>>();
+        assert!(names.contains(&"/distCoin/0"));
+        assert!(names.contains(&"reels:/0/2"));
+        assert!(names.iter().all(|n| n.starts_with("reels:") || n.contains("dist")));
+
+        let selected = dists.into_iter().filter(|d| d.0 == "/distCoin/0" || d.0 == "reels:/0/2").collect::<Vec<_>>();
+        let results = check(CounterSource::from_seed(7), 20_000, 1e-6, &[2, 10], &selected).unwrap();
+        assert_eq!(results.len(), 2 * 4 + 1 + 2 * 2);
+        assert!(results.iter().all(|r| r.passed), "{results:?}");
+
+        let args = ["ThunderExpressMath", "--source", "ChaChaSource", "--seed", "3", "--samples", "20000", "--ranges", "2,6", "--alpha", "0.000001"].map(String::from);
+        let report = rng_check::run(RngCheckCommand::parse(&args).unwrap()).unwrap();
+        assert_eq!(report.seed, 3);
+        assert!(report.results.iter().any(|r| r.target == "range 6"));
+        assert!(report.is_valid(), "{report}");
+        let args = ["ConfigurableSlotMath"].map(String::from);
+        assert!(rng_check::run(RngCheckCommand::parse(&args).unwrap()).is_err());
+        let args = ["ThunderExpressMath", "--ranges", "1"].map(String::from);
+        assert!(RngCheckCommand::parse(&args).is_err());
+    }
+}
@@ -0,0 +1,7 @@
+use fugaso_sim::cli::main_with;
+use fugaso_sim::{run_main, MainCommand};
+
+/// `fugaso_sim <math class> [options]` or `fugaso_sim merge <out> <checkpoint>...`
+fn main() {
+    main_with(MainCommand::parse, run_main)
+}
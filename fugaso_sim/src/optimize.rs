@@ -0,0 +1,380 @@
+use crate::cli::{Command, Report};
+use crate::exact::{self, ExactCommand};
+use crate::options::Options;
+use crate::SimCommand;
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use fugaso_math::simulate::{ParallelArg, SimulationArg};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt::{Display, Formatter};
+
+/// distribution of the config to tune by the json pointer, `/distCoin/0` for the config or `reels:/0/2@K` for a column of the reels file,
+/// the symbol ranks the windows of a column by its count, the numeric values are ranked by themselves
+#[derive(Debug, Clone)]
+pub struct Tunable {
+    pub reels: bool,
+    pub pointer: String,
+    pub symbol: Option<char>,
+}
+
+impl Tunable {
+    pub fn parse(s: &str) -> Result<Self, ServerError> {
+        let (reels, rest) = match s.strip_prefix("reels:") {
+            Some(r) => (true, r),
+            None => (false, s),
+        };
+        let (pointer, symbol) = match rest.split_once('@') {
+            Some((p, sym)) => {
+                let mut chars = sym.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => (p, Some(c)),
+                    _ => return Err(err_on!(format!("illegal symbol of {s}!"))),
+                }
+            }
+            None => (rest, None),
+        };
+        if !pointer.starts_with('/') {
+            return Err(err_on!(format!("illegal pointer of {s}!")));
+        }
+        Ok(Self {
+            reels,
+            pointer: pointer.to_string(),
+            symbol,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    Sim,
+    Exact,
+}
+
+#[derive(Debug)]
+pub struct OptimizeCommand {
+    pub math_class: String,
+    pub config: String,
+    pub reels_cfg: Option<String>,
+    pub target: f64,
+    pub tolerance: f64,
+    pub tunables: Vec<Tunable>,
+    pub objective: Objective,
+    pub category: usize,
+    pub rounds: u64,
+    pub seed: u64,
+    pub threads: usize,
+    /// the tilt is searched in `[-range, range]`
+    pub range: f64,
+    pub iterations: usize,
+    pub output: Option<String>,
+    pub output_reels: Option<String>,
+    pub json: bool,
+}
+
+impl OptimizeCommand {
+    pub fn parse(args: &[String]) -> Result<Self, ServerError> {
+        let options = Options::parse(
+            args,
+            &[
+                "--config",
+                "--reels",
+                "--target",
+                "--tolerance",
+                "--tune",
+                "--objective",
+                "--category",
+                "--rounds",
+                "--seed",
+                "--threads",
+                "--range",
+                "--iterations",
+                "--output",
+                "--output-reels",
+            ],
+            &["--json"],
+        )?;
+        let tune: String = options.value("--tune")?.ok_or_else(|| err_on!("--tune is absent!"))?;
+        let objective = match options.value_or("--objective", "sim".to_string())?.as_str() {
+            "sim" => Objective::Sim,
+            "exact" => Objective::Exact,
+            o => return Err(err_on!(format!("unknown objective {o}!"))),
+        };
+        Ok(Self {
+            config: options.file("--config")?.ok_or_else(|| err_on!("--config is absent!"))?,
+            reels_cfg: options.file("--reels")?,
+            target: options.value("--target")?.ok_or_else(|| err_on!("--target is absent!"))?,
+            tolerance: options.value_or("--tolerance", 0.001)?,
+            tunables: tune.split(',').map(Tunable::parse).collect::<Result<Vec<_>, _>>()?,
+            objective,
+            category: options.value_or("--category", 0)?,
+            rounds: options.value_or("--rounds", 1_000_000)?,
+            seed: options.value_or("--seed", 1)?,
+            threads: options.value_or("--threads", std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1))?,
+            range: options.value_or("--range", 3.0)?,
+            iterations: options.value_or("--iterations", 30)?,
+            output: options.value("--output")?,
+            output_reels: options.value("--output-reels")?,
+            json: options.flag("--json"),
+            math_class: options.math_class,
+        })
+    }
+}
+
+impl Command for OptimizeCommand {
+    fn json(&self) -> bool {
+        self.json
+    }
+}
+
+fn score(value: &Value, symbol: Option<char>) -> Result<f64, ServerError> {
+    match symbol {
+        Some(s) => {
+            let window = value.as_array().ok_or_else(|| err_on!(format!("value {value} is not a window!")))?;
+            Ok(window.iter().filter(|v| v.as_str().and_then(|c| c.chars().next()) == Some(s)).count() as f64)
+        }
+        None => value.as_f64().ok_or_else(|| err_on!(format!("value {value} is not numeric, a symbol is needed!"))),
+    }
+}
+
+/// integer weights of `weights` with the sum `total` exactly, every weight keeps at least 1,
+/// the rounding gap goes to the largest remainders and the excess of the raised weights is taken from the largest weights
+fn apportion(weights: &[f64], total: i32) -> Result<Vec<i32>, ServerError> {
+    if weights.len() as i32 > total {
+        return Err(err_on!(format!("{} weights do not fit the total {total}!", weights.len())));
+    }
+    let mut result = weights.iter().map(|w| std::cmp::max(1, w.floor() as i32)).collect::<Vec<_>>();
+    let mut order = (0..weights.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| (weights[*b] - weights[*b].floor()).total_cmp(&(weights[*a] - weights[*a].floor())));
+    // the floors lose less than 1 by a weight, so the gap is shorter than the weights
+    let gap = total - result.iter().sum::<i32>();
+    for i in order.into_iter().take(std::cmp::max(0, gap) as usize) {
+        result[i] += 1;
+    }
+    let mut diff = total - result.iter().sum::<i32>();
+    while diff < 0 {
+        let i = (0..result.len()).max_by_key(|i| result[*i]).ok_or_else(|| err_on!("weights are absent!"))?;
+        result[i] -= 1;
+        diff += 1;
+    }
+    Ok(result)
+}
+
+/// scales the weights by `exp(t * rank)` keeping the total weight exactly, the ranks of the values are in `[-1, 1]`
+pub fn tilt(dist: &mut Value, symbol: Option<char>, t: f64) -> Result<(), ServerError> {
+    let map = dist.as_object().ok_or_else(|| err_on!("distribution is not an object!"))?;
+    let mut entries = map
+        .iter()
+        .map(|(k, v)| k.parse::<i32>().map(|b| (b, v.clone())).map_err(|e| err_on!(format!("{k}: {e}"))))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|e| e.0);
+    let mut prev = 0;
+    let mut weights = vec![];
+    for (bound, _) in entries.iter() {
+        if *bound <= prev {
+            return Err(err_on!(format!("illegal weight bound {bound}!")));
+        }
+        weights.push((*bound - prev) as f64);
+        prev = *bound;
+    }
+    let total = prev as f64;
+    let scores = entries.iter().map(|(_, v)| score(v, symbol)).collect::<Result<Vec<_>, _>>()?;
+    let (min, max) = scores.iter().fold((f64::MAX, f64::MIN), |(lo, hi), s| (lo.min(*s), hi.max(*s)));
+    let ranks = scores
+        .iter()
+        .map(|s| {
+            if max > min {
+                2.0 * (s - min) / (max - min) - 1.0
+            } else {
+                0.0
+            }
+        })
+        .collect::<Vec<_>>();
+    let tilted = weights.iter().zip(ranks.iter()).map(|(w, r)| w * (t * r).exp()).collect::<Vec<_>>();
+    let scale = total / tilted.iter().sum::<f64>();
+
+    let scaled = tilted.iter().map(|w| w * scale).collect::<Vec<_>>();
+
+    let mut bound = 0;
+    let mut result = Map::new();
+    for (w, (_, value)) in apportion(&scaled, prev)?.into_iter().zip(entries.into_iter()) {
+        bound += w;
+        result.insert(bound.to_string(), value);
+    }
+    *dist = Value::Object(result);
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizeReport {
+    pub target: f64,
+    pub rtp: f64,
+    pub tilt: f64,
+    pub evaluations: usize,
+    pub config: Value,
+    pub reels_cfg: Option<Value>,
+}
+
+impl Display for OptimizeReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "target:      {:.4}%", self.target * 100.0)?;
+        writeln!(f, "rtp:         {:.4}%", self.rtp * 100.0)?;
+        writeln!(f, "tilt:        {:.6}", self.tilt)?;
+        write!(f, "evaluations: {}", self.evaluations)
+    }
+}
+
+impl Report for OptimizeReport {}
+
+struct Optimizer<'a> {
+    command: &'a OptimizeCommand,
+    config: Value,
+    reels_cfg: Option<Value>,
+    evaluations: usize,
+}
+
+impl<'a> Optimizer<'a> {
+    fn new(command: &'a OptimizeCommand) -> Result<Self, ServerError> {
+        let config = serde_json::from_str(&command.config).map_err(|e| err_on!(e))?;
+        let reels_cfg = command.reels_cfg.as_ref().map(|r| serde_json::from_str(r).map_err(|e| err_on!(e))).transpose()?;
+        Ok(Self {
+            command,
+            config,
+            reels_cfg,
+            evaluations: 0,
+        })
+    }
+
+    fn apply(&self, t: f64) -> Result<(Value, Option<Value>), ServerError> {
+        let mut config = self.config.clone();
+        let mut reels_cfg = self.reels_cfg.clone();
+        for tunable in self.command.tunables.iter() {
+            let root = if tunable.reels {
+                reels_cfg.as_mut().ok_or_else(|| err_on!("reels file is absent!"))?
+            } else {
+                &mut config
+            };
+            let dist = root.pointer_mut(&tunable.pointer).ok_or_else(|| err_on!(format!("{} is absent!", tunable.pointer)))?;
+            tilt(dist, tunable.symbol, t)?;
+        }
+        Ok((config, reels_cfg))
+    }
+
+    fn evaluate(&mut self, config: &Value, reels_cfg: &Option<Value>) -> Result<f64, ServerError> {
+        self.evaluations += 1;
+        let command = self.command;
+        let config = Some(config.to_string());
+        let reels_cfg = reels_cfg.as_ref().map(|r| r.to_string());
+        let rtp = match command.objective {
+            Objective::Sim => {
+                let sim = SimCommand {
+                    math_class: command.math_class.clone(),
+                    config,
+                    reels_cfg,
+                    json: false,
+                    arg: SimulationArg {
+                        game_name: command.math_class.clone(),
+                        rounds: command.rounds,
+                        bet: 1,
+                        line: 0,
+                        denom: 1,
+                        bet_counter: 0,
+                        stop_factor: 0,
+                    },
+                    parallel: ParallelArg {
+                        threads: command.threads,
+                        seed: command.seed,
+                        chunk: 100_000,
+                        checkpoint: None,
+                    },
+                };
+                crate::run(sim)?.rtp
+            }
+            Objective::Exact => {
+                let exact = ExactCommand {
+                    math_class: command.math_class.clone(),
+                    config,
+                    reels_cfg,
+                    category: command.category,
+                    stake: None,
+                    json: false,
+                };
+                exact::run(exact)?.rtp
+            }
+        };
+        Ok(rtp)
+    }
+
+    fn rtp_at(&mut self, t: f64) -> Result<(f64, Value, Option<Value>), ServerError> {
+        let (config, reels_cfg) = self.apply(t)?;
+        let rtp = self.evaluate(&config, &reels_cfg)?;
+        info!("tilt: {t} rtp: {rtp}");
+        Ok((rtp, config, reels_cfg))
+    }
+
+    /// bisection of the one tilt of every tunable on the target, the same seed is used for every evaluation,
+    /// but the tilt changes the symbols drawn by it, so the rtp is not guaranteed to be monotone in the tilt:
+    /// the search fails when the rtp of a midpoint leaves the rtps of its bracket or the iterations end out of the tolerance
+    fn run(mut self) -> Result<OptimizeReport, ServerError> {
+        let target = self.command.target;
+        let tolerance = self.command.tolerance;
+        let (mut lo, mut hi) = (-self.command.range, self.command.range);
+        let (mut rtp_lo, ..) = self.rtp_at(lo)?;
+        let (mut rtp_hi, ..) = self.rtp_at(hi)?;
+        if (rtp_lo - target) * (rtp_hi - target) > 0.0 {
+            return Err(err_on!(format!("target {target} is out of rtp {rtp_lo}..{rtp_hi} of the range!")));
+        }
+        let rising = rtp_hi >= rtp_lo;
+        let mut best: Option<(f64, f64, Value, Option<Value>)> = None;
+        for _ in 0..self.command.iterations {
+            let t = (lo + hi) / 2.0;
+            let (rtp, config, reels_cfg) = self.rtp_at(t)?;
+            if best.as_ref().map(|b| (rtp - target).abs() < (b.1 - target).abs()).unwrap_or(true) {
+                best = Some((t, rtp, config, reels_cfg));
+            }
+            if (rtp - target).abs() <= tolerance {
+                break;
+            }
+            if rtp < rtp_lo.min(rtp_hi) || rtp > rtp_lo.max(rtp_hi) {
+                return Err(err_on!(format!("rtp {rtp} of tilt {t} is out of rtp {rtp_lo}..{rtp_hi} of the bracket {lo}..{hi}, the rtp is not monotone!")));
+            }
+            if (rtp < target) == rising {
+                lo = t;
+                rtp_lo = rtp;
+            } else {
+                hi = t;
+                rtp_hi = rtp;
+            }
+        }
+        let (t, rtp, config, reels_cfg) = best.ok_or_else(|| err_on!("iterations are absent!"))?;
+        if (rtp - target).abs() > tolerance {
+            return Err(err_on!(format!("bisection does not converge in {} iterations, the closest rtp {rtp} of tilt {t} is out of the tolerance!", self.command.iterations)));
+        }
+        Ok(OptimizeReport {
+            target,
+            rtp,
+            tilt: t,
+            evaluations: self.evaluations,
+            config,
+            reels_cfg,
+        })
+    }
+}
+
+fn write(path: &str, value: &Value) -> Result<(), ServerError> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| err_on!(e))?;
+    std::fs::write(path, json).map_err(|e| err_on!(format!("{path}: {e}")))
+}
+
+pub fn run(command: OptimizeCommand) -> Result<OptimizeReport, ServerError> {
+    let report = Optimizer::new(&command)?.run()?;
+    if let Some(path) = command.output.as_ref() {
+        write(path, &report.config)?;
+    }
+    if let (Some(path), Some(reels_cfg)) = (command.output_reels.as_ref(), report.reels_cfg.as_ref()) {
+        write(path, reels_cfg)?;
+    }
+    Ok(report)
+}
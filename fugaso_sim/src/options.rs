@@ -0,0 +1,62 @@
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// command line of the tools: `<math class> [--key value]... [--flag]...`
+#[derive(Debug)]
+pub struct Options {
+    pub math_class: String,
+    values: HashMap<String, String>,
+    flags: HashSet<String>,
+}
+
+impl Options {
+    pub fn parse(args: &[String], keys: &[&str], flags: &[&str]) -> Result<Self, ServerError> {
+        let math_class = args.get(0).cloned().ok_or_else(|| err_on!("math class is absent!"))?;
+        let mut options = Self {
+            math_class,
+            values: HashMap::new(),
+            flags: HashSet::new(),
+        };
+        let mut i = 1;
+        while i < args.len() {
+            let key = args[i].as_str();
+            if flags.contains(&key) {
+                options.flags.insert(key.to_string());
+                i += 1;
+            } else if keys.contains(&key) {
+                let value = args.get(i + 1).ok_or_else(|| err_on!(format!("value of {key} is absent!")))?;
+                options.values.insert(key.to_string(), value.clone());
+                i += 2;
+            } else {
+                return Err(err_on!(format!("unknown argument {key}!")));
+            }
+        }
+        Ok(options)
+    }
+
+    pub fn value<T: FromStr>(&self, key: &str) -> Result<Option<T>, ServerError>
+    where
+        T::Err: Display,
+    {
+        self.values.get(key).map(|v| v.parse::<T>().map_err(|e| err_on!(format!("{key}: {e}")))).transpose()
+    }
+
+    pub fn value_or<T: FromStr>(&self, key: &str, default: T) -> Result<T, ServerError>
+    where
+        T::Err: Display,
+    {
+        Ok(self.value(key)?.unwrap_or(default))
+    }
+
+    /// content of the file passed by the key
+    pub fn file(&self, key: &str) -> Result<Option<String>, ServerError> {
+        self.values.get(key).map(|p| std::fs::read_to_string(p).map_err(|e| err_on!(format!("{p}: {e}")))).transpose()
+    }
+
+    pub fn flag(&self, key: &str) -> bool {
+        self.flags.contains(key)
+    }
+}
@@ -0,0 +1,106 @@
+use crate::cli::{Command, Report};
+use crate::options::Options;
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use fugaso_math::config::{MathConfig, ReelDist};
+use fugaso_math::par::ParSheet;
+use fugaso_math_ed6::config::{bonanza_1000, thunder_express, BonanzaLinkCashConfig, ThunderExpressConfig};
+use fugaso_math_ed7::config::{mega_thunder, MegaThunderConfig};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+/// `<config class> [--config file] [--reels file] [--json]`, the embedded configs are used without the files
+#[derive(Debug)]
+pub struct ParCommand {
+    pub config_class: String,
+    pub config: Option<String>,
+    pub reels_cfg: Option<String>,
+    pub json: bool,
+}
+
+impl ParCommand {
+    pub fn parse(args: &[String]) -> Result<Self, ServerError> {
+        let options = Options::parse(args, &["--config", "--reels"], &["--json"])?;
+        Ok(Self {
+            config: options.file("--config")?,
+            reels_cfg: options.file("--reels")?,
+            json: options.flag("--json"),
+            config_class: options.math_class,
+        })
+    }
+}
+
+impl Command for ParCommand {
+    fn json(&self) -> bool {
+        self.json
+    }
+}
+
+impl Report for ParSheet {}
+
+fn load<T: DeserializeOwned>(json: Option<String>, embedded: &Arc<T>) -> Result<Arc<T>, ServerError> {
+    match json {
+        Some(j) => Ok(Arc::new(serde_json::from_str(&j).map_err(|e| err_on!(e))?)),
+        None => Ok(Arc::clone(embedded)),
+    }
+}
+
+pub fn run(command: ParCommand) -> Result<ParSheet, ServerError> {
+    let name = command.config_class.as_str();
+    if name == stringify!(ThunderExpressConfig) {
+        let config: Arc<ThunderExpressConfig> = load(command.config, &thunder_express::CFG)?;
+        let reels_cfg: Arc<ReelDist> = load(command.reels_cfg, &thunder_express::REELS_CFG)?;
+        let mut sheet = ParSheet::new(name).with_reels(&config.reels).with_reel_dist(&reels_cfg)?.with_paytable(&config.wins).with_lines(&config.lines);
+        sheet.add_dists("distCoin", &config.dist_coin)?;
+        sheet.add_dists("distOver", &config.dist_over)?;
+        if !config.dist_base_category.is_empty() {
+            sheet.add_dist("distBaseCategory", None, &config.dist_base_category)?;
+        }
+        if !config.dist_coin_ultra.is_empty() {
+            sheet.add_dist("distCoinUltra", None, &config.dist_coin_ultra)?;
+        }
+        Ok(sheet)
+    } else if name == stringify!(MegaThunderConfig) {
+        let config: Arc<MegaThunderConfig> = load(command.config, &mega_thunder::CFG)?;
+        let reels_cfg: Arc<ReelDist> = load(command.reels_cfg, &mega_thunder::REELS_CFG)?;
+        let mut sheet = ParSheet::new(name).with_reels(&config.reels).with_reel_dist(&reels_cfg)?.with_paytable(&config.wins).with_lines(&config.lines);
+        sheet.add_chance("distCoin", config.dist_coin)?;
+        sheet.add_dists("distCoinValue", &config.dist_coin_value)?;
+        sheet.add_chance("distJackpot", config.dist_jackpot)?;
+        sheet.add_dists("distJackpotValue", &config.dist_jackpot_value)?;
+        sheet.add_chance("distLift", config.dist_lift)?;
+        sheet.add_dists("distLiftMult", &config.dist_lift_mult)?;
+        sheet.add_dists("distLiftSymbol", &config.dist_lift_symbol)?;
+        sheet.add_dist("distOver", None, &config.dist_over)?;
+        sheet.add_dist("distOverSymbol", None, &config.dist_over_symbol)?;
+        if !config.dist_base_category.is_empty() {
+            sheet.add_dist("distBaseCategory", None, &config.dist_base_category)?;
+        }
+        if !config.dist_coin_ultra.is_empty() {
+            sheet.add_dist("distCoinUltra", None, &config.dist_coin_ultra)?;
+        }
+        Ok(sheet)
+    } else if name == stringify!(BonanzaLinkCashConfig) {
+        let config: Arc<BonanzaLinkCashConfig> = load(command.config, &bonanza_1000::CFG)?;
+        let mut sheet = ParSheet::new(name).with_reels(&config.reels).with_paytable(&config.wins).with_lines(&config.lines);
+        sheet.add_dist("distCoin", None, &config.dist_coin)?;
+        sheet.add_chance("distShift", config.dist_shift)?;
+        sheet.add_chance("distPull", config.dist_pull)?;
+        sheet.add_dist("distOver", None, &config.dist_over)?;
+        sheet.add_dist("distBang", None, &config.dist_bang)?;
+        for (i, chance) in config.dist_wilds.iter().enumerate() {
+            sheet.add_chance(&format!("distWilds[{i}]"), *chance)?;
+        }
+        Ok(sheet)
+    } else if name == stringify!(MathConfig) {
+        let json = command.config.ok_or_else(|| err_on!("config of MathConfig is absent!"))?;
+        let config: MathConfig = serde_json::from_str(&json).map_err(|e| err_on!(e))?;
+        let sheet = ParSheet::new(name).with_reels(&config.reels).with_paytable(&config.wins).with_lines(&config.lines);
+        match command.reels_cfg {
+            Some(r) => sheet.with_reel_dist(&serde_json::from_str(&r).map_err(|e| err_on!(e))?),
+            None => Ok(sheet),
+        }
+    } else {
+        Err(err_on!("config is not supported!"))
+    }
+}
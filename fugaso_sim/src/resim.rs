@@ -0,0 +1,83 @@
+use crate::cli::{Command, Report};
+use crate::options::Options;
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use fugaso_math::config::{config_hash, find_variant_by_hash, ConfigVariant};
+use fugaso_math::configurable::ConfigurableSlotMath;
+use fugaso_math::seed::{resimulate, ResimReport, RoundRecord};
+use fugaso_math_ed6::config::{bonanza_1000, thunder_express};
+use fugaso_math_ed6::math::{BonanzaLink1000Math, ThunderExpressMath};
+use fugaso_math_ed7::config::mega_thunder;
+use fugaso_math_ed7::math::MegaThunderMath;
+
+/// `<math class> --round file [--config file] [--server-seed s] [--json]`, the round is the json of `RoundRecord` with the step of the round,
+/// the revealed server seed of a provably fair round is checked and keys the round which is played again
+#[derive(Debug)]
+pub struct ResimCommand {
+    pub math_class: String,
+    pub round: RoundRecord,
+    pub config: Option<String>,
+    pub server_seed: Option<String>,
+    pub json: bool,
+}
+
+impl ResimCommand {
+    pub fn parse(args: &[String]) -> Result<Self, ServerError> {
+        let options = Options::parse(args, &["--round", "--config", "--server-seed"], &["--json"])?;
+        let round = options.file("--round")?.ok_or_else(|| err_on!("round is absent!"))?;
+        Ok(Self {
+            round: serde_json::from_str(&round).map_err(|e| err_on!(e))?,
+            config: options.file("--config")?,
+            server_seed: options.value("--server-seed")?,
+            json: options.flag("--json"),
+            math_class: options.math_class,
+        })
+    }
+}
+
+impl Command for ResimCommand {
+    fn json(&self) -> bool {
+        self.json
+    }
+}
+
+impl Report for ResimReport {
+    fn passed(&self) -> bool {
+        self.is_exact()
+    }
+}
+
+type VariantHash = fn(&ConfigVariant) -> String;
+
+fn variant<'a>(variants: &'a [ConfigVariant], variant_hash: VariantHash, hash: &str) -> Result<&'a ConfigVariant, ServerError> {
+    find_variant_by_hash(variants, variant_hash, hash).ok_or_else(|| err_on!(format!("config {hash} is absent!")))
+}
+
+/// the math is built from the config of the round which is found by its hash
+pub fn run(command: ResimCommand) -> Result<ResimReport, ServerError> {
+    let mut record = command.round.clone();
+    if let Some(s) = command.server_seed.as_ref() {
+        record.verify_fair(s)?;
+        record.server_seed = Some(s.clone());
+    }
+    let record = &record;
+    let hash = record.config_hash.as_deref().ok_or_else(|| err_on!(format!("config hash of round {} is none!", record.id)))?;
+    if command.math_class == stringify!(ThunderExpressMath) {
+        let v = variant(&thunder_express::VARIANTS, thunder_express::variant_hash, hash)?;
+        resimulate(ThunderExpressMath::new(v.config(), v.reels())?, record)
+    } else if command.math_class == stringify!(BonanzaLink1000Math) {
+        let v = variant(&bonanza_1000::VARIANTS, bonanza_1000::variant_hash, hash)?;
+        resimulate(BonanzaLink1000Math::new(v.config())?, record)
+    } else if command.math_class == stringify!(MegaThunderMath) {
+        let v = variant(&mega_thunder::VARIANTS, mega_thunder::variant_hash, hash)?;
+        resimulate(MegaThunderMath::new(v.config(), v.reels())?, record)
+    } else if command.math_class == stringify!(ConfigurableSlotMath) {
+        let json = command.config.as_ref().ok_or_else(|| err_on!("definition of ConfigurableSlotMath is absent!"))?;
+        if config_hash(json, None) != hash {
+            return Err(err_on!(format!("definition is not the config {hash} of the round!")));
+        }
+        resimulate(ConfigurableSlotMath::new(json)?, record)
+    } else {
+        Err(err_on!("game is not supported!"))
+    }
+}
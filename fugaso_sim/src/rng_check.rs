@@ -0,0 +1,185 @@
+use crate::cli::{Command, Report};
+use crate::options::Options;
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use essential_rand::random::RandomGenerator;
+use fugaso_math::configurable::ConfigurableSlotMath;
+use fugaso_math::rng::{ChaChaSource, CounterSource, RngSource};
+use fugaso_math::rng_check::{RngChecker, TestResult};
+use fugaso_math_ed6::config::{bonanza_1000, thunder_express};
+use fugaso_math_ed6::math::{BonanzaLink1000Math, ThunderExpressMath};
+use fugaso_math_ed7::config::mega_thunder;
+use fugaso_math_ed7::math::MegaThunderMath;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+pub const DEFAULT_RANGES: [usize; 6] = [2, 3, 5, 10, 100, 1000];
+
+/// `<math class> [--source RandomGenerator|ChaChaSource|CounterSource] [--seed n] [--samples n] [--ranges 2,10,100] [--alpha p] [--config file] [--reels file] [--json]`,
+/// the numbers of the ranges and the distributions of the config and the reels are tested, the seed is random when it is absent
+#[derive(Debug)]
+pub struct RngCheckCommand {
+    pub math_class: String,
+    pub source: String,
+    pub seed: u64,
+    pub samples: usize,
+    pub ranges: Vec<usize>,
+    pub alpha: f64,
+    pub config: Option<String>,
+    pub reels_cfg: Option<String>,
+    pub json: bool,
+}
+
+impl RngCheckCommand {
+    pub fn parse(args: &[String]) -> Result<Self, ServerError> {
+        let options = Options::parse(args, &["--source", "--seed", "--samples", "--ranges", "--alpha", "--config", "--reels"], &["--json"])?;
+        let ranges = match options.value::<String>("--ranges")? {
+            Some(r) => r.split(',').map(|n| n.trim().parse::<usize>().map_err(|e| err_on!(format!("{n}: {e}")))).collect::<Result<Vec<_>, _>>()?,
+            None => DEFAULT_RANGES.to_vec(),
+        };
+        if ranges.iter().any(|n| *n < 2) {
+            return Err(err_on!("range must be at least 2!"));
+        }
+        Ok(Self {
+            source: options.value_or("--source", stringify!(RandomGenerator).to_string())?,
+            seed: options.value_or("--seed", RandomGenerator::new().random_i64() as u64)?,
+            samples: options.value_or("--samples", 100_000)?,
+            ranges,
+            alpha: options.value_or("--alpha", 0.001)?,
+            config: options.file("--config")?,
+            reels_cfg: options.file("--reels")?,
+            json: options.flag("--json"),
+            math_class: options.math_class,
+        })
+    }
+}
+
+impl Command for RngCheckCommand {
+    fn json(&self) -> bool {
+        self.json
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RngCheckReport {
+    pub math_class: String,
+    pub source: String,
+    pub seed: u64,
+    pub samples: usize,
+    pub alpha: f64,
+    pub results: Vec<TestResult>,
+}
+
+impl RngCheckReport {
+    pub fn is_valid(&self) -> bool {
+        self.results.iter().all(|r| r.passed)
+    }
+}
+
+impl Display for RngCheckReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let failed = self.results.iter().filter(|r| !r.passed).count();
+        write!(f, "{} {} seed {} alpha {}: {} tests, {failed} failed", self.math_class, self.source, self.seed, self.alpha, self.results.len())?;
+        for r in self.results.iter() {
+            write!(f, "\n  {r}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for RngCheckReport {
+    fn passed(&self) -> bool {
+        self.is_valid()
+    }
+}
+
+/// config and reels of the game, the embedded ones for the absent files
+fn game_files(command: &RngCheckCommand) -> Result<(String, Option<String>), ServerError> {
+    let (config, reels_cfg) = if command.math_class == stringify!(ThunderExpressMath) {
+        (Some(thunder_express::CONFIG_JSON), Some(thunder_express::REELS_JSON))
+    } else if command.math_class == stringify!(BonanzaLink1000Math) {
+        (Some(bonanza_1000::CONFIG_JSON), None)
+    } else if command.math_class == stringify!(MegaThunderMath) {
+        (Some(mega_thunder::CONFIG_JSON), Some(mega_thunder::REELS_JSON))
+    } else if command.math_class == stringify!(ConfigurableSlotMath) {
+        (None, None)
+    } else {
+        return Err(err_on!("game is not supported!"));
+    };
+    let config = command.config.as_deref().or(config).ok_or_else(|| err_on!(format!("config of {} is absent!", command.math_class)))?;
+    Ok((config.to_string(), command.reels_cfg.as_deref().or(reels_cfg).map(|r| r.to_string())))
+}
+
+fn as_dist(value: &Value) -> Option<BTreeMap<i32, Value>> {
+    let map = value.as_object().filter(|m| !m.is_empty())?;
+    map.iter().map(|(k, v)| k.parse::<i32>().ok().map(|b| (b, v.clone()))).collect()
+}
+
+/// the objects of the numeric bounds by their json pointers, `in_dist` is set under a key of a distribution,
+/// so the wins and the maps of the numeric keys are skipped
+fn collect_dists(value: &Value, pointer: String, in_dist: bool, dists: &mut Vec<(String, BTreeMap<i32, Value>)>) {
+    if in_dist {
+        if let Some(dist) = as_dist(value) {
+            dists.push((pointer, dist));
+            return;
+        }
+    }
+    match value {
+        Value::Object(map) => map.iter().for_each(|(k, v)| collect_dists(v, format!("{pointer}/{k}"), in_dist || k.to_lowercase().contains("dist"), dists)),
+        Value::Array(values) => values.iter().enumerate().for_each(|(i, v)| collect_dists(v, format!("{pointer}/{i}"), in_dist, dists)),
+        _ => {}
+    }
+}
+
+/// distributions of the config and every distribution of the reels file, the ones of the reels file have the prefix `reels:`
+pub fn find_dists(config: &str, reels_cfg: Option<&str>) -> Result<Vec<(String, BTreeMap<i32, Value>)>, ServerError> {
+    let mut dists = vec![];
+    let config: Value = serde_json::from_str(config).map_err(|e| err_on!(e))?;
+    collect_dists(&config, String::new(), false, &mut dists);
+    if let Some(r) = reels_cfg {
+        let reels: Value = serde_json::from_str(r).map_err(|e| err_on!(e))?;
+        let mut reel_dists = vec![];
+        collect_dists(&reels, String::new(), true, &mut reel_dists);
+        dists.extend(reel_dists.into_iter().map(|(p, d)| (format!("reels:{p}"), d)));
+    }
+    Ok(dists)
+}
+
+/// every test draws from the same stream of the source
+pub fn check<R: RngSource>(mut rand: R, samples: usize, alpha: f64, ranges: &[usize], dists: &[(String, BTreeMap<i32, Value>)]) -> Result<Vec<TestResult>, ServerError> {
+    let mut checker = RngChecker::new(samples, alpha);
+    for n in ranges.iter() {
+        checker.check_range(&mut rand, *n);
+    }
+    checker.check_birthday(&mut rand);
+    for (name, dist) in dists.iter() {
+        checker.check_dist(&mut rand, name, dist).map_err(|e| err_on!(format!("{name}: {e:?}")))?;
+    }
+    Ok(checker.results)
+}
+
+pub fn run(command: RngCheckCommand) -> Result<RngCheckReport, ServerError> {
+    let (config, reels_cfg) = game_files(&command)?;
+    let dists = find_dists(&config, reels_cfg.as_deref())?;
+    let (samples, alpha, ranges) = (command.samples, command.alpha, &command.ranges);
+    let results = if command.source == stringify!(RandomGenerator) {
+        check(<RandomGenerator as RngSource>::from_seed(command.seed), samples, alpha, ranges, &dists)?
+    } else if command.source == stringify!(ChaChaSource) {
+        check(ChaChaSource::from_seed(command.seed), samples, alpha, ranges, &dists)?
+    } else if command.source == stringify!(CounterSource) {
+        check(CounterSource::from_seed(command.seed), samples, alpha, ranges, &dists)?
+    } else {
+        return Err(err_on!(format!("unknown source {}!", command.source)));
+    };
+    Ok(RngCheckReport {
+        math_class: command.math_class,
+        source: command.source,
+        seed: command.seed,
+        samples,
+        alpha,
+        results,
+    })
+}
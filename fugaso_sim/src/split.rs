@@ -0,0 +1,128 @@
+use fugaso_data::fugaso_action::ActionKind;
+use fugaso_math::protocol::{SpinData, StartInfo};
+use fugaso_math::simulate::{Feature, FeatureSplit, FeatureWins};
+use fugaso_math_ed6::config::{thunder_express, ThunderExpressConfig};
+use fugaso_math_ed6::protocol::{BonanzaLink1000Info, ThunderExpressInfo};
+use fugaso_math_ed7::config::mega_thunder;
+use fugaso_math_ed7::protocol::MegaThunderInfo;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+fn add(features: &mut FeatureWins, feature: Feature, amount: i64) {
+    if amount != 0 {
+        *features.entry(feature).or_insert(0) += amount;
+    }
+}
+
+/// jackpot coins of a respin are added to every collect, so their part is kept until the bonus pays out
+pub struct ThunderExpressSplit {
+    jackpots: HashSet<char>,
+    pending: i64,
+}
+
+impl ThunderExpressSplit {
+    pub fn new(config: Arc<ThunderExpressConfig>) -> Self {
+        Self {
+            jackpots: config.map_jack.values().cloned().collect(),
+            pending: 0,
+        }
+    }
+}
+
+impl FeatureSplit<ThunderExpressInfo, StartInfo> for ThunderExpressSplit {
+    fn split(&mut self, kind: &ActionKind, data: &SpinData<ThunderExpressInfo, StartInfo>, features: &mut FeatureWins) {
+        match kind {
+            ActionKind::RESPIN => {
+                let grid = &data.result.grid;
+                if let Some(special) = data.result.special.as_ref() {
+                    let collects = grid.iter().flat_map(|c| c.iter().filter(|v| **v == thunder_express::SYM_COLLECT)).count() as i64;
+                    let jackpot = grid
+                        .iter()
+                        .enumerate()
+                        .flat_map(|(c, col)| col.iter().enumerate().map(move |(r, v)| (c, r, *v)))
+                        .filter(|(_, _, v)| self.jackpots.contains(v))
+                        .map(|(c, r, _)| special.mults.get(c).and_then(|m| m.get(r)).cloned().unwrap_or(0) as i64)
+                        .sum::<i64>();
+                    self.pending += jackpot * collects * data.curr_bet as i64 * data.curr_denom as i64 * data.round_multiplier as i64;
+                }
+                let amount = data.result.gains.iter().map(|g| g.amount).sum::<i64>();
+                if amount > 0 {
+                    let jackpot = std::cmp::min(self.pending, amount);
+                    add(features, Feature::Jackpot, jackpot);
+                    add(features, Feature::Respin, amount - jackpot);
+                    self.pending = 0;
+                }
+            }
+            _ => {
+                self.pending = 0;
+                for gain in data.result.gains.iter() {
+                    let feature = if gain.symbol == thunder_express::SYM_COLLECT {
+                        Feature::Collect
+                    } else {
+                        Feature::Line
+                    };
+                    add(features, feature, gain.amount);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MegaThunderSplit;
+
+impl MegaThunderSplit {
+    /// part of the coin gains which is paid by jackpot symbols, taken from the columns of the gain
+    fn jackpot_part(data: &SpinData<MegaThunderInfo, StartInfo>, special: &MegaThunderInfo, column: Option<usize>) -> i64 {
+        let grid = data.result.grid.iter().enumerate().filter(|(c, _)| column.map(|n| n == *c).unwrap_or(true));
+        grid.flat_map(|(c, col)| col.iter().enumerate().map(move |(r, v)| (c, r, *v)))
+            .filter(|(_, _, v)| *v == mega_thunder::SYM_JACKPOT)
+            .map(|(c, r, _)| {
+                let value = special.mults.get(c).and_then(|m| m.get(r)).cloned().unwrap_or(0) as i64;
+                let lift = special.lifts.get(c).and_then(|m| m.get(r)).cloned().unwrap_or(1) as i64;
+                value * lift * data.curr_bet as i64 * data.curr_denom as i64
+            })
+            .sum::<i64>()
+    }
+}
+
+impl FeatureSplit<MegaThunderInfo, StartInfo> for MegaThunderSplit {
+    fn split(&mut self, kind: &ActionKind, data: &SpinData<MegaThunderInfo, StartInfo>, features: &mut FeatureWins) {
+        let coin_feature = if *kind == ActionKind::RESPIN {
+            Feature::Respin
+        } else {
+            Feature::Collect
+        };
+        for gain in data.result.gains.iter() {
+            if gain.symbol == mega_thunder::SYM_GRAND_JACKPOT {
+                add(features, Feature::Jackpot, gain.amount);
+            } else if gain.symbol == mega_thunder::SYM_COIN || gain.symbol == mega_thunder::SYM_COIN_COLUMN {
+                let column = if gain.symbol == mega_thunder::SYM_COIN_COLUMN {
+                    Some(gain.line_num)
+                } else {
+                    None
+                };
+                let jackpot = data.result.special.as_ref().map(|s| Self::jackpot_part(data, s, column)).unwrap_or(0);
+                let jackpot = std::cmp::min(jackpot, gain.amount);
+                add(features, Feature::Jackpot, jackpot);
+                add(features, coin_feature, gain.amount - jackpot);
+            } else {
+                add(features, Feature::Line, gain.amount);
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct BonanzaLink1000Split;
+
+impl FeatureSplit<BonanzaLink1000Info, StartInfo> for BonanzaLink1000Split {
+    fn split(&mut self, kind: &ActionKind, data: &SpinData<BonanzaLink1000Info, StartInfo>, features: &mut FeatureWins) {
+        let feature = match kind {
+            ActionKind::RESPIN => Feature::Respin,
+            ActionKind::FREE_SPIN => Feature::Free,
+            _ => Feature::Line,
+        };
+        add(features, feature, data.result.gains.iter().map(|g| g.amount).sum::<i64>());
+    }
+}
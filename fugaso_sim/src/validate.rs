@@ -0,0 +1,117 @@
+use crate::cli::{Command, Report};
+use crate::options::Options;
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use fugaso_math::config::ReelDist;
+use fugaso_math::config_validator::ConfigValidator;
+use fugaso_math_ed6::config::{bonanza_1000, thunder_express, BonanzaLinkCashConfig, ThunderExpressConfig};
+use fugaso_math_ed7::config::{mega_thunder, MegaThunderConfig};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// `<math class> [--config file] [--reels file] [--json]`, every variant of the game is validated without the files
+#[derive(Debug)]
+pub struct ValidateCommand {
+    pub math_class: String,
+    pub config: Option<String>,
+    pub reels_cfg: Option<String>,
+    pub json: bool,
+}
+
+impl ValidateCommand {
+    pub fn parse(args: &[String]) -> Result<Self, ServerError> {
+        let options = Options::parse(args, &["--config", "--reels"], &["--json"])?;
+        Ok(Self {
+            config: options.file("--config")?,
+            reels_cfg: options.file("--reels")?,
+            json: options.flag("--json"),
+            math_class: options.math_class,
+        })
+    }
+}
+
+impl Command for ValidateCommand {
+    fn json(&self) -> bool {
+        self.json
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateReport {
+    pub math_class: String,
+    pub configs: Vec<ConfigValidator>,
+}
+
+impl ValidateReport {
+    pub fn is_valid(&self) -> bool {
+        self.configs.iter().all(|c| c.is_valid())
+    }
+}
+
+impl Display for ValidateReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.math_class)?;
+        for c in self.configs.iter() {
+            write!(f, "\n{c}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for ValidateReport {
+    fn passed(&self) -> bool {
+        self.is_valid()
+    }
+}
+
+/// the files are validated with the embedded config or reels for the absent one
+fn validate_files(command: &ValidateCommand) -> Result<ConfigValidator, ServerError> {
+    let config = command.config.as_deref();
+    let reels_cfg = command.reels_cfg.as_deref();
+    let mut v = ConfigValidator::new("file");
+    if command.math_class == stringify!(ThunderExpressMath) {
+        let config = v.parse::<ThunderExpressConfig>("config", config.unwrap_or(thunder_express::CONFIG_JSON));
+        let reels_cfg = v.parse::<ReelDist>("reels", reels_cfg.unwrap_or(thunder_express::REELS_JSON));
+        if let (Some(config), Some(reels_cfg)) = (config, reels_cfg) {
+            v.merge(config.validate(&reels_cfg, &thunder_express::CFG.settings()));
+        }
+    } else if command.math_class == stringify!(BonanzaLink1000Math) {
+        if let Some(config) = v.parse::<BonanzaLinkCashConfig>("config", config.unwrap_or(bonanza_1000::CONFIG_JSON)) {
+            v.merge(config.validate(&bonanza_1000::CFG.settings()));
+        }
+    } else if command.math_class == stringify!(MegaThunderMath) {
+        let config = v.parse::<MegaThunderConfig>("config", config.unwrap_or(mega_thunder::CONFIG_JSON));
+        let reels_cfg = v.parse::<ReelDist>("reels", reels_cfg.unwrap_or(mega_thunder::REELS_JSON));
+        if let (Some(config), Some(reels_cfg)) = (config, reels_cfg) {
+            v.merge(config.validate(&reels_cfg, &mega_thunder::CFG.settings()));
+        }
+    } else {
+        return Err(err_on!("game is not supported!"));
+    }
+    Ok(v)
+}
+
+fn validate_variants(math_class: &str) -> Result<Vec<ConfigValidator>, ServerError> {
+    if math_class == stringify!(ThunderExpressMath) {
+        Ok(thunder_express::VARIANTS.iter().map(thunder_express::validate).collect())
+    } else if math_class == stringify!(BonanzaLink1000Math) {
+        Ok(bonanza_1000::VARIANTS.iter().map(bonanza_1000::validate).collect())
+    } else if math_class == stringify!(MegaThunderMath) {
+        Ok(mega_thunder::VARIANTS.iter().map(mega_thunder::validate).collect())
+    } else {
+        Err(err_on!("game is not supported!"))
+    }
+}
+
+pub fn run(command: ValidateCommand) -> Result<ValidateReport, ServerError> {
+    let configs = if command.config.is_some() || command.reels_cfg.is_some() {
+        vec![validate_files(&command)?]
+    } else {
+        validate_variants(&command.math_class)?
+    };
+    Ok(ValidateReport {
+        math_class: command.math_class,
+        configs,
+    })
+}
@@ -0,0 +1,102 @@
+use crate::cli::{Command, Report};
+use crate::options::Options;
+use essential_core::err_on;
+use essential_core::error::ServerError;
+use fugaso_math::config::{find_variant_by_hash, ConfigVariant};
+use fugaso_math_ed6::config::{bonanza_1000, thunder_express};
+use fugaso_math_ed7::config::mega_thunder;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// `<math class> [--hash hash] [--output file] [--json]`, the config of the variant with the hash of a round is written to the output
+#[derive(Debug)]
+pub struct VariantsCommand {
+    pub math_class: String,
+    pub hash: Option<String>,
+    pub output: Option<String>,
+    pub json: bool,
+}
+
+impl VariantsCommand {
+    pub fn parse(args: &[String]) -> Result<Self, ServerError> {
+        let options = Options::parse(args, &["--hash", "--output"], &["--json"])?;
+        Ok(Self {
+            hash: options.value("--hash")?,
+            output: options.value("--output")?,
+            json: options.flag("--json"),
+            math_class: options.math_class,
+        })
+    }
+}
+
+impl Command for VariantsCommand {
+    fn json(&self) -> bool {
+        self.json
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantInfo {
+    pub id: String,
+    pub percent: i32,
+    pub active: bool,
+    pub hash: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantsReport {
+    pub math_class: String,
+    pub variants: Vec<VariantInfo>,
+}
+
+impl Display for VariantsReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.math_class)?;
+        for v in self.variants.iter() {
+            write!(f, "\n  {:<24} {:>4}% {:<8} {}", v.id, v.percent, if v.active { "active" } else { "retired" }, v.hash)?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for VariantsReport {}
+
+type VariantHash = fn(&ConfigVariant) -> String;
+
+/// variants of the game with the embedded configs for the absent ones
+fn game_variants(math_class: &str) -> Result<(&'static [ConfigVariant], VariantHash, &'static str), ServerError> {
+    if math_class == stringify!(ThunderExpressMath) {
+        Ok((&thunder_express::VARIANTS, thunder_express::variant_hash, thunder_express::CONFIG_JSON))
+    } else if math_class == stringify!(BonanzaLink1000Math) {
+        Ok((&bonanza_1000::VARIANTS, bonanza_1000::variant_hash, bonanza_1000::CONFIG_JSON))
+    } else if math_class == stringify!(MegaThunderMath) {
+        Ok((&mega_thunder::VARIANTS, mega_thunder::variant_hash, mega_thunder::CONFIG_JSON))
+    } else {
+        Err(err_on!("game is not supported!"))
+    }
+}
+
+pub fn run(command: VariantsCommand) -> Result<VariantsReport, ServerError> {
+    let (variants, variant_hash, config) = game_variants(&command.math_class)?;
+    let selected = match command.hash.as_ref() {
+        Some(h) => vec![*find_variant_by_hash(variants, variant_hash, h).ok_or_else(|| err_on!(format!("config {h} is absent!")))?],
+        None => variants.to_vec(),
+    };
+    if let (Some(path), Some(v)) = (command.output.as_ref(), command.hash.as_ref().and(selected.first())) {
+        std::fs::write(path, v.config.unwrap_or(config)).map_err(|e| err_on!(format!("{path}: {e}")))?;
+    }
+    Ok(VariantsReport {
+        math_class: command.math_class,
+        variants: selected
+            .iter()
+            .map(|v| VariantInfo {
+                id: v.id.to_string(),
+                percent: v.percent,
+                active: v.active,
+                hash: variant_hash(v),
+            })
+            .collect(),
+    })
+}
@@ -1,5 +1,5 @@
 use sea_orm::{ConnectionTrait, DbBackend, DbConn, Schema};
-use fugaso_data::{common_round, fugaso_game};
+use fugaso_data::{common_round, fugaso_action, fugaso_fair_seed, fugaso_game, fugaso_round};
 
 pub async fn setup_schema_fugaso_game(db: &DbConn) {
     let schema = Schema::new(DbBackend::Sqlite);
@@ -15,4 +15,28 @@ pub async fn setup_schema_common_round(db: &DbConn) {
 
     db.execute(db.get_database_backend().build(&create_common_round))
         .await.expect("error create common_round");
+}
+
+pub async fn setup_schema_fugaso_round(db: &DbConn) {
+    let schema = Schema::new(DbBackend::Sqlite);
+    let create_fugaso_round = schema.create_table_from_entity(fugaso_round::Entity);
+
+    db.execute(db.get_database_backend().build(&create_fugaso_round))
+        .await.expect("error create fugaso_round");
+}
+
+pub async fn setup_schema_fugaso_action(db: &DbConn) {
+    let schema = Schema::new(DbBackend::Sqlite);
+    let create_fugaso_action = schema.create_table_from_entity(fugaso_action::Entity);
+
+    db.execute(db.get_database_backend().build(&create_fugaso_action))
+        .await.expect("error create fugaso_action");
+}
+
+pub async fn setup_schema_fugaso_fair_seed(db: &DbConn) {
+    let schema = Schema::new(DbBackend::Sqlite);
+    let create_fugaso_fair_seed = schema.create_table_from_entity(fugaso_fair_seed::Entity);
+
+    db.execute(db.get_database_backend().build(&create_fugaso_fair_seed))
+        .await.expect("error create fugaso_fair_seed");
 }
\ No newline at end of file